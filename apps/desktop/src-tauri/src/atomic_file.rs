@@ -0,0 +1,26 @@
+use std::path::Path;
+
+use anyhow::Context;
+use anyhow::Result;
+
+/// File name prefix of the temporary files [`write_atomically`] leaves
+/// behind when interrupted.
+pub const TEMP_FILE_PREFIX: &str = ".tmp-";
+
+/// Replace `path` with `bytes` through a temporary file in the same
+/// directory, so a crash mid-write leaves the previous contents intact.
+pub fn write_atomically(path: &Path, bytes: &[u8]) -> Result<()> {
+    let parent = path
+        .parent()
+        .with_context(|| format!("{} has no parent directory", path.display()))?;
+    std::fs::create_dir_all(parent)
+        .with_context(|| format!("Failed to create {}", parent.display()))?;
+
+    let temp = parent.join(format!("{}{}", TEMP_FILE_PREFIX, uuid::Uuid::new_v4()));
+    std::fs::write(&temp, bytes).with_context(|| format!("Failed to write {}", temp.display()))?;
+    if let Err(err) = std::fs::rename(&temp, path) {
+        let _ = std::fs::remove_file(&temp);
+        return Err(err).with_context(|| format!("Failed to replace {}", path.display()));
+    }
+    Ok(())
+}
@@ -1,21 +1,17 @@
 use std::collections::HashMap;
 use std::path::Path;
 use std::path::PathBuf;
+use std::sync::Arc;
 
-use codex_core::Cursor;
-use codex_core::INTERACTIVE_SESSION_SOURCES;
-use codex_core::RolloutRecorder;
-use codex_core::SessionMeta;
+use codex_core::CodexConversation;
 use codex_core::config::Config;
 use codex_core::config::ConfigOverrides;
-use codex_core::parse_turn_item;
 use codex_protocol::ConversationId;
 use codex_protocol::config_types::ReasoningEffort;
 use codex_protocol::config_types::ReasoningSummary;
 use codex_protocol::config_types::SandboxMode;
-use codex_protocol::items::TurnItem;
-use codex_protocol::models::ResponseItem;
 use codex_protocol::protocol::AskForApproval;
+use codex_protocol::protocol::EventMsg;
 use codex_protocol::protocol::Op;
 use codex_protocol::protocol::SandboxPolicy;
 use codex_protocol::protocol::SessionConfiguredEvent;
@@ -30,7 +26,19 @@ use ts_rs::TS;
 use uuid::Uuid;
 
 use crate::codex_runtime::CodexRuntime;
+use crate::conversation_archive::archive_rollout;
+use crate::conversation_archive::unarchive_rollout;
+use crate::conversation_listing::ConversationListCursor;
+use crate::conversation_listing::ConversationListIndex;
+use crate::conversation_listing::IndexedConversation;
+use crate::conversation_listing::normalized_path_string;
+use crate::conversation_search::ConversationSearchIndex;
+use crate::conversation_search::SearchSnippet;
+use crate::conversation_search::rollout_user_messages;
+use crate::conversation_search::truncate_chars;
 use crate::env;
+use crate::review_drafts::ReviewDrafts;
+use crate::review_snapshots::ConversationSnapshotSummary;
 use crate::workspace_manager::WorkspaceComposerDefaults;
 use crate::workspace_manager::WorkspaceManager;
 
@@ -38,6 +46,9 @@ use super::util::CommandResult;
 
 const DEFAULT_CONVERSATION_LIMIT: usize = 25;
 const MAX_CONVERSATION_LIMIT: usize = 100;
+const DEFAULT_SEARCH_LIMIT: usize = 20;
+const MAX_SEARCH_LIMIT: usize = 100;
+const PREVIEW_MAX_LEN: usize = 80;
 
 /// Row displayed in the conversation list.
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
@@ -51,6 +62,10 @@ pub struct ConversationSummary {
     pub preview: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub timestamp: Option<String>,
+    /// Time of the latest recorded event.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_activity: Option<String>,
+    pub archived: bool,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default, TS)]
@@ -63,6 +78,9 @@ pub struct ListConversationsParams {
     pub limit: Option<usize>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub model_providers: Option<Vec<String>>,
+    /// List archived conversations too; they are hidden by default.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_archived: Option<bool>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
@@ -73,17 +91,37 @@ pub struct ListConversationsResponse {
     pub next_cursor: Option<String>,
 }
 
-fn parse_cursor_token(token: &str) -> CommandResult<Cursor> {
-    serde_json::from_value::<Cursor>(Value::String(token.to_string()))
-        .map_err(|err| format!("Invalid cursor token: {}", err))
+fn parse_cursor_token(token: &str) -> CommandResult<ConversationListCursor> {
+    let (timestamp, conversation_id) = token
+        .rsplit_once('|')
+        .ok_or_else(|| format!("Invalid cursor token: {}", token))?;
+    Ok(ConversationListCursor {
+        timestamp: timestamp.to_string(),
+        conversation_id: conversation_id.to_string(),
+    })
+}
+
+fn serialize_cursor_token(conversation: &IndexedConversation) -> String {
+    format!(
+        "{}|{}",
+        conversation.timestamp.as_deref().unwrap_or_default(),
+        conversation.conversation_id
+    )
 }
 
-fn serialize_cursor_token(cursor: &Cursor) -> CommandResult<String> {
-    serde_json::to_value(cursor)
-        .map_err(|err| format!("Failed to serialize cursor: {}", err))?
-        .as_str()
-        .map(|s| s.to_string())
-        .ok_or_else(|| "Cursor serialization produced invalid value".to_string())
+fn conversation_summary(conversation: IndexedConversation) -> ConversationSummary {
+    ConversationSummary {
+        conversation_id: conversation.conversation_id,
+        path: conversation.path,
+        cwd: conversation.cwd,
+        preview: conversation
+            .first_message
+            .map(|message| truncate_chars(&message, PREVIEW_MAX_LEN))
+            .unwrap_or_default(),
+        timestamp: conversation.timestamp,
+        last_activity: conversation.last_activity,
+        archived: conversation.archived,
+    }
 }
 
 /// List all conversations scoped to the current workspace.
@@ -91,6 +129,7 @@ fn serialize_cursor_token(cursor: &Cursor) -> CommandResult<String> {
 pub async fn list_conversations(
     workspace_manager: State<'_, WorkspaceManager>,
     runtime: State<'_, CodexRuntime>,
+    conversation_index: State<'_, ConversationListIndex>,
     params: ListConversationsParams,
 ) -> CommandResult<ListConversationsResponse> {
     let ListConversationsParams {
@@ -98,6 +137,7 @@ pub async fn list_conversations(
         cursor: cursor_token,
         limit,
         model_providers,
+        include_archived,
     } = params;
 
     let workspace_path = workspace_manager
@@ -110,12 +150,11 @@ pub async fn list_conversations(
     let mut limit = limit.unwrap_or(DEFAULT_CONVERSATION_LIMIT);
     limit = limit.clamp(1, MAX_CONVERSATION_LIMIT);
 
-    let mut cursor: Option<Cursor> = match cursor_token.as_deref() {
+    let cursor = match cursor_token.as_deref() {
         Some(token) => Some(parse_cursor_token(token)?),
         None => None,
     };
     let config = runtime.config();
-    let workspace_cwd = Path::new(&workspace_path);
     let provider_filter = match model_providers {
         Some(providers) => {
             if providers.is_empty() {
@@ -127,74 +166,374 @@ pub async fn list_conversations(
         None => Some(vec![config.model_provider_id.clone()]),
     };
     let fallback_provider = config.model_provider_id.clone();
+    let include_archived = include_archived.unwrap_or(false);
 
-    let mut items: Vec<ConversationSummary> = Vec::new();
-    let mut next_cursor_token: Option<String> = None;
+    let workspace = normalized_path_string(Path::new(&workspace_path));
 
-    // Continue fetching pages until we have enough workspace-matching conversations
-    // or we've exhausted all available pages
-    loop {
-        let mut page = RolloutRecorder::list_conversations(
+    let (page, has_more) = conversation_index
+        .list(
             &config.codex_home,
+            workspace,
+            cursor,
             limit,
-            cursor.as_ref(),
-            INTERACTIVE_SESSION_SOURCES,
-            provider_filter.as_deref(),
-            fallback_provider.as_str(),
+            move |conversation| {
+                let provider = conversation
+                    .model_provider
+                    .as_deref()
+                    .unwrap_or(fallback_provider.as_str());
+                (include_archived || !conversation.archived)
+                    && provider_filter
+                        .as_ref()
+                        .is_none_or(|providers| providers.iter().any(|p| p == provider))
+            },
         )
         .await
         .map_err(|e| format!("Failed to list conversations: {}", e))?;
 
-        log::info!("Found {} conversations in page", page.items.len());
+    let next_cursor = if has_more {
+        page.last().map(serialize_cursor_token)
+    } else {
+        None
+    };
 
-        let mut next_cursor = page.next_cursor.take();
-        let serialized_cursor = match next_cursor.as_ref() {
-            Some(cursor_ref) => Some(serialize_cursor_token(cursor_ref)?),
-            None => None,
-        };
-        // Only update next_cursor_token if we have a cursor (avoids unused assignment warning)
-        if serialized_cursor.is_some() {
-            next_cursor_token = serialized_cursor.clone();
-        }
+    let mut items = Vec::with_capacity(page.len());
+    for conversation in page {
+        workspace_manager
+            .store_active_conversation(
+                conversation.conversation_id.to_string(),
+                conversation.path.clone(),
+                conversation.cwd.clone(),
+            )
+            .await;
+        items.push(conversation_summary(conversation));
+    }
+
+    Ok(ListConversationsResponse { items, next_cursor })
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchConversationsParams {
+    pub query: String,
+    /// Limit results to conversations started in this workspace.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub workspace_path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+    /// Search archived conversations too; they are hidden by default.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_archived: Option<bool>,
+}
+
+/// Conversation matching a search, with the passage that matched best.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct ConversationSearchResult {
+    pub conversation: ConversationSummary,
+    pub score: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snippet: Option<SearchSnippet>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchConversationsResponse {
+    pub items: Vec<ConversationSearchResult>,
+}
 
-        // Filter and collect conversations that match the current workspace
-        for summary in page
-            .items
-            .into_iter()
-            .filter_map(|it| extract_conversation_summary(it.path, &it.head, workspace_cwd))
-        {
-            let conversation_id = summary.conversation_id.to_string();
+/// Search user messages, agent messages and reasoning across all
+/// conversations, best match first.
+#[tauri::command]
+pub async fn search_conversations(
+    workspace_manager: State<'_, WorkspaceManager>,
+    runtime: State<'_, CodexRuntime>,
+    search_index: State<'_, ConversationSearchIndex>,
+    params: SearchConversationsParams,
+) -> CommandResult<SearchConversationsResponse> {
+    let SearchConversationsParams {
+        query,
+        workspace_path,
+        limit,
+        include_archived,
+    } = params;
+
+    let workspace_path = match workspace_path {
+        Some(path) => Some(
             workspace_manager
-                .store_active_conversation(
-                    conversation_id,
-                    summary.path.clone(),
-                    summary.cwd.clone(),
-                )
-                .await;
-            items.push(summary);
-
-            // Stop processing current page if we have enough matches
-            if items.len() >= limit {
-                break;
+                .normalize_workspace_path(&path)
+                .map_err(|e| e.to_string())?,
+        ),
+        None => None,
+    };
+    if !runtime.is_initialized().await {
+        return Err("Runtime not initialized".to_string());
+    }
+
+    let limit = limit
+        .unwrap_or(DEFAULT_SEARCH_LIMIT)
+        .clamp(1, MAX_SEARCH_LIMIT);
+    let config = runtime.config();
+    let include_archived = include_archived.unwrap_or(false);
+    let mut workspace = workspace_path.as_deref().map(WorkspaceMatcher::new);
+    let matches = search_index
+        .search(&config.codex_home, &query, limit, move |conversation| {
+            (include_archived || !conversation.archived)
+                && workspace
+                    .as_mut()
+                    .is_none_or(|workspace| workspace.matches(&conversation.cwd))
+        })
+        .await
+        .map_err(|e| format!("Failed to search conversations: {}", e))?;
+
+    let mut items = Vec::with_capacity(matches.len());
+    for found in matches {
+        let conversation = found.conversation;
+        workspace_manager
+            .store_active_conversation(
+                conversation.conversation_id.to_string(),
+                conversation.path.clone(),
+                conversation.cwd.clone(),
+            )
+            .await;
+        items.push(ConversationSearchResult {
+            conversation: conversation_summary(conversation),
+            score: found.score,
+            snippet: found.snippet,
+        });
+    }
+
+    Ok(SearchConversationsResponse { items })
+}
+
+/// Conversations targeted by a bulk archive or unarchive.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct ConversationBatchParams {
+    pub conversation_ids: Vec<String>,
+}
+
+/// Conversations to delete for good.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteConversationsParams {
+    pub conversation_ids: Vec<String>,
+    /// Must be `true`; deleted conversations cannot be restored.
+    #[serde(default)]
+    pub confirm: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct ConversationBatchFailure {
+    pub conversation_id: String,
+    pub error: String,
+}
+
+/// Outcome of a bulk operation; each conversation succeeds or fails on its
+/// own.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct ConversationBatchResponse {
+    pub succeeded: Vec<String>,
+    pub failed: Vec<ConversationBatchFailure>,
+}
+
+impl ConversationBatchResponse {
+    fn record(&mut self, conversation_id: String, result: CommandResult<()>) {
+        match result {
+            Ok(()) => self.succeeded.push(conversation_id),
+            Err(error) => self.failed.push(ConversationBatchFailure {
+                conversation_id,
+                error,
+            }),
+        }
+    }
+}
+
+/// Move conversations out of the listing. Running conversations are shut
+/// down first.
+#[tauri::command]
+pub async fn archive_conversations(
+    params: ConversationBatchParams,
+    workspace_manager: State<'_, WorkspaceManager>,
+    runtime: State<'_, CodexRuntime>,
+    conversation_index: State<'_, ConversationListIndex>,
+) -> CommandResult<ConversationBatchResponse> {
+    if !runtime.is_initialized().await {
+        return Err("Runtime not initialized".to_string());
+    }
+
+    let codex_home = runtime.config().codex_home.clone();
+    let mut response = ConversationBatchResponse::default();
+    for conversation_id in params.conversation_ids {
+        let result = async {
+            let conversation =
+                indexed_conversation(&conversation_index, &codex_home, &conversation_id).await?;
+            if conversation.archived {
+                return Ok(());
             }
+            close_conversation(&runtime, &workspace_manager, &conversation).await;
+            let task_home = codex_home.clone();
+            let path = conversation.path.clone();
+            let archived = tokio::task::spawn_blocking(move || archive_rollout(&task_home, &path))
+                .await
+                .map_err(|e| format!("Failed to join archive task: {}", e))?
+                .map_err(|e| format!("Failed to archive conversation: {:#}", e))?;
+            sync_listing(
+                &conversation_index,
+                &codex_home,
+                vec![conversation.path, archived],
+            )
+            .await;
+            Ok(())
         }
+        .await;
+        response.record(conversation_id, result);
+    }
+
+    Ok(response)
+}
 
-        // Check if we have enough matches or if there are no more pages
-        if items.len() >= limit || next_cursor.is_none() {
-            break;
+/// Move archived conversations back into the listing.
+#[tauri::command]
+pub async fn unarchive_conversations(
+    params: ConversationBatchParams,
+    runtime: State<'_, CodexRuntime>,
+    conversation_index: State<'_, ConversationListIndex>,
+) -> CommandResult<ConversationBatchResponse> {
+    if !runtime.is_initialized().await {
+        return Err("Runtime not initialized".to_string());
+    }
+
+    let codex_home = runtime.config().codex_home.clone();
+    let mut response = ConversationBatchResponse::default();
+    for conversation_id in params.conversation_ids {
+        let result = async {
+            let conversation =
+                indexed_conversation(&conversation_index, &codex_home, &conversation_id).await?;
+            if !conversation.archived {
+                return Ok(());
+            }
+            let task_home = codex_home.clone();
+            let path = conversation.path.clone();
+            let restored =
+                tokio::task::spawn_blocking(move || unarchive_rollout(&task_home, &path))
+                    .await
+                    .map_err(|e| format!("Failed to join unarchive task: {}", e))?
+                    .map_err(|e| format!("Failed to unarchive conversation: {:#}", e))?;
+            sync_listing(
+                &conversation_index,
+                &codex_home,
+                vec![conversation.path, restored],
+            )
+            .await;
+            Ok(())
         }
+        .await;
+        response.record(conversation_id, result);
+    }
+
+    Ok(response)
+}
 
-        // Continue to next page
-        cursor = next_cursor.take();
+/// Delete conversations along with their review snapshots and comments.
+/// The rollout goes last, so a failed cleanup leaves the conversation listed
+/// and the delete can be retried.
+#[tauri::command]
+pub async fn delete_conversations(
+    params: DeleteConversationsParams,
+    workspace_manager: State<'_, WorkspaceManager>,
+    runtime: State<'_, CodexRuntime>,
+    conversation_index: State<'_, ConversationListIndex>,
+    review_drafts: State<'_, ReviewDrafts>,
+) -> CommandResult<ConversationBatchResponse> {
+    if !params.confirm {
+        return Err("Deleting conversations requires confirmation".to_string());
+    }
+    if !runtime.is_initialized().await {
+        return Err("Runtime not initialized".to_string());
     }
 
-    let next_cursor = if items.len() >= limit {
-        next_cursor_token
-    } else {
-        None
-    };
+    let codex_home = runtime.config().codex_home.clone();
+    let mut response = ConversationBatchResponse::default();
+    for conversation_id in params.conversation_ids {
+        let result = async {
+            let conversation =
+                indexed_conversation(&conversation_index, &codex_home, &conversation_id).await?;
+            close_conversation(&runtime, &workspace_manager, &conversation).await;
+            workspace_manager
+                .discard_review_snapshots(&conversation_id)
+                .await
+                .map_err(|e| format!("Failed to delete review snapshots: {:#}", e))?;
+            review_drafts
+                .discard_conversation(&conversation_id)
+                .await
+                .map_err(|e| format!("Failed to delete review comments: {:#}", e))?;
+            tokio::fs::remove_file(&conversation.path)
+                .await
+                .map_err(|e| format!("Failed to delete conversation: {}", e))?;
+            sync_listing(&conversation_index, &codex_home, vec![conversation.path]).await;
+            Ok(())
+        }
+        .await;
+        response.record(conversation_id, result);
+    }
 
-    Ok(ListConversationsResponse { items, next_cursor })
+    Ok(response)
+}
+
+async fn indexed_conversation(
+    conversation_index: &ConversationListIndex,
+    codex_home: &Path,
+    conversation_id: &str,
+) -> CommandResult<IndexedConversation> {
+    conversation_index
+        .find(codex_home, conversation_id)
+        .await
+        .map_err(|e| format!("Failed to look up conversation: {}", e))?
+        .ok_or_else(|| format!("Unknown conversation: {}", conversation_id))
+}
+
+/// Update the listing after rollouts moved or were deleted. A failure only
+/// leaves it stale until the next reconcile.
+async fn sync_listing(
+    conversation_index: &ConversationListIndex,
+    codex_home: &Path,
+    paths: Vec<PathBuf>,
+) {
+    if let Err(e) = conversation_index.sync_paths(codex_home, paths).await {
+        log::warn!("Failed to update conversation listing: {:#}", e);
+    }
+}
+
+/// Shut down a running conversation and forget its session so nothing
+/// writes to the rollout or its snapshots while it is moved or deleted.
+async fn close_conversation(
+    runtime: &CodexRuntime,
+    workspace_manager: &WorkspaceManager,
+    conversation: &IndexedConversation,
+) {
+    let conversation_id = conversation.conversation_id;
+    // Waits for a turn snapshot still being captured from its events.
+    runtime
+        .event_manager()
+        .unsubscribe_conversation(&conversation_id)
+        .await;
+    if let Some(running) = runtime
+        .conversation_manager()
+        .remove_conversation(&conversation_id)
+        .await
+        && let Err(e) = running.submit(Op::Shutdown).await
+    {
+        log::debug!(
+            "Failed to shut down conversation {}: {}",
+            conversation_id,
+            e
+        );
+    }
+    workspace_manager
+        .remove_active_conversation(&conversation_id.to_string())
+        .await;
 }
 
 /// Parameters for initializing conversation history.
@@ -251,6 +590,24 @@ pub async fn initialize_conversation(
     let conv_id = ConversationId::from_string(&conversation_id)
         .map_err(|e| format!("Invalid conversation ID: {}", e))?;
 
+    // Number later turn snapshots after the user messages already in history.
+    let replayed_prompts: Vec<&str> = session_configured
+        .initial_messages
+        .iter()
+        .flatten()
+        .filter_map(|event| match event {
+            EventMsg::UserMessage(user_message) => Some(user_message.message.as_str()),
+            _ => None,
+        })
+        .collect();
+    session
+        .review_snapshots()
+        .sync_user_turns(
+            replayed_prompts.len() as u32,
+            replayed_prompts.last().copied(),
+        )
+        .await;
+
     // Subscribe to live event stream
     if let Ok(conversation) = runtime
         .conversation_manager()
@@ -422,6 +779,267 @@ pub async fn new_conversation(
     })
 }
 
+/// Parameters for forking a conversation.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct ForkConversationParams {
+    pub conversation_id: String,
+    /// User turns carried over to the fork; the fork starts just before the
+    /// user message of turn `keep_turns + 1`.
+    pub keep_turns: u32,
+}
+
+/// Response returned after forking a conversation.
+#[derive(Serialize, Deserialize, Debug, Clone, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct ForkConversationResponse {
+    pub conversation_id: ConversationId,
+    pub session_configured: SessionConfiguredEvent,
+    pub reasoning_summary: ReasoningSummary,
+    /// The first user message left out of the fork.
+    pub dropped_message: String,
+    /// Set when the workspace was restored to the snapshot matching the fork
+    /// point.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub restored_commit_id: Option<String>,
+    /// Snapshot of the workspace taken before it was restored.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub safety_commit_id: Option<String>,
+}
+
+/// Start a new conversation from the first `keep_turns` user turns of an
+/// existing one. When the source has review snapshots, the fork keeps those
+/// of the kept turns and the workspace is restored to the latest of them once
+/// the fork exists.
+#[tauri::command]
+pub async fn fork_conversation(
+    params: ForkConversationParams,
+    workspace_manager: State<'_, WorkspaceManager>,
+    runtime: State<'_, CodexRuntime>,
+    app_handle: AppHandle,
+) -> CommandResult<ForkConversationResponse> {
+    if !runtime.is_initialized().await {
+        return Err("Runtime not initialized".to_string());
+    }
+
+    fork_session(
+        &runtime,
+        &workspace_manager,
+        app_handle,
+        &params.conversation_id,
+        params.keep_turns,
+        true,
+    )
+    .await
+}
+
+/// Parameters for editing and resending a user message.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct EditUserMessageParams {
+    pub conversation_id: String,
+    /// 1-based user turn whose message is replaced.
+    pub turn_index: u32,
+    pub items: Vec<InputItem>,
+    /// Also restore the workspace to the review snapshot taken before the
+    /// edited turn.
+    #[serde(default)]
+    pub restore_workspace: bool,
+}
+
+/// Replace a user message and everything after it. The conversation is
+/// forked just before the message and the new input is submitted to the
+/// fork, which the response describes; the original stays untouched.
+#[tauri::command]
+pub async fn edit_user_message(
+    params: EditUserMessageParams,
+    workspace_manager: State<'_, WorkspaceManager>,
+    runtime: State<'_, CodexRuntime>,
+    app_handle: AppHandle,
+) -> CommandResult<ForkConversationResponse> {
+    if !runtime.is_initialized().await {
+        return Err("Runtime not initialized".to_string());
+    }
+    if params.items.is_empty() {
+        return Err("Edited message is empty".to_string());
+    }
+    let keep_turns = params
+        .turn_index
+        .checked_sub(1)
+        .ok_or_else(|| "Turn index starts at 1".to_string())?;
+
+    let forked = fork_session(
+        &runtime,
+        &workspace_manager,
+        app_handle.clone(),
+        &params.conversation_id,
+        keep_turns,
+        params.restore_workspace,
+    )
+    .await?;
+
+    let conversation_id = forked.conversation_id.to_string();
+    let conversation = subscribed_conversation(&runtime, app_handle, &conversation_id).await?;
+    snapshot_external_changes(&workspace_manager, &conversation_id).await;
+    conversation
+        .submit(Op::UserInput {
+            items: params.items.into_iter().map(core_user_input).collect(),
+        })
+        .await
+        .map_err(|e| format!("Failed to submit edited message: {}", e))?;
+
+    Ok(forked)
+}
+
+async fn fork_session(
+    runtime: &CodexRuntime,
+    workspace_manager: &WorkspaceManager,
+    app_handle: AppHandle,
+    conversation_id: &str,
+    keep_turns: u32,
+    restore_workspace: bool,
+) -> CommandResult<ForkConversationResponse> {
+    let source = workspace_manager
+        .get_active_conversation(conversation_id)
+        .await
+        .ok_or_else(|| format!("Unknown conversation: {}", conversation_id))?;
+
+    let rollout_path = source.rollout_path.clone();
+    let user_messages = tokio::task::spawn_blocking(move || rollout_user_messages(&rollout_path))
+        .await
+        .map_err(|e| format!("Failed to join rollout read: {}", e))?
+        .map_err(|e| format!("Failed to read conversation history: {}", e))?;
+    // Codex forks before the user message at this index, so one must exist.
+    let Some(dropped_message) = user_messages.get(keep_turns as usize).cloned() else {
+        return Err(format!(
+            "Conversation has {} user turns; cannot fork after turn {}",
+            user_messages.len(),
+            keep_turns
+        ));
+    };
+
+    let snapshots = source.review_snapshots();
+    let restore_to = if restore_workspace {
+        snapshot_after_turn(snapshots.snapshot_summary().await, keep_turns)
+    } else {
+        None
+    };
+
+    let mut config = runtime.config().as_ref().clone();
+    let fallback_env = config.shell_environment_policy.r#set.clone();
+    config.cwd = source.cwd.clone();
+    let env_vars = source.workspace_environment(&fallback_env).await;
+    config.shell_environment_policy.r#set = env_vars.clone();
+    let reasoning_summary = config.model_reasoning_summary;
+
+    let forked = runtime
+        .conversation_manager()
+        .fork_conversation(keep_turns as usize, config, source.rollout_path.clone())
+        .await
+        .map_err(|e| format!("Failed to fork conversation: {}", e))?;
+    let conversation_id = forked.conversation_id;
+
+    // Only touch the workspace once the fork exists, so a failed fork leaves
+    // it as it was.
+    let mut restored_commit_id = None;
+    let mut safety_commit_id = None;
+    if let Some(event_id) = restore_to {
+        match snapshots.restore_snapshot(event_id.as_deref()).await {
+            Ok(restored) => {
+                restored_commit_id = Some(restored.restored_commit);
+                safety_commit_id = Some(restored.safety_commit);
+            }
+            Err(err) => {
+                if let Some(fork) = runtime
+                    .conversation_manager()
+                    .remove_conversation(&conversation_id)
+                    .await
+                    && let Err(e) = fork.submit(Op::Shutdown).await
+                {
+                    log::debug!(
+                        "Failed to shut down fork {} after a failed restore: {}",
+                        conversation_id,
+                        e
+                    );
+                }
+                return Err(format!("Failed to restore workspace for fork: {:#}", err));
+            }
+        }
+    }
+
+    let conversation_id_str = conversation_id.to_string();
+    let session = workspace_manager
+        .store_active_conversation(
+            conversation_id_str.clone(),
+            forked.session_configured.rollout_path.clone(),
+            source.cwd.clone(),
+        )
+        .await;
+    session.set_environment_cache(env_vars).await;
+    session
+        .review_snapshots()
+        .inherit_history(&snapshots, keep_turns)
+        .await;
+
+    let kept_prompt = (keep_turns as usize)
+        .checked_sub(1)
+        .and_then(|index| user_messages.get(index));
+    session
+        .review_snapshots()
+        .sync_user_turns(keep_turns, kept_prompt.map(String::as_str))
+        .await;
+    if let Err(err) = session.review_snapshots().ensure_base().await {
+        log::debug!(
+            "Failed to capture baseline snapshot for conversation {}: {}",
+            conversation_id_str,
+            err
+        );
+    }
+
+    if let Ok(conversation) = runtime
+        .conversation_manager()
+        .get_conversation(conversation_id)
+        .await
+    {
+        let _ = runtime
+            .event_manager()
+            .subscribe(
+                conversation_id,
+                conversation,
+                app_handle,
+                conversation_id_str,
+            )
+            .await;
+    }
+
+    Ok(ForkConversationResponse {
+        conversation_id,
+        session_configured: forked.session_configured,
+        reasoning_summary,
+        dropped_message,
+        restored_commit_id,
+        safety_commit_id,
+    })
+}
+
+/// Snapshot holding the workspace as it was after `keep_turns` user turns:
+/// the latest one recorded for those turns, or the base (`Some(None)`) when
+/// none was. `None` when the conversation has no usable snapshots.
+fn snapshot_after_turn(
+    summary: ConversationSnapshotSummary,
+    keep_turns: u32,
+) -> Option<Option<String>> {
+    if summary.disabled || summary.base_commit.is_none() {
+        return None;
+    }
+    let event_id = summary
+        .turns
+        .into_iter()
+        .rfind(|turn| turn.turn_index <= keep_turns)
+        .map(|turn| turn.event_id);
+    Some(event_id)
+}
+
 /// Wire representation of user-provided inputs.
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
 #[serde(rename_all = "camelCase")]
@@ -462,6 +1080,7 @@ pub struct SendUserMessageParams {
 pub async fn send_user_message(
     params: SendUserMessageParams,
     runtime: State<'_, CodexRuntime>,
+    workspace_manager: State<'_, WorkspaceManager>,
     app_handle: AppHandle,
 ) -> CommandResult<()> {
     if !runtime.is_initialized().await {
@@ -478,33 +1097,9 @@ pub async fn send_user_message(
         approval_policy,
     } = params;
 
-    let conv_id = ConversationId::from_string(&conversation_id)
-        .map_err(|e| format!("Invalid conversation ID: {}", e))?;
-
-    let conversation = runtime
-        .conversation_manager()
-        .get_conversation(conv_id)
-        .await
-        .map_err(|_| format!("Conversation not found: {}", conversation_id))?;
-
-    let _ = runtime
-        .event_manager()
-        .subscribe(
-            conv_id,
-            conversation.clone(),
-            app_handle,
-            conversation_id.clone(),
-        )
-        .await;
+    let conversation = subscribed_conversation(&runtime, app_handle, &conversation_id).await?;
 
-    let mapped_items: Vec<CoreUserInput> = items
-        .into_iter()
-        .map(|item| match item {
-            InputItem::Text { text } => CoreUserInput::Text { text },
-            InputItem::Image { image_url } => CoreUserInput::Image { image_url },
-            InputItem::LocalImage { path } => CoreUserInput::LocalImage { path },
-        })
-        .collect();
+    let mapped_items: Vec<CoreUserInput> = items.into_iter().map(core_user_input).collect();
 
     let sandbox_policy = sandbox.map(sandbox_mode_to_policy);
     let effort_override = reasoning_effort.map(Some);
@@ -528,6 +1123,8 @@ pub async fn send_user_message(
             .map_err(|e| format!("Failed to apply turn overrides: {}", e))?;
     }
 
+    snapshot_external_changes(&workspace_manager, &conversation_id).await;
+
     conversation
         .submit(Op::UserInput {
             items: mapped_items,
@@ -538,6 +1135,70 @@ pub async fn send_user_message(
     Ok(())
 }
 
+fn core_user_input(item: InputItem) -> CoreUserInput {
+    match item {
+        InputItem::Text { text } => CoreUserInput::Text { text },
+        InputItem::Image { image_url } => CoreUserInput::Image { image_url },
+        InputItem::LocalImage { path } => CoreUserInput::LocalImage { path },
+    }
+}
+
+/// Snapshot the workspace before a turn starts so edits made outside the
+/// agent get their own review entry. Failures only cost that separation and
+/// never block the message.
+pub(crate) async fn snapshot_external_changes(
+    workspace_manager: &WorkspaceManager,
+    conversation_id: &str,
+) {
+    let Some(session) = workspace_manager
+        .get_active_conversation(conversation_id)
+        .await
+    else {
+        return;
+    };
+
+    let snapshots = session.review_snapshots();
+    let result = match snapshots.ensure_base().await {
+        Ok(()) => snapshots.record_external_changes().await.map(|_| ()),
+        Err(err) => Err(err),
+    };
+    if let Err(err) = result {
+        log::debug!(
+            "Failed to snapshot changes before turn for conversation {}: {}",
+            conversation_id,
+            err
+        );
+    }
+}
+
+/// Look up a live conversation and make sure its events stream to the renderer.
+pub(crate) async fn subscribed_conversation(
+    runtime: &CodexRuntime,
+    app_handle: AppHandle,
+    conversation_id: &str,
+) -> CommandResult<Arc<CodexConversation>> {
+    let conv_id = ConversationId::from_string(conversation_id)
+        .map_err(|e| format!("Invalid conversation ID: {}", e))?;
+
+    let conversation = runtime
+        .conversation_manager()
+        .get_conversation(conv_id)
+        .await
+        .map_err(|_| format!("Conversation not found: {}", conversation_id))?;
+
+    let _ = runtime
+        .event_manager()
+        .subscribe(
+            conv_id,
+            conversation.clone(),
+            app_handle,
+            conversation_id.to_string(),
+        )
+        .await;
+
+    Ok(conversation)
+}
+
 /// Parameters accepted when compacting a conversation.
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
 #[serde(rename_all = "camelCase")]
@@ -840,6 +1501,47 @@ mod tests {
         assert_eq!(options.sandbox, Some(SandboxMode::WorkspaceWrite));
         assert_eq!(options.approval_policy, Some(AskForApproval::OnFailure));
     }
+
+    #[test]
+    fn snapshot_after_turn_picks_latest_snapshot_of_kept_turns() {
+        use crate::review_snapshots::SnapshotOrigin;
+        use crate::review_snapshots::TurnSnapshotSummary;
+
+        let turn = |event_id: &str, turn_index: u32, origin: SnapshotOrigin| TurnSnapshotSummary {
+            event_id: event_id.to_string(),
+            commit_id: format!("commit-{}", event_id),
+            turn_index,
+            captured_at: chrono::Utc::now(),
+            prompt: None,
+            stats: None,
+            origin,
+        };
+        let summary = ConversationSnapshotSummary {
+            disabled: false,
+            last_error: None,
+            base_commit: Some("base".to_string()),
+            turns: vec![
+                turn("turn-1", 1, SnapshotOrigin::Turn),
+                turn("revert-1", 1, SnapshotOrigin::Revert),
+                turn("turn-3", 3, SnapshotOrigin::Turn),
+            ],
+        };
+
+        assert_eq!(snapshot_after_turn(summary.clone(), 0), Some(None));
+        assert_eq!(
+            snapshot_after_turn(summary.clone(), 2),
+            Some(Some("revert-1".to_string()))
+        );
+        assert_eq!(
+            snapshot_after_turn(summary.clone(), 3),
+            Some(Some("turn-3".to_string()))
+        );
+        let disabled = ConversationSnapshotSummary {
+            disabled: true,
+            ..summary
+        };
+        assert_eq!(snapshot_after_turn(disabled, 1), None);
+    }
 }
 
 fn sandbox_mode_to_policy(mode: SandboxMode) -> SandboxPolicy {
@@ -850,75 +1552,27 @@ fn sandbox_mode_to_policy(mode: SandboxMode) -> SandboxPolicy {
     }
 }
 
-fn normalized_path_string(path: &Path) -> String {
-    let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
-    path_to_normalized_string(&canonical)
-}
-
-#[cfg(not(windows))]
-fn path_to_normalized_string(path: &Path) -> String {
-    path.to_string_lossy().into_owned()
+/// Compares conversation cwds with a workspace, normalizing each distinct
+/// cwd once since many conversations share one.
+struct WorkspaceMatcher {
+    workspace: String,
+    seen: HashMap<PathBuf, bool>,
 }
 
-#[cfg(windows)]
-fn path_to_normalized_string(path: &Path) -> String {
-    let mut s = path.to_string_lossy().into_owned();
-    if s.starts_with(r"\\?\") {
-        s = s[4..].to_string();
+impl WorkspaceMatcher {
+    fn new(workspace_path: &str) -> Self {
+        Self {
+            workspace: normalized_path_string(Path::new(workspace_path)),
+            seen: HashMap::new(),
+        }
     }
-    s = s.replace('/', "\\");
-    s.make_ascii_lowercase();
-    s
-}
 
-fn normalized_path_match(a: &Path, b: &Path) -> bool {
-    normalized_path_string(a) == normalized_path_string(b)
-}
-
-fn extract_conversation_summary(
-    path: PathBuf,
-    head: &[serde_json::Value],
-    workspace_cwd: &Path,
-) -> Option<ConversationSummary> {
-    let session_meta = match head.first() {
-        Some(first_line) => serde_json::from_value::<SessionMeta>(first_line.clone()).ok()?,
-        None => return None,
-    };
-
-    if !normalized_path_match(&session_meta.cwd, workspace_cwd) {
-        return None;
+    fn matches(&mut self, cwd: &Path) -> bool {
+        if let Some(&matches) = self.seen.get(cwd) {
+            return matches;
+        }
+        let matches = normalized_path_string(cwd) == self.workspace;
+        self.seen.insert(cwd.to_path_buf(), matches);
+        matches
     }
-
-    let preview: String = head
-        .iter()
-        .filter_map(|value| serde_json::from_value::<ResponseItem>(value.clone()).ok())
-        .find_map(|item| match parse_turn_item(&item) {
-            Some(TurnItem::UserMessage(user)) => Some(user.message()),
-            _ => None,
-        })?;
-
-    const PREVIEW_MAX_LEN: usize = 80;
-    let preview = if preview.chars().count() > PREVIEW_MAX_LEN {
-        // Truncate at character boundary, not byte boundary
-        let truncate_at = preview
-            .char_indices()
-            .nth(PREVIEW_MAX_LEN)
-            .map(|(idx, _)| idx)
-            .unwrap_or(preview.len());
-        format!("{}…", &preview[..truncate_at])
-    } else {
-        preview
-    };
-
-    let conversation_id = session_meta.id;
-    let timestamp = session_meta.timestamp;
-    let cwd = session_meta.cwd;
-
-    Some(ConversationSummary {
-        conversation_id,
-        path,
-        cwd,
-        preview,
-        timestamp: Some(timestamp),
-    })
 }
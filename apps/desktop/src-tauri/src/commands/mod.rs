@@ -17,6 +17,22 @@ codex_command_descriptors! {
         params: conversations::ListConversationsParams,
         result: conversations::ListConversationsResponse,
     },
+    conversations::search_conversations {
+        params: conversations::SearchConversationsParams,
+        result: conversations::SearchConversationsResponse,
+    },
+    conversations::archive_conversations {
+        params: conversations::ConversationBatchParams,
+        result: conversations::ConversationBatchResponse,
+    },
+    conversations::unarchive_conversations {
+        params: conversations::ConversationBatchParams,
+        result: conversations::ConversationBatchResponse,
+    },
+    conversations::delete_conversations {
+        params: conversations::DeleteConversationsParams,
+        result: conversations::ConversationBatchResponse,
+    },
     conversations::initialize_conversation {
         params: conversations::InitializeConversationParams,
         result: conversations::InitializeConversationResponse,
@@ -25,6 +41,14 @@ codex_command_descriptors! {
         params: conversations::NewConversationCommandParams,
         result: conversations::NewConversationResponse,
     },
+    conversations::fork_conversation {
+        params: conversations::ForkConversationParams,
+        result: conversations::ForkConversationResponse,
+    },
+    conversations::edit_user_message {
+        params: conversations::EditUserMessageParams,
+        result: conversations::ForkConversationResponse,
+    },
     conversations::send_user_message {
         params: conversations::SendUserMessageParams,
         result: (),
@@ -33,10 +57,78 @@ codex_command_descriptors! {
         params: review::GetTurnDiffRangeParams,
         result: review::GetTurnDiffRangeResponse,
     },
+    review::list_turn_diff_files {
+        params: review::ListTurnDiffFilesParams,
+        result: review::ListTurnDiffFilesResponse,
+    },
+    review::get_turn_diff_files {
+        params: review::GetTurnDiffFilesParams,
+        result: review::GetTurnDiffFilesResponse,
+    },
     review::list_turn_snapshots {
         params: review::ListTurnSnapshotsParams,
         result: review::ListTurnSnapshotsResponse,
     },
+    review::reenable_turn_snapshots {
+        params: review::ReenableTurnSnapshotsParams,
+        result: review::ListTurnSnapshotsResponse,
+    },
+    review::restore_turn_snapshot {
+        params: review::RestoreTurnSnapshotParams,
+        result: review::RestoreTurnSnapshotResponse,
+    },
+    review::get_file_at_snapshot {
+        params: review::GetFileAtSnapshotParams,
+        result: review::GetFileAtSnapshotResponse,
+    },
+    review::get_turn_blame {
+        params: review::GetTurnBlameParams,
+        result: review::GetTurnBlameResponse,
+    },
+    review::get_snapshot_retention {
+        params: (),
+        result: review::SnapshotRetentionSettings,
+    },
+    review::set_snapshot_retention {
+        params: review::SnapshotRetentionSettings,
+        result: (),
+    },
+    review::prune_review_snapshots {
+        params: review::PruneReviewSnapshotsParams,
+        result: crate::review_retention::SnapshotPruneSummary,
+    },
+    review::revert_turn_changes {
+        params: review::RevertTurnChangesParams,
+        result: review::RevertTurnChangesResponse,
+    },
+    review::export_turn_patch {
+        params: review::ExportTurnPatchParams,
+        result: review::ExportTurnPatchResponse,
+    },
+    review::create_turn_branch {
+        params: review::CreateTurnBranchParams,
+        result: review::CreateTurnBranchResponse,
+    },
+    review::add_review_comment {
+        params: review::AddReviewCommentParams,
+        result: crate::review_drafts::ReviewComment,
+    },
+    review::update_review_comment {
+        params: review::UpdateReviewCommentParams,
+        result: crate::review_drafts::ReviewComment,
+    },
+    review::delete_review_comment {
+        params: review::DeleteReviewCommentParams,
+        result: (),
+    },
+    review::list_review_comments {
+        params: review::ListReviewCommentsParams,
+        result: review::ListReviewCommentsResponse,
+    },
+    review::submit_turn_review {
+        params: review::SubmitTurnReviewParams,
+        result: review::SubmitTurnReviewResponse,
+    },
     conversations::compact_conversation {
         params: conversations::CompactConversationParams,
         result: (),
@@ -69,6 +161,14 @@ codex_command_descriptors! {
         params: workspace::WorkspacePathParams,
         result: crate::workspace_manager::WorkspaceComposerDefaults,
     },
+    workspace::get_workspace_snapshot_settings {
+        params: workspace::WorkspacePathParams,
+        result: crate::review_capture::SnapshotCaptureSettings,
+    },
+    workspace::set_workspace_snapshot_settings {
+        params: workspace::SetWorkspaceSnapshotSettingsParams,
+        result: (),
+    },
     workspace::list_recent_workspaces {
         params: (),
         result: Vec<String>,
@@ -1,30 +1,156 @@
-use std::process::Command;
+use std::path::PathBuf;
 
-use anyhow::Context;
-use anyhow::Result as AnyResult;
+use codex_protocol::protocol::Op;
+use codex_protocol::user_input::UserInput as CoreUserInput;
 use serde::Deserialize;
 use serde::Serialize;
+use tauri::AppHandle;
 use tauri::State;
 use ts_rs::TS;
 
 use crate::codex_runtime::CodexRuntime;
+use crate::review_blame::FileTurnBlame;
+use crate::review_blame::blame_file;
+use crate::review_diff::DiffFile;
+use crate::review_diff::DiffFileSummary;
+use crate::review_diff::ReviewDiffOptions;
+use crate::review_diff::SnapshotFile;
+use crate::review_diff::SnapshotSource;
+use crate::review_diff::diff_range;
+use crate::review_diff::diff_selected_files;
+use crate::review_diff::list_changed_files;
+use crate::review_diff::snapshot_file;
+use crate::review_drafts::NewReviewComment;
+use crate::review_drafts::ReviewComment;
+use crate::review_drafts::ReviewCommentSide;
+use crate::review_drafts::ReviewDrafts;
+use crate::review_drafts::ReviewSubmission;
+use crate::review_drafts::build_review_message;
+use crate::review_export;
+use crate::review_export::ExportedTurnCommit;
+use crate::review_export::default_branch_name;
+use crate::review_export::write_patch_series;
+use crate::review_retention::SnapshotPruneSummary;
+use crate::review_retention::retention_duration;
+use crate::review_revert::RevertConflict;
+use crate::review_revert::RevertSelection;
+use crate::review_revert::revert_changes;
+use crate::review_snapshots::ConversationSnapshotSummary;
+use crate::review_snapshots::SnapshotExportRange;
+use crate::review_snapshots::SnapshotOrigin;
+use crate::review_snapshots::SnapshotRef;
+use crate::review_snapshots::TurnChangeStats;
 use crate::workspace_manager::WorkspaceManager;
 
+use super::conversations::snapshot_external_changes;
+use super::conversations::subscribed_conversation;
 use super::util::CommandResult;
 
+/// The two sides of a review comparison in a conversation.
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
 #[serde(rename_all = "camelCase")]
-pub struct GetTurnDiffRangeParams {
+pub struct TurnDiffRange {
     pub conversation_id: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub base_event_id: Option<String>,
-    pub target_event_id: String,
+    /// Git revision to compare from instead of a snapshot, e.g. `main` or a
+    /// commit SHA. At most one of `base_event_id` and `base_ref` may be set;
+    /// the conversation base is used when neither is.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base_ref: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target_event_id: Option<String>,
+    /// Git revision to compare against instead of a turn snapshot. Exactly
+    /// one of `target_event_id` and `target_ref` must be set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target_ref: Option<String>,
+}
+
+impl TurnDiffRange {
+    /// Resolve the snapshot storage and commit ids the range compares.
+    async fn resolve(
+        &self,
+        workspace_manager: &WorkspaceManager,
+    ) -> CommandResult<(SnapshotSource, String, String)> {
+        let base = match (self.base_event_id.as_deref(), self.base_ref.as_deref()) {
+            (Some(_), Some(_)) => {
+                return Err("At most one of baseEventId and baseRef may be set".to_string());
+            }
+            (event_id, git_ref) => SnapshotRef::from_parts(event_id, git_ref),
+        };
+        let target = match (self.target_event_id.as_deref(), self.target_ref.as_deref()) {
+            (Some(event_id), None) => SnapshotRef::Turn(event_id),
+            (None, Some(git_ref)) => SnapshotRef::Git(git_ref),
+            _ => return Err("Exactly one of targetEventId and targetRef is required".to_string()),
+        };
+        resolve_snapshot_range(workspace_manager, &self.conversation_id, base, target).await
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct GetTurnDiffRangeParams {
+    #[serde(flatten)]
+    pub range: TurnDiffRange,
+    #[serde(default)]
+    #[ts(optional, as = "Option<ReviewDiffOptions>")]
+    pub options: ReviewDiffOptions,
+    /// Also return the raw `git diff` text, e.g. to copy the range as a patch.
+    #[serde(default)]
+    #[ts(optional, as = "Option<bool>")]
+    pub include_unified_diff: bool,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
 #[serde(rename_all = "camelCase")]
 pub struct GetTurnDiffRangeResponse {
-    pub unified_diff: String,
+    /// The diff parsed into files, hunks and numbered lines. Files over the
+    /// size caps are marked `collapsed` and carry no hunks.
+    pub files: Vec<DiffFile>,
+    /// Raw diff text, present only when requested.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub unified_diff: Option<String>,
+}
+
+/// Parameters accepted when listing the files changed in a snapshot range.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct ListTurnDiffFilesParams {
+    #[serde(flatten)]
+    pub range: TurnDiffRange,
+    #[serde(default)]
+    #[ts(optional, as = "Option<ReviewDiffOptions>")]
+    pub options: ReviewDiffOptions,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct ListTurnDiffFilesResponse {
+    pub files: Vec<DiffFileSummary>,
+}
+
+/// Parameters accepted when fetching hunks for specific files in a range.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct GetTurnDiffFilesParams {
+    #[serde(flatten)]
+    pub range: TurnDiffRange,
+    /// Paths to fetch; either side of a rename matches.
+    pub paths: Vec<String>,
+    /// Return hunks for files that would otherwise be collapsed.
+    #[serde(default)]
+    pub expand_collapsed: bool,
+    /// Should match the options the file list was fetched with.
+    #[serde(default)]
+    #[ts(optional, as = "Option<ReviewDiffOptions>")]
+    pub options: ReviewDiffOptions,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct GetTurnDiffFilesResponse {
+    pub files: Vec<DiffFile>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
@@ -33,22 +159,75 @@ pub struct ListTurnSnapshotsParams {
     pub conversation_id: String,
 }
 
+/// Turn snapshot entry, listed in capture order.
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
 #[serde(rename_all = "camelCase")]
 pub struct TurnSnapshotDescriptor {
     pub event_id: String,
     pub commit_id: String,
+    /// 1-based user turn that produced the snapshot.
+    pub turn_index: u32,
+    pub captured_at: String,
+    /// User prompt that started the turn, shortened for display.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompt: Option<String>,
+    /// Changes relative to the previous snapshot.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stats: Option<TurnChangeStats>,
+    pub origin: SnapshotOrigin,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
 #[serde(rename_all = "camelCase")]
 pub struct ListTurnSnapshotsResponse {
     pub disabled: bool,
+    /// Why the most recent snapshot capture failed, if it did.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub base_commit_id: Option<String>,
     pub snapshots: Vec<TurnSnapshotDescriptor>,
 }
 
+/// Parameters accepted when rolling the workspace back to a snapshot.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoreTurnSnapshotParams {
+    pub conversation_id: String,
+    /// Turn snapshot to restore; the conversation base is used when omitted.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event_id: Option<String>,
+}
+
+/// Response returned after restoring the workspace.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoreTurnSnapshotResponse {
+    pub restored_commit_id: String,
+    /// Snapshot of the workspace as it was right before the restore.
+    pub safety_commit_id: String,
+}
+
+/// Resolve the snapshot storage and commit ids for a review range.
+async fn resolve_snapshot_range(
+    workspace_manager: &WorkspaceManager,
+    conversation_id: &str,
+    base: SnapshotRef<'_>,
+    target: SnapshotRef<'_>,
+) -> CommandResult<(SnapshotSource, String, String)> {
+    let session = workspace_manager
+        .get_active_conversation(conversation_id)
+        .await
+        .ok_or_else(|| format!("Unknown conversation for review diff: {}", conversation_id))?;
+    let store = session.review_snapshots();
+    let commits = store
+        .commits_for_range(base, target)
+        .await
+        .map_err(|err| format!("Failed to resolve snapshots: {}", err))?;
+
+    commits.ok_or_else(|| "Snapshot data unavailable for requested range".to_string())
+}
+
 #[tauri::command]
 pub async fn get_turn_diff_range(
     params: GetTurnDiffRangeParams,
@@ -59,45 +238,210 @@ pub async fn get_turn_diff_range(
         return Err("Runtime not initialized".to_string());
     }
 
+    let (source, base_commit, target_commit) = params.range.resolve(&workspace_manager).await?;
+
+    let options = params.options;
+    let diff = tokio::task::spawn_blocking(move || {
+        diff_range(&source, &base_commit, &target_commit, &options)
+    })
+    .await
+    .map_err(|err| format!("Failed to join diff task: {}", err))?
+    .map_err(|err| format!("Failed to compute diff: {}", err))?;
+
+    Ok(GetTurnDiffRangeResponse {
+        files: diff.files,
+        unified_diff: params.include_unified_diff.then_some(diff.unified_diff),
+    })
+}
+
+/// List the files changed in a range with their line stats, without hunks.
+#[tauri::command]
+pub async fn list_turn_diff_files(
+    params: ListTurnDiffFilesParams,
+    runtime: State<'_, CodexRuntime>,
+    workspace_manager: State<'_, WorkspaceManager>,
+) -> CommandResult<ListTurnDiffFilesResponse> {
+    if !runtime.is_initialized().await {
+        return Err("Runtime not initialized".to_string());
+    }
+
+    let (source, base_commit, target_commit) = params.range.resolve(&workspace_manager).await?;
+
+    let options = params.options;
+    let files = tokio::task::spawn_blocking(move || {
+        list_changed_files(&source, &base_commit, &target_commit, &options)
+    })
+    .await
+    .map_err(|err| format!("Failed to join diff task: {}", err))?
+    .map_err(|err| format!("Failed to list changed files: {}", err))?;
+
+    Ok(ListTurnDiffFilesResponse { files })
+}
+
+/// Fetch hunks for a subset of the files changed in a range.
+#[tauri::command]
+pub async fn get_turn_diff_files(
+    params: GetTurnDiffFilesParams,
+    runtime: State<'_, CodexRuntime>,
+    workspace_manager: State<'_, WorkspaceManager>,
+) -> CommandResult<GetTurnDiffFilesResponse> {
+    if !runtime.is_initialized().await {
+        return Err("Runtime not initialized".to_string());
+    }
+
+    let (source, base_commit, target_commit) = params.range.resolve(&workspace_manager).await?;
+
+    let GetTurnDiffFilesParams {
+        paths,
+        expand_collapsed,
+        options,
+        ..
+    } = params;
+    let files = tokio::task::spawn_blocking(move || {
+        diff_selected_files(
+            &source,
+            &base_commit,
+            &target_commit,
+            &paths,
+            expand_collapsed,
+            &options,
+        )
+    })
+    .await
+    .map_err(|err| format!("Failed to join diff task: {}", err))?
+    .map_err(|err| format!("Failed to compute diff: {}", err))?;
+
+    Ok(GetTurnDiffFilesResponse { files })
+}
+
+/// Parameters accepted when reading a file from a snapshot.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct GetFileAtSnapshotParams {
+    pub conversation_id: String,
+    /// Turn snapshot to read from; the conversation base is used when omitted.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event_id: Option<String>,
+    /// Git revision to read from instead of a snapshot. At most one of
+    /// `event_id` and `git_ref` may be set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub git_ref: Option<String>,
+    /// Path relative to the repository root, as in diff responses.
+    pub path: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct GetFileAtSnapshotResponse {
+    pub commit_id: String,
+    /// `None` when the file does not exist in the snapshot.
+    pub file: Option<SnapshotFile>,
+}
+
+/// Read a whole file from the base, a turn snapshot or a git ref, e.g. to
+/// expand the context around a hunk.
+#[tauri::command]
+pub async fn get_file_at_snapshot(
+    params: GetFileAtSnapshotParams,
+    runtime: State<'_, CodexRuntime>,
+    workspace_manager: State<'_, WorkspaceManager>,
+) -> CommandResult<GetFileAtSnapshotResponse> {
+    if !runtime.is_initialized().await {
+        return Err("Runtime not initialized".to_string());
+    }
+    if params.event_id.is_some() && params.git_ref.is_some() {
+        return Err("At most one of eventId and gitRef may be set".to_string());
+    }
+
     let session = workspace_manager
         .get_active_conversation(&params.conversation_id)
         .await
         .ok_or_else(|| {
             format!(
-                "Unknown conversation for review diff: {}",
+                "Unknown conversation for snapshot file: {}",
                 params.conversation_id
             )
         })?;
-    let store = session.review_snapshots();
-    let commits = store
-        .commits_for_range(params.base_event_id.as_deref(), &params.target_event_id)
+    let (source, commit_id) = session
+        .review_snapshots()
+        .snapshot_for(SnapshotRef::from_parts(
+            params.event_id.as_deref(),
+            params.git_ref.as_deref(),
+        ))
         .await
-        .map_err(|err| format!("Failed to resolve snapshots: {}", err))?;
+        .map_err(|err| format!("Failed to resolve snapshot: {}", err))?
+        .ok_or_else(|| "Snapshot data unavailable for requested turn".to_string())?;
 
-    let (cwd, base_commit, target_commit) =
-        commits.ok_or_else(|| "Snapshot data unavailable for requested range".to_string())?;
-
-    let diff = tokio::task::spawn_blocking(move || -> AnyResult<String> {
-        let output = Command::new("git")
-            .current_dir(&cwd)
-            .args(["diff", "--no-color", &base_commit, &target_commit])
-            .output()
-            .context("failed to execute git diff")?;
-
-        if !output.status.success() {
-            return Err(anyhow::anyhow!(
-                "git diff exited with status {}",
-                output.status
-            ));
-        }
+    let commit = commit_id.clone();
+    let path = params.path;
+    let file = tokio::task::spawn_blocking(move || snapshot_file(&source, &commit, &path))
+        .await
+        .map_err(|err| format!("Failed to join snapshot read task: {}", err))?
+        .map_err(|err| format!("Failed to read snapshot file: {}", err))?;
+
+    Ok(GetFileAtSnapshotResponse { commit_id, file })
+}
+
+/// Parameters accepted when attributing a file's lines to turns.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct GetTurnBlameParams {
+    pub conversation_id: String,
+    /// Turn snapshot to blame; the latest snapshot is used when omitted.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event_id: Option<String>,
+    /// Path relative to the repository root, as in diff responses.
+    pub path: String,
+}
 
-        String::from_utf8(output.stdout).context("git diff produced invalid UTF-8")
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct GetTurnBlameResponse {
+    /// Snapshot the blame describes.
+    pub commit_id: String,
+    pub blame: FileTurnBlame,
+}
+
+/// Attribute each line of a file to the turn that last changed it.
+#[tauri::command]
+pub async fn get_turn_blame(
+    params: GetTurnBlameParams,
+    runtime: State<'_, CodexRuntime>,
+    workspace_manager: State<'_, WorkspaceManager>,
+) -> CommandResult<GetTurnBlameResponse> {
+    if !runtime.is_initialized().await {
+        return Err("Runtime not initialized".to_string());
+    }
+
+    let session = workspace_manager
+        .get_active_conversation(&params.conversation_id)
+        .await
+        .ok_or_else(|| {
+            format!(
+                "Unknown conversation for turn blame: {}",
+                params.conversation_id
+            )
+        })?;
+    let history = session
+        .review_snapshots()
+        .history_until(params.event_id.as_deref())
+        .await
+        .ok_or_else(|| "Snapshot data unavailable for requested turn".to_string())?;
+
+    let commit_id = history
+        .turns
+        .last()
+        .map(|turn| turn.commit_id.clone())
+        .unwrap_or_else(|| history.base_commit.clone());
+    let path = params.path;
+    let blame = tokio::task::spawn_blocking(move || {
+        blame_file(&history.source, &history.base_commit, &history.turns, &path)
     })
     .await
-    .map_err(|err| format!("Failed to join git diff task: {}", err))?
-    .map_err(|err| format!("Failed to compute diff: {}", err))?;
+    .map_err(|err| format!("Failed to join blame task: {}", err))?
+    .map_err(|err| format!("Failed to compute turn blame: {}", err))?;
 
-    Ok(GetTurnDiffRangeResponse { unified_diff: diff })
+    Ok(GetTurnBlameResponse { commit_id, blame })
 }
 
 #[tauri::command]
@@ -123,18 +467,691 @@ pub async fn list_turn_snapshots(
     let store = session.review_snapshots();
     let summary = store.snapshot_summary().await;
 
-    let response = ListTurnSnapshotsResponse {
+    Ok(snapshot_listing(summary))
+}
+
+/// Parameters accepted when turning snapshotting back on for a conversation.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct ReenableTurnSnapshotsParams {
+    pub conversation_id: String,
+}
+
+/// Clear a disabled snapshot state and try to capture the base snapshot again.
+#[tauri::command]
+pub async fn reenable_turn_snapshots(
+    params: ReenableTurnSnapshotsParams,
+    runtime: State<'_, CodexRuntime>,
+    workspace_manager: State<'_, WorkspaceManager>,
+) -> CommandResult<ListTurnSnapshotsResponse> {
+    if !runtime.is_initialized().await {
+        return Err("Runtime not initialized".to_string());
+    }
+
+    let session = workspace_manager
+        .get_active_conversation(&params.conversation_id)
+        .await
+        .ok_or_else(|| {
+            format!(
+                "Unknown conversation for re-enabling snapshots: {}",
+                params.conversation_id
+            )
+        })?;
+
+    let store = session.review_snapshots();
+    store.reenable().await;
+    // A failure is reported through `last_error` in the listing.
+    if let Err(err) = store.ensure_base().await {
+        log::debug!(
+            "Failed to capture baseline snapshot for conversation {}: {}",
+            params.conversation_id,
+            err
+        );
+    }
+
+    Ok(snapshot_listing(store.snapshot_summary().await))
+}
+
+fn snapshot_listing(summary: ConversationSnapshotSummary) -> ListTurnSnapshotsResponse {
+    ListTurnSnapshotsResponse {
         disabled: summary.disabled,
+        last_error: summary.last_error,
         base_commit_id: summary.base_commit,
         snapshots: summary
-            .turn_commits
+            .turns
             .into_iter()
-            .map(|(event_id, commit_id)| TurnSnapshotDescriptor {
-                event_id,
-                commit_id,
+            .map(|turn| TurnSnapshotDescriptor {
+                event_id: turn.event_id,
+                commit_id: turn.commit_id,
+                turn_index: turn.turn_index,
+                captured_at: turn.captured_at.to_rfc3339(),
+                prompt: turn.prompt,
+                stats: turn.stats,
+                origin: turn.origin,
             })
             .collect(),
+    }
+}
+
+/// Check out the working tree to the base snapshot or a turn snapshot.
+#[tauri::command]
+pub async fn restore_turn_snapshot(
+    params: RestoreTurnSnapshotParams,
+    runtime: State<'_, CodexRuntime>,
+    workspace_manager: State<'_, WorkspaceManager>,
+) -> CommandResult<RestoreTurnSnapshotResponse> {
+    if !runtime.is_initialized().await {
+        return Err("Runtime not initialized".to_string());
+    }
+
+    let session = workspace_manager
+        .get_active_conversation(&params.conversation_id)
+        .await
+        .ok_or_else(|| {
+            format!(
+                "Unknown conversation for snapshot restore: {}",
+                params.conversation_id
+            )
+        })?;
+
+    let restored = session
+        .review_snapshots()
+        .restore_snapshot(params.event_id.as_deref())
+        .await
+        .map_err(|err| format!("Failed to restore snapshot: {:#}", err))?;
+
+    Ok(RestoreTurnSnapshotResponse {
+        restored_commit_id: restored.restored_commit,
+        safety_commit_id: restored.safety_commit,
+    })
+}
+
+/// Parameters accepted when reverting selected changes from a range.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct RevertTurnChangesParams {
+    pub conversation_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base_event_id: Option<String>,
+    pub target_event_id: String,
+    pub selections: Vec<RevertSelection>,
+    /// Options of the diff the hunk indexes were taken from.
+    #[serde(default)]
+    #[ts(optional, as = "Option<ReviewDiffOptions>")]
+    pub options: ReviewDiffOptions,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct RevertTurnChangesResponse {
+    /// Paths written or removed; empty when there were conflicts.
+    pub reverted_paths: Vec<String>,
+    /// Selections whose files changed since the range target. Nothing is
+    /// reverted when this is non-empty.
+    pub conflicts: Vec<RevertConflict>,
+    /// Snapshot taken after the revert, listed as a `revert` entry in the
+    /// turn timeline.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snapshot_event_id: Option<String>,
+}
+
+/// Undo selected files or hunks of a range in the working tree, then take a
+/// new snapshot so later ranges reflect the revert.
+#[tauri::command]
+pub async fn revert_turn_changes(
+    params: RevertTurnChangesParams,
+    runtime: State<'_, CodexRuntime>,
+    workspace_manager: State<'_, WorkspaceManager>,
+) -> CommandResult<RevertTurnChangesResponse> {
+    if !runtime.is_initialized().await {
+        return Err("Runtime not initialized".to_string());
+    }
+    if params.selections.is_empty() {
+        return Err("No changes selected to revert".to_string());
+    }
+
+    let session = workspace_manager
+        .get_active_conversation(&params.conversation_id)
+        .await
+        .ok_or_else(|| {
+            format!(
+                "Unknown conversation for revert: {}",
+                params.conversation_id
+            )
+        })?;
+    let (source, base_commit, target_commit) = resolve_snapshot_range(
+        &workspace_manager,
+        &params.conversation_id,
+        SnapshotRef::from_parts(params.base_event_id.as_deref(), None),
+        SnapshotRef::Turn(&params.target_event_id),
+    )
+    .await?;
+
+    let cwd = session.cwd.clone();
+    let RevertTurnChangesParams {
+        selections,
+        options,
+        ..
+    } = params;
+    let outcome = tokio::task::spawn_blocking(move || {
+        revert_changes(
+            &source,
+            &base_commit,
+            &target_commit,
+            &cwd,
+            &selections,
+            &options,
+        )
+    })
+    .await
+    .map_err(|err| format!("Failed to join revert task: {}", err))?
+    .map_err(|err| format!("Failed to revert changes: {}", err))?;
+
+    let mut snapshot_event_id = None;
+    if !outcome.written_paths.is_empty() {
+        let description = format!("Reverted changes to {}", outcome.written_paths.join(", "));
+        match session
+            .review_snapshots()
+            .record_revert_snapshot(&description)
+            .await
+        {
+            Ok(snapshot) => snapshot_event_id = snapshot.map(|(event_id, _)| event_id),
+            Err(err) => log::warn!(
+                "Failed to snapshot reverted workspace for conversation {}: {:#}",
+                params.conversation_id,
+                err
+            ),
+        }
+    }
+
+    Ok(RevertTurnChangesResponse {
+        reverted_paths: outcome.written_paths,
+        conflicts: outcome.conflicts,
+        snapshot_event_id,
+    })
+}
+
+/// Parameters accepted when exporting a snapshot range as a patch file.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportTurnPatchParams {
+    pub conversation_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base_event_id: Option<String>,
+    pub target_event_id: String,
+    /// File the `git format-patch` style series is written to.
+    pub output_path: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportTurnPatchResponse {
+    pub output_path: String,
+    /// One patch per turn that changed the workspace.
+    pub commits: Vec<ExportedTurnCommit>,
+}
+
+/// Parameters accepted when materialising a snapshot range as a branch.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateTurnBranchParams {
+    pub conversation_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base_event_id: Option<String>,
+    pub target_event_id: String,
+    /// Defaults to `pasture/<conversation>/turn-<N>`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub branch_name: Option<String>,
+    /// Move the branch if it already exists.
+    #[serde(default)]
+    pub force: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateTurnBranchResponse {
+    pub branch_name: String,
+    pub head_commit_id: String,
+    pub commits: Vec<ExportedTurnCommit>,
+}
+
+async fn resolve_export_range(
+    workspace_manager: &WorkspaceManager,
+    conversation_id: &str,
+    base_event_id: Option<&str>,
+    target_event_id: &str,
+) -> CommandResult<SnapshotExportRange> {
+    let session = workspace_manager
+        .get_active_conversation(conversation_id)
+        .await
+        .ok_or_else(|| format!("Unknown conversation for export: {}", conversation_id))?;
+
+    session
+        .review_snapshots()
+        .export_range(base_event_id, target_event_id)
+        .await
+        .map_err(|err| format!("Failed to resolve snapshots: {:#}", err))
+}
+
+/// Write a snapshot range as a patch series that `git am` can apply.
+#[tauri::command]
+pub async fn export_turn_patch(
+    params: ExportTurnPatchParams,
+    runtime: State<'_, CodexRuntime>,
+    workspace_manager: State<'_, WorkspaceManager>,
+) -> CommandResult<ExportTurnPatchResponse> {
+    if !runtime.is_initialized().await {
+        return Err("Runtime not initialized".to_string());
+    }
+
+    let range = resolve_export_range(
+        &workspace_manager,
+        &params.conversation_id,
+        params.base_event_id.as_deref(),
+        &params.target_event_id,
+    )
+    .await?;
+
+    let ExportTurnPatchParams {
+        conversation_id,
+        output_path,
+        ..
+    } = params;
+    let path = PathBuf::from(&output_path);
+    let commits = tokio::task::spawn_blocking(move || {
+        write_patch_series(
+            &range.repo_path,
+            &conversation_id,
+            &range.base_commit,
+            &range.turns,
+            &path,
+        )
+    })
+    .await
+    .map_err(|err| format!("Failed to join export task: {}", err))?
+    .map_err(|err| format!("Failed to export patch: {}", err))?;
+
+    Ok(ExportTurnPatchResponse {
+        output_path,
+        commits,
+    })
+}
+
+/// Replay a snapshot range as real commits on a local branch.
+#[tauri::command]
+pub async fn create_turn_branch(
+    params: CreateTurnBranchParams,
+    runtime: State<'_, CodexRuntime>,
+    workspace_manager: State<'_, WorkspaceManager>,
+) -> CommandResult<CreateTurnBranchResponse> {
+    if !runtime.is_initialized().await {
+        return Err("Runtime not initialized".to_string());
+    }
+
+    let range = resolve_export_range(
+        &workspace_manager,
+        &params.conversation_id,
+        params.base_event_id.as_deref(),
+        &params.target_event_id,
+    )
+    .await?;
+
+    let CreateTurnBranchParams {
+        conversation_id,
+        branch_name,
+        force,
+        ..
+    } = params;
+    let branch_name =
+        branch_name.unwrap_or_else(|| default_branch_name(&conversation_id, &range.turns));
+    let branch = branch_name.clone();
+    let (head_commit_id, commits) = tokio::task::spawn_blocking(move || {
+        review_export::create_turn_branch(
+            &range.repo_path,
+            &conversation_id,
+            &range.base_commit,
+            &range.turns,
+            &branch,
+            force,
+        )
+    })
+    .await
+    .map_err(|err| format!("Failed to join export task: {}", err))?
+    .map_err(|err| format!("Failed to create branch: {}", err))?;
+
+    Ok(CreateTurnBranchResponse {
+        branch_name,
+        head_commit_id,
+        commits,
+    })
+}
+
+/// How long review snapshots of inactive conversations are kept.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotRetentionSettings {
+    /// Snapshots are kept forever when unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retention_days: Option<u32>,
+}
+
+/// Parameters accepted when pruning review snapshots.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct PruneReviewSnapshotsParams {
+    /// Defaults to the configured retention.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub older_than_days: Option<u32>,
+}
+
+#[tauri::command]
+pub async fn get_snapshot_retention(
+    workspace_manager: State<'_, WorkspaceManager>,
+) -> CommandResult<SnapshotRetentionSettings> {
+    Ok(SnapshotRetentionSettings {
+        retention_days: workspace_manager.get_snapshot_retention_days().await,
+    })
+}
+
+#[tauri::command]
+pub async fn set_snapshot_retention(
+    params: SnapshotRetentionSettings,
+    workspace_manager: State<'_, WorkspaceManager>,
+) -> CommandResult<()> {
+    workspace_manager
+        .set_snapshot_retention_days(params.retention_days)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Delete the snapshots and review drafts of conversations that are not
+/// subscribed to and have been inactive for the given number of days.
+#[tauri::command]
+pub async fn prune_review_snapshots(
+    params: PruneReviewSnapshotsParams,
+    workspace_manager: State<'_, WorkspaceManager>,
+    runtime: State<'_, CodexRuntime>,
+    review_drafts: State<'_, ReviewDrafts>,
+) -> CommandResult<SnapshotPruneSummary> {
+    let older_than_days = match params.older_than_days {
+        Some(days) => days,
+        None => workspace_manager
+            .get_snapshot_retention_days()
+            .await
+            .ok_or_else(|| "No snapshot retention configured".to_string())?,
+    };
+
+    let summary = workspace_manager
+        .prune_review_snapshots(retention_duration(older_than_days), runtime.event_manager())
+        .await
+        .map_err(|err| format!("Failed to prune review snapshots: {}", err))?;
+    if let Err(err) = review_drafts
+        .discard_conversations(&summary.pruned_conversation_ids)
+        .await
+    {
+        log::warn!(
+            "Failed to drop review drafts of pruned conversations: {:#}",
+            err
+        );
+    }
+    Ok(summary)
+}
+
+/// Parameters accepted when drafting a review comment.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct AddReviewCommentParams {
+    pub conversation_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base_event_id: Option<String>,
+    pub target_event_id: String,
+    pub file_path: String,
+    pub side: ReviewCommentSide,
+    pub line: u32,
+    pub body: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context_line: Option<String>,
+}
+
+/// Parameters accepted when editing a drafted review comment.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateReviewCommentParams {
+    pub conversation_id: String,
+    pub comment_id: String,
+    pub body: String,
+}
+
+/// Parameters accepted when deleting a drafted review comment.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteReviewCommentParams {
+    pub conversation_id: String,
+    pub comment_id: String,
+}
+
+/// Parameters accepted when listing drafted review comments.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct ListReviewCommentsParams {
+    pub conversation_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base_event_id: Option<String>,
+    /// Range target; every comment in the conversation is returned when omitted.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target_event_id: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct ListReviewCommentsResponse {
+    pub comments: Vec<ReviewComment>,
+    /// Every review submitted for the conversation, oldest first.
+    pub submissions: Vec<ReviewSubmission>,
+}
+
+/// Save a new review comment draft for a snapshot range.
+#[tauri::command]
+pub async fn add_review_comment(
+    params: AddReviewCommentParams,
+    review_drafts: State<'_, ReviewDrafts>,
+) -> CommandResult<ReviewComment> {
+    let AddReviewCommentParams {
+        conversation_id,
+        base_event_id,
+        target_event_id,
+        file_path,
+        side,
+        line,
+        body,
+        context_line,
+    } = params;
+
+    review_drafts
+        .add_comment(
+            &conversation_id,
+            NewReviewComment {
+                base_event_id,
+                target_event_id,
+                file_path,
+                side,
+                line,
+                body,
+                context_line,
+            },
+        )
+        .await
+        .map_err(|err| format!("Failed to save review comment: {:#}", err))
+}
+
+/// Replace the body of a review comment draft.
+#[tauri::command]
+pub async fn update_review_comment(
+    params: UpdateReviewCommentParams,
+    review_drafts: State<'_, ReviewDrafts>,
+) -> CommandResult<ReviewComment> {
+    review_drafts
+        .update_comment(&params.conversation_id, &params.comment_id, params.body)
+        .await
+        .map_err(|err| format!("Failed to update review comment: {:#}", err))
+}
+
+/// Remove a review comment draft.
+#[tauri::command]
+pub async fn delete_review_comment(
+    params: DeleteReviewCommentParams,
+    review_drafts: State<'_, ReviewDrafts>,
+) -> CommandResult<()> {
+    review_drafts
+        .delete_comment(&params.conversation_id, &params.comment_id)
+        .await
+        .map_err(|err| format!("Failed to delete review comment: {:#}", err))
+}
+
+/// List review comment drafts for a conversation or a single range.
+#[tauri::command]
+pub async fn list_review_comments(
+    params: ListReviewCommentsParams,
+    review_drafts: State<'_, ReviewDrafts>,
+) -> CommandResult<ListReviewCommentsResponse> {
+    let range = params
+        .target_event_id
+        .as_deref()
+        .map(|target| (params.base_event_id.as_deref(), target));
+    let comments = review_drafts
+        .list_comments(&params.conversation_id, range)
+        .await;
+    let submissions = review_drafts
+        .list_submissions(&params.conversation_id)
+        .await;
+
+    Ok(ListReviewCommentsResponse {
+        comments,
+        submissions,
+    })
+}
+
+/// Parameters accepted when sending the drafted comments for a range to the agent.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct SubmitTurnReviewParams {
+    pub conversation_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base_event_id: Option<String>,
+    pub target_event_id: String,
+    /// Turn number used in the message heading.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub turn_number: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct SubmitTurnReviewResponse {
+    pub submission: ReviewSubmission,
+    /// Message that was sent to the agent.
+    pub message: String,
+}
+
+/// Send every unsubmitted comment in a range as one consolidated user message.
+#[tauri::command]
+pub async fn submit_turn_review(
+    params: SubmitTurnReviewParams,
+    runtime: State<'_, CodexRuntime>,
+    workspace_manager: State<'_, WorkspaceManager>,
+    review_drafts: State<'_, ReviewDrafts>,
+    app_handle: AppHandle,
+) -> CommandResult<SubmitTurnReviewResponse> {
+    if !runtime.is_initialized().await {
+        return Err("Runtime not initialized".to_string());
+    }
+
+    let SubmitTurnReviewParams {
+        conversation_id,
+        base_event_id,
+        target_event_id,
+        turn_number,
+    } = params;
+
+    let comments: Vec<ReviewComment> = review_drafts
+        .list_comments(
+            &conversation_id,
+            Some((base_event_id.as_deref(), &target_event_id)),
+        )
+        .await
+        .into_iter()
+        .filter(|comment| comment.submission_id.is_none())
+        .collect();
+    if comments.is_empty() {
+        return Err("No unsubmitted review comments for this range".to_string());
+    }
+
+    let mut paths: Vec<String> = comments
+        .iter()
+        .map(|comment| comment.file_path.clone())
+        .collect();
+    paths.sort();
+    paths.dedup();
+
+    // Quote lines from the current diff; stored context lines cover anything we cannot resolve.
+    let files = match resolve_snapshot_range(
+        &workspace_manager,
+        &conversation_id,
+        SnapshotRef::from_parts(base_event_id.as_deref(), None),
+        SnapshotRef::Turn(&target_event_id),
+    )
+    .await
+    {
+        Ok((source, base_commit, target_commit)) => tokio::task::spawn_blocking(move || {
+            diff_selected_files(
+                &source,
+                &base_commit,
+                &target_commit,
+                &paths,
+                true,
+                &ReviewDiffOptions::default(),
+            )
+        })
+        .await
+        .map_err(|err| err.to_string())
+        .and_then(|result| result.map_err(|err| err.to_string()))
+        .unwrap_or_else(|err| {
+            log::warn!("Failed to load diff context for review: {}", err);
+            Vec::new()
+        }),
+        Err(err) => {
+            log::warn!("Failed to load diff context for review: {}", err);
+            Vec::new()
+        }
     };
 
-    Ok(response)
+    let turn_label = turn_number
+        .map(|number| format!("turn {}", number))
+        .unwrap_or_else(|| "this turn".to_string());
+    let message = build_review_message(&turn_label, &comments, &files);
+
+    let conversation = subscribed_conversation(&runtime, app_handle, &conversation_id).await?;
+    snapshot_external_changes(&workspace_manager, &conversation_id).await;
+    conversation
+        .submit(Op::UserInput {
+            items: vec![CoreUserInput::Text {
+                text: message.clone(),
+            }],
+        })
+        .await
+        .map_err(|e| format!("Failed to submit review: {}", e))?;
+
+    let submission = review_drafts
+        .record_submission(
+            &conversation_id,
+            base_event_id,
+            target_event_id,
+            comments.into_iter().map(|comment| comment.id).collect(),
+        )
+        .await
+        .map_err(|err| format!("Review sent but could not be recorded: {:#}", err))?;
+
+    Ok(SubmitTurnReviewResponse {
+        submission,
+        message,
+    })
 }
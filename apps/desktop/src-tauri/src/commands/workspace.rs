@@ -5,6 +5,8 @@ use tauri::State;
 use tauri::Window;
 use ts_rs::TS;
 
+use crate::review_capture::CaptureFilter;
+use crate::review_capture::SnapshotCaptureSettings;
 use crate::workspace_manager::WorkspaceComposerDefaults;
 use crate::workspace_manager::WorkspaceManager;
 
@@ -121,3 +123,43 @@ pub async fn get_workspace_composer_defaults(
         .get_workspace_defaults_for_normalized(&normalized)
         .await)
 }
+
+/// Retrieve the review snapshot capture settings of a workspace.
+#[tauri::command]
+pub async fn get_workspace_snapshot_settings(
+    params: WorkspacePathParams,
+    workspace_manager: State<'_, WorkspaceManager>,
+) -> CommandResult<SnapshotCaptureSettings> {
+    let normalized = workspace_manager
+        .normalize_workspace_path(&params.workspace_path)
+        .map_err(|e| e.to_string())?;
+    Ok(workspace_manager
+        .get_snapshot_settings_for_normalized(&normalized)
+        .await)
+}
+
+/// Parameters accepted when saving snapshot capture settings.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct SetWorkspaceSnapshotSettingsParams {
+    pub workspace_path: String,
+    pub settings: SnapshotCaptureSettings,
+}
+
+/// Save the review snapshot capture settings of a workspace.
+#[tauri::command]
+pub async fn set_workspace_snapshot_settings(
+    params: SetWorkspaceSnapshotSettingsParams,
+    workspace_manager: State<'_, WorkspaceManager>,
+) -> CommandResult<()> {
+    // Reject bad globs here rather than failing every later capture.
+    CaptureFilter::new(&params.settings).map_err(|e| e.to_string())?;
+
+    let normalized = workspace_manager
+        .normalize_workspace_path(&params.workspace_path)
+        .map_err(|e| e.to_string())?;
+    workspace_manager
+        .set_snapshot_settings_for_normalized(&normalized, params.settings)
+        .await
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,102 @@
+use std::path::Path;
+use std::path::PathBuf;
+
+use anyhow::Context;
+use anyhow::Result;
+use anyhow::anyhow;
+use anyhow::bail;
+
+/// Directory under `codex_home` holding live rollouts, nested by date.
+pub const SESSIONS_SUBDIR: &str = "sessions";
+/// Directory under `codex_home` that codex moves archived rollouts into.
+pub const ARCHIVED_SESSIONS_SUBDIR: &str = "archived_sessions";
+
+/// Move a rollout out of the sessions tree into the archive. Returns the new
+/// path.
+pub fn archive_rollout(codex_home: &Path, rollout_path: &Path) -> Result<PathBuf> {
+    if !rollout_path.starts_with(codex_home.join(SESSIONS_SUBDIR)) {
+        bail!(
+            "Rollout is not in the sessions directory: {}",
+            rollout_path.display()
+        );
+    }
+    let file_name = rollout_path
+        .file_name()
+        .ok_or_else(|| anyhow!("Rollout path has no file name: {}", rollout_path.display()))?;
+
+    let destination = codex_home.join(ARCHIVED_SESSIONS_SUBDIR).join(file_name);
+    move_rollout(rollout_path, &destination)?;
+    Ok(destination)
+}
+
+/// Move an archived rollout back to the dated sessions directory its file
+/// name belongs to. Returns the new path.
+pub fn unarchive_rollout(codex_home: &Path, rollout_path: &Path) -> Result<PathBuf> {
+    if !rollout_path.starts_with(codex_home.join(ARCHIVED_SESSIONS_SUBDIR)) {
+        bail!("Rollout is not archived: {}", rollout_path.display());
+    }
+    let file_name = rollout_path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| anyhow!("Rollout path has no file name: {}", rollout_path.display()))?;
+    let (year, month, day) = rollout_date(file_name)
+        .ok_or_else(|| anyhow!("Rollout file name has no date: {}", file_name))?;
+
+    let destination = codex_home
+        .join(SESSIONS_SUBDIR)
+        .join(year)
+        .join(month)
+        .join(day)
+        .join(file_name);
+    move_rollout(rollout_path, &destination)?;
+    Ok(destination)
+}
+
+/// Year, month and day of a `rollout-YYYY-MM-DDThh-mm-ss-<id>.jsonl` name.
+fn rollout_date(file_name: &str) -> Option<(&str, &str, &str)> {
+    let date = file_name.strip_prefix("rollout-")?.get(..10)?;
+    let (year, rest) = date.split_once('-')?;
+    let (month, day) = rest.split_once('-')?;
+    let digits = |part: &str, len: usize| {
+        part.len() == len && part.bytes().all(|byte| byte.is_ascii_digit())
+    };
+    (digits(year, 4) && digits(month, 2) && digits(day, 2)).then_some((year, month, day))
+}
+
+fn move_rollout(from: &Path, to: &Path) -> Result<()> {
+    if to.exists() {
+        bail!("{} already exists", to.display());
+    }
+    if let Some(parent) = to.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    std::fs::rename(from, to)
+        .with_context(|| format!("Failed to move {} to {}", from.display(), to.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn archives_and_restores_rollouts_by_date() {
+        let dir = tempfile::tempdir().unwrap();
+        let name = "rollout-2025-10-01T10-00-00-0199a213-81c0-7800-8aa1-bbab2a035a53.jsonl";
+        let original = dir.path().join("sessions/2025/10/01").join(name);
+        std::fs::create_dir_all(original.parent().unwrap()).unwrap();
+        std::fs::write(&original, "{}\n").unwrap();
+
+        let archived = archive_rollout(dir.path(), &original).unwrap();
+        assert_eq!(archived, dir.path().join("archived_sessions").join(name));
+        assert!(!original.exists());
+        assert!(archive_rollout(dir.path(), &archived).is_err());
+
+        let restored = unarchive_rollout(dir.path(), &archived).unwrap();
+        assert_eq!(restored, original);
+        assert_eq!(std::fs::read_to_string(&restored).unwrap(), "{}\n");
+        assert!(unarchive_rollout(dir.path(), &restored).is_err());
+
+        assert_eq!(rollout_date("rollout-2025-1-01T"), None);
+    }
+}
@@ -0,0 +1,735 @@
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use anyhow::Context;
+use anyhow::Result;
+use codex_protocol::ConversationId;
+use serde::Deserialize;
+use serde::Serialize;
+use tokio::sync::Mutex;
+
+use crate::atomic_file::write_atomically;
+use crate::conversation_archive::ARCHIVED_SESSIONS_SUBDIR;
+use crate::conversation_archive::SESSIONS_SUBDIR;
+use crate::conversation_search::FIRST_MESSAGE_MAX_LEN;
+use crate::conversation_search::RolloutEntry;
+use crate::conversation_search::SearchEntryKind;
+use crate::conversation_search::collect_rollouts;
+use crate::conversation_search::parse_line;
+use crate::conversation_search::rollout_entry;
+use crate::conversation_search::truncate_chars;
+
+/// Bumped whenever the persisted layout changes; older listings are rebuilt
+/// from scratch.
+const LISTING_VERSION: u32 = 1;
+/// Bytes read from the end of a rollout to find its latest timestamp.
+const TAIL_BYTES: u64 = 64 * 1024;
+
+/// Listing metadata of an indexed conversation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexedConversation {
+    pub conversation_id: ConversationId,
+    pub path: PathBuf,
+    pub cwd: PathBuf,
+    pub timestamp: Option<String>,
+    pub first_message: Option<String>,
+    pub model_provider: Option<String>,
+    /// Timestamp of the last complete rollout line.
+    pub last_activity: Option<String>,
+    pub archived: bool,
+}
+
+/// Position after the last conversation of a listing page.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConversationListCursor {
+    pub timestamp: String,
+    pub conversation_id: String,
+}
+
+/// Start time, conversation id and path; orders a workspace's listing.
+type ListingKey = (String, String, PathBuf);
+
+/// What the listing shows of one rollout file.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct ListedRollout {
+    conversation_id: ConversationId,
+    cwd: PathBuf,
+    /// `cwd` normalized the way workspace paths are; listings are grouped by it.
+    workspace: String,
+    timestamp: Option<String>,
+    first_message: Option<String>,
+    model_provider: Option<String>,
+    last_activity: Option<String>,
+    archived: bool,
+    interactive: bool,
+    /// Bytes of complete lines searched for the metadata and first message.
+    head_len: u64,
+    /// Size and modification time of the file when it was last read.
+    len: u64,
+    modified: Option<SystemTime>,
+}
+
+impl ListedRollout {
+    fn listable(&self) -> bool {
+        self.interactive && self.first_message.is_some()
+    }
+
+    fn key(&self, path: &Path) -> ListingKey {
+        (
+            self.timestamp.clone().unwrap_or_default(),
+            self.conversation_id.to_string(),
+            path.to_path_buf(),
+        )
+    }
+
+    fn listing(&self, path: &Path) -> IndexedConversation {
+        IndexedConversation {
+            conversation_id: self.conversation_id,
+            path: path.to_path_buf(),
+            cwd: self.cwd.clone(),
+            timestamp: self.timestamp.clone(),
+            first_message: self.first_message.clone(),
+            model_provider: self.model_provider.clone(),
+            last_activity: self.last_activity.clone(),
+            archived: self.archived,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+struct ListingState {
+    version: u32,
+    rollouts: HashMap<PathBuf, ListedRollout>,
+    /// Newest `YYYY/MM/DD` session directory seen so far. New rollouts are
+    /// written there or to later days, so refreshes only scan from it on.
+    scanned_day: Option<String>,
+    /// Listable rollouts of each workspace, oldest first.
+    #[serde(skip)]
+    by_workspace: HashMap<String, BTreeSet<ListingKey>>,
+    /// Changed since the last save.
+    #[serde(skip)]
+    dirty: bool,
+}
+
+impl ListingState {
+    fn empty() -> Self {
+        Self {
+            version: LISTING_VERSION,
+            ..Self::default()
+        }
+    }
+
+    fn rebuild_order(&mut self) {
+        self.by_workspace.clear();
+        for (path, rollout) in &self.rollouts {
+            if rollout.listable() {
+                self.by_workspace
+                    .entry(rollout.workspace.clone())
+                    .or_default()
+                    .insert(rollout.key(path));
+            }
+        }
+    }
+
+    fn insert(&mut self, path: PathBuf, rollout: ListedRollout) {
+        self.remove(&path);
+        if rollout.listable() {
+            self.by_workspace
+                .entry(rollout.workspace.clone())
+                .or_default()
+                .insert(rollout.key(&path));
+        }
+        self.rollouts.insert(path, rollout);
+        self.dirty = true;
+    }
+
+    fn remove(&mut self, path: &Path) -> Option<ListedRollout> {
+        let rollout = self.rollouts.remove(path)?;
+        if let Some(keys) = self.by_workspace.get_mut(&rollout.workspace) {
+            keys.remove(&rollout.key(path));
+            if keys.is_empty() {
+                self.by_workspace.remove(&rollout.workspace);
+            }
+        }
+        self.dirty = true;
+        Some(rollout)
+    }
+
+    fn set_scanned_day(&mut self, day: Option<String>) {
+        if day.is_some() && day != self.scanned_day {
+            self.scanned_day = day;
+            self.dirty = true;
+        }
+    }
+}
+
+/// Listing metadata of the rollouts in `codex_home`, kept apart from the
+/// full-text index and ordered per workspace, so a page only reads the
+/// rollouts it returns and those written since the last page.
+#[derive(Clone)]
+pub struct ConversationListIndex {
+    state: Arc<Mutex<ListingState>>,
+    state_path: PathBuf,
+}
+
+impl ConversationListIndex {
+    pub fn new(state_path: PathBuf) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(ListingState::empty())),
+            state_path,
+        }
+    }
+
+    pub async fn load_state(&self) -> Result<()> {
+        let content = match tokio::fs::read_to_string(&self.state_path).await {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Ok(());
+            }
+            Err(e) => {
+                return Err(e).context("Failed to read conversation listing file");
+            }
+        };
+
+        let mut loaded_state: ListingState =
+            serde_json::from_str(&content).context("Failed to parse conversation listing JSON")?;
+        if loaded_state.version != LISTING_VERSION {
+            return Ok(());
+        }
+        loaded_state.rebuild_order();
+
+        let mut state = self.state.lock().await;
+        *state = loaded_state;
+
+        Ok(())
+    }
+
+    /// Pick up rollouts written since the last call, then return the next
+    /// `limit` interactive conversations with a user message in `workspace`
+    /// that `include` accepts, newest first, and whether more follow.
+    /// `workspace` must be normalized with [`normalized_path_string`].
+    pub async fn list(
+        &self,
+        codex_home: &Path,
+        workspace: String,
+        after: Option<ConversationListCursor>,
+        limit: usize,
+        include: impl FnMut(&IndexedConversation) -> bool + Send + 'static,
+    ) -> Result<(Vec<IndexedConversation>, bool)> {
+        self.with_state(codex_home, move |state, codex_home| {
+            scan_new_rollouts(state, codex_home);
+            list_page(
+                state,
+                codex_home,
+                &workspace,
+                after.as_ref(),
+                limit,
+                include,
+            )
+        })
+        .await
+    }
+
+    /// Look up a conversation by id.
+    pub async fn find(
+        &self,
+        codex_home: &Path,
+        conversation_id: &str,
+    ) -> Result<Option<IndexedConversation>> {
+        let conversation_id = conversation_id.to_string();
+        self.with_state(codex_home, move |state, codex_home| {
+            let lookup = |state: &ListingState| {
+                state
+                    .rollouts
+                    .iter()
+                    .find(|(_, rollout)| rollout.conversation_id.to_string() == conversation_id)
+                    .map(|(path, _)| path.clone())
+            };
+            let path = match lookup(state) {
+                Some(path) => path,
+                None => {
+                    scan_new_rollouts(state, codex_home);
+                    lookup(state)?
+                }
+            };
+            update_rollout(state, codex_home, &path);
+            state
+                .rollouts
+                .get(&path)
+                .map(|rollout| rollout.listing(&path))
+        })
+        .await
+    }
+
+    /// Re-read `paths` after they were moved, archived or deleted.
+    pub async fn sync_paths(&self, codex_home: &Path, paths: Vec<PathBuf>) -> Result<()> {
+        self.with_state(codex_home, move |state, codex_home| {
+            for path in &paths {
+                update_rollout(state, codex_home, path);
+            }
+        })
+        .await
+    }
+
+    /// Walk every live and archived rollout, picking up changes made outside
+    /// the app. Reads every rollout that changed; meant for the background.
+    pub async fn reconcile(&self, codex_home: &Path) -> Result<()> {
+        self.with_state(codex_home, reconcile).await
+    }
+
+    async fn with_state<T: Send + 'static>(
+        &self,
+        codex_home: &Path,
+        update: impl FnOnce(&mut ListingState, &Path) -> T + Send + 'static,
+    ) -> Result<T> {
+        let state = self.state.clone();
+        let state_path = self.state_path.clone();
+        let codex_home = codex_home.to_path_buf();
+
+        tokio::task::spawn_blocking(move || {
+            let mut state = state.blocking_lock();
+            let value = update(&mut state, &codex_home);
+            if state.dirty {
+                save_state(&state_path, &state)?;
+                state.dirty = false;
+            }
+            Ok(value)
+        })
+        .await
+        .context("Conversation listing task failed")?
+    }
+}
+
+fn save_state(state_path: &Path, state: &ListingState) -> Result<()> {
+    let json = serde_json::to_string(state).context("Failed to serialize conversation listing")?;
+    write_atomically(state_path, json.as_bytes()).context("Failed to save conversation listing")
+}
+
+/// Index the rollouts in session directories from the last scanned day on.
+/// An empty listing is built with a full [`reconcile`].
+fn scan_new_rollouts(state: &mut ListingState, codex_home: &Path) {
+    let Some(scanned_day) = state.scanned_day.clone() else {
+        reconcile(state, codex_home);
+        return;
+    };
+
+    let days = day_dirs(&codex_home.join(SESSIONS_SUBDIR), Some(&scanned_day));
+    for (_, dir) in &days {
+        let mut paths = Vec::new();
+        collect_rollouts(dir, &mut paths);
+        for path in &paths {
+            update_rollout(state, codex_home, path);
+        }
+    }
+    state.set_scanned_day(days.last().map(|(day, _)| day.clone()));
+}
+
+fn reconcile(state: &mut ListingState, codex_home: &Path) {
+    let sessions = codex_home.join(SESSIONS_SUBDIR);
+    let mut paths = Vec::new();
+    collect_rollouts(&sessions, &mut paths);
+    collect_rollouts(&codex_home.join(ARCHIVED_SESSIONS_SUBDIR), &mut paths);
+
+    let present: HashSet<&PathBuf> = paths.iter().collect();
+    let gone: Vec<PathBuf> = state
+        .rollouts
+        .keys()
+        .filter(|path| !present.contains(path))
+        .cloned()
+        .collect();
+    for path in &gone {
+        state.remove(path);
+    }
+    for path in &paths {
+        update_rollout(state, codex_home, path);
+    }
+
+    let newest_day = day_dirs(&sessions, state.scanned_day.as_deref()).pop();
+    state.set_scanned_day(newest_day.map(|(day, _)| day));
+}
+
+/// `YYYY/MM/DD` session directories on or after `from`, oldest first.
+fn day_dirs(sessions: &Path, from: Option<&str>) -> Vec<(String, PathBuf)> {
+    let before = |key: &str| from.is_some_and(|from| key < from.get(..key.len()).unwrap_or(from));
+    let mut days = Vec::new();
+    for (year, year_dir) in subdirs(sessions) {
+        if before(&year) {
+            continue;
+        }
+        for (month, month_dir) in subdirs(&year_dir) {
+            let month = format!("{year}/{month}");
+            if before(&month) {
+                continue;
+            }
+            for (day, day_dir) in subdirs(&month_dir) {
+                let day = format!("{month}/{day}");
+                if !before(&day) {
+                    days.push((day, day_dir));
+                }
+            }
+        }
+    }
+    days.sort();
+    days
+}
+
+fn subdirs(dir: &Path) -> Vec<(String, PathBuf)> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter(|entry| entry.file_type().is_ok_and(|file_type| file_type.is_dir()))
+        .filter_map(|entry| Some((entry.file_name().to_str()?.to_string(), entry.path())))
+        .collect()
+}
+
+/// Page of listable conversations ordered by start time, then id, newest
+/// first; the flag tells whether more follow. Rows are checked against the
+/// file system as they are picked, so deleted rollouts drop out and resumed
+/// ones show their latest activity.
+fn list_page(
+    state: &mut ListingState,
+    codex_home: &Path,
+    workspace: &str,
+    after: Option<&ConversationListCursor>,
+    limit: usize,
+    mut include: impl FnMut(&IndexedConversation) -> bool,
+) -> (Vec<IndexedConversation>, bool) {
+    let mut upper: Option<ListingKey> = after.map(|cursor| {
+        (
+            cursor.timestamp.clone(),
+            cursor.conversation_id.clone(),
+            PathBuf::new(),
+        )
+    });
+    let mut page = Vec::new();
+
+    while page.len() <= limit {
+        let Some(keys) = state.by_workspace.get(workspace) else {
+            break;
+        };
+        let next = match &upper {
+            Some(upper) => keys.range(..upper.clone()).next_back(),
+            None => keys.iter().next_back(),
+        };
+        let Some(key) = next.cloned() else {
+            break;
+        };
+        let path = key.2.clone();
+        upper = Some(key);
+
+        let Some(rollout) = state.rollouts.get(&path) else {
+            continue;
+        };
+        if !include(&rollout.listing(&path)) {
+            continue;
+        }
+        update_rollout(state, codex_home, &path);
+        if let Some(rollout) = state.rollouts.get(&path)
+            && rollout.listable()
+            && rollout.workspace == workspace
+        {
+            page.push(rollout.listing(&path));
+        }
+    }
+
+    let has_more = page.len() > limit;
+    page.truncate(limit);
+    (page, has_more)
+}
+
+/// Bring the entry of `path` in line with the file: drop it when the file is
+/// gone and re-read it when the file changed.
+fn update_rollout(state: &mut ListingState, codex_home: &Path, path: &Path) {
+    let Ok(metadata) = std::fs::metadata(path) else {
+        state.remove(path);
+        return;
+    };
+    let len = metadata.len();
+    let modified = metadata.modified().ok();
+    if let Some(rollout) = state.rollouts.get(path)
+        && rollout.len == len
+        && rollout.modified == modified
+    {
+        return;
+    }
+
+    // Rollouts are append-only; anything else was rewritten and is read anew.
+    let previous = state.remove(path).filter(|rollout| rollout.len < len);
+    let archived = path.starts_with(codex_home.join(ARCHIVED_SESSIONS_SUBDIR));
+    match read_rollout(path, previous, len) {
+        Ok(Some(mut rollout)) => {
+            rollout.archived = archived;
+            rollout.len = len;
+            rollout.modified = modified;
+            state.insert(path.to_path_buf(), rollout);
+        }
+        Ok(None) => {}
+        Err(e) => log::warn!("Failed to read rollout {}: {}", path.display(), e),
+    }
+}
+
+/// Read what the listing needs from `path`: the session metadata and first
+/// user message from the head, unless `previous` has them, and the latest
+/// timestamp from the tail. `None` while the metadata line is not written.
+fn read_rollout(
+    path: &Path,
+    previous: Option<ListedRollout>,
+    len: u64,
+) -> Result<Option<ListedRollout>> {
+    let mut file = File::open(path)?;
+    let mut rollout = previous;
+
+    if rollout
+        .as_ref()
+        .is_none_or(|rollout| rollout.first_message.is_none())
+    {
+        let mut head_len = rollout.as_ref().map_or(0, |rollout| rollout.head_len);
+        file.seek(SeekFrom::Start(head_len))?;
+        let mut reader = BufReader::new(&file);
+        let mut line = Vec::new();
+        loop {
+            line.clear();
+            let read = reader.read_until(b'\n', &mut line)?;
+            // Stop before a line that is still being written.
+            if read == 0 || line.last() != Some(&b'\n') {
+                break;
+            }
+            head_len += read as u64;
+            let Some(parsed) = parse_line(&line) else {
+                continue;
+            };
+            match rollout_entry(parsed.item) {
+                Some(RolloutEntry::Meta {
+                    conversation_id,
+                    cwd,
+                    timestamp,
+                    model_provider,
+                    interactive,
+                }) if rollout.is_none() => {
+                    rollout = Some(ListedRollout {
+                        conversation_id,
+                        workspace: normalized_path_string(&cwd),
+                        cwd,
+                        timestamp: Some(timestamp),
+                        first_message: None,
+                        model_provider,
+                        last_activity: Some(parsed.timestamp),
+                        archived: false,
+                        interactive,
+                        head_len: 0,
+                        len: 0,
+                        modified: None,
+                    });
+                }
+                Some(RolloutEntry::Text(SearchEntryKind::UserMessage, text)) => {
+                    if let Some(rollout) = rollout.as_mut() {
+                        rollout.first_message = Some(truncate_chars(&text, FIRST_MESSAGE_MAX_LEN));
+                        break;
+                    }
+                }
+                _ => {}
+            }
+        }
+        if let Some(rollout) = rollout.as_mut() {
+            rollout.head_len = head_len;
+        }
+    }
+
+    let Some(mut rollout) = rollout else {
+        return Ok(None);
+    };
+    if let Some(timestamp) = last_timestamp(&mut file, len)? {
+        rollout.last_activity = Some(timestamp);
+    }
+    Ok(Some(rollout))
+}
+
+/// Timestamp of the last complete line in the final [`TAIL_BYTES`] of `file`.
+fn last_timestamp(file: &mut File, len: u64) -> Result<Option<String>> {
+    let start = len.saturating_sub(TAIL_BYTES);
+    file.seek(SeekFrom::Start(start))?;
+    let mut tail = Vec::new();
+    file.take(len - start).read_to_end(&mut tail)?;
+
+    let Some(end) = tail.iter().rposition(|&byte| byte == b'\n') else {
+        return Ok(None);
+    };
+    // The first line may be cut off by the window; it then fails to parse.
+    Ok(tail[..end]
+        .split(|&byte| byte == b'\n')
+        .rev()
+        .find_map(parse_line)
+        .map(|parsed| parsed.timestamp))
+}
+
+pub(crate) fn normalized_path_string(path: &Path) -> String {
+    let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    path_to_normalized_string(&canonical)
+}
+
+#[cfg(not(windows))]
+fn path_to_normalized_string(path: &Path) -> String {
+    path.to_string_lossy().into_owned()
+}
+
+#[cfg(windows)]
+fn path_to_normalized_string(path: &Path) -> String {
+    let mut s = path.to_string_lossy().into_owned();
+    if s.starts_with(r"\\?\") {
+        s = s[4..].to_string();
+    }
+    s = s.replace('/', "\\");
+    s.make_ascii_lowercase();
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIRST_ID: &str = "0199a213-81c0-7800-8aa1-bbab2a035a53";
+    const SECOND_ID: &str = "0199a213-81c0-7800-8aa1-bbab2a035a54";
+    const THIRD_ID: &str = "0199a213-81c0-7800-8aa1-bbab2a035a55";
+
+    fn rollout(id: &str, day: &str, cwd: &str, messages: &[(&str, &str)]) -> String {
+        let mut content = format!(
+            r#"{{"timestamp":"{day}T10:00:00.000Z","type":"session_meta","payload":{{"id":"{id}","timestamp":"{day}T10:00:00.000Z","cwd":"{cwd}","originator":"pasture","cli_version":"0.0.0","instructions":null,"source":"cli"}}}}"#
+        );
+        content.push('\n');
+        for (time, message) in messages {
+            content.push_str(&message_line(day, time, message));
+        }
+        content
+    }
+
+    fn message_line(day: &str, time: &str, message: &str) -> String {
+        format!(
+            "{}\n",
+            format_args!(
+                r#"{{"timestamp":"{day}T{time}.000Z","type":"event_msg","payload":{{"type":"user_message","message":{}}}}}"#,
+                serde_json::to_string(message).unwrap()
+            )
+        )
+    }
+
+    fn write_rollout(codex_home: &Path, id: &str, day: &str, content: &str) -> PathBuf {
+        let dir = codex_home.join(SESSIONS_SUBDIR).join(day.replace('-', "/"));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(format!("rollout-{day}T10-00-00-{id}.jsonl"));
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    fn ids(page: &[IndexedConversation]) -> Vec<String> {
+        page.iter()
+            .map(|conversation| conversation.conversation_id.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn lists_pages_per_workspace_from_recent_days() {
+        let dir = tempfile::tempdir().unwrap();
+        let home = dir.path();
+        let first = write_rollout(
+            home,
+            FIRST_ID,
+            "2025-10-01",
+            &rollout(
+                FIRST_ID,
+                "2025-10-01",
+                "/work/app",
+                &[("10:00:01", "Fix it")],
+            ),
+        );
+        write_rollout(
+            home,
+            SECOND_ID,
+            "2025-10-02",
+            &rollout(
+                SECOND_ID,
+                "2025-10-02",
+                "/work/other",
+                &[("10:00:01", "Hi")],
+            ),
+        );
+
+        let mut state = ListingState::empty();
+        scan_new_rollouts(&mut state, home);
+        assert_eq!(state.scanned_day.as_deref(), Some("2025/10/02"));
+        let (page, has_more) = list_page(&mut state, home, "/work/app", None, 1, |_| true);
+        assert_eq!(ids(&page), vec![FIRST_ID]);
+        assert!(!has_more);
+
+        // New rollouts are found in the last scanned day and later ones.
+        let third = write_rollout(
+            home,
+            THIRD_ID,
+            "2025-10-03",
+            &rollout(THIRD_ID, "2025-10-03", "/work/app", &[]),
+        );
+        scan_new_rollouts(&mut state, home);
+        assert_eq!(state.scanned_day.as_deref(), Some("2025/10/03"));
+        let (page, _) = list_page(&mut state, home, "/work/app", None, 10, |_| true);
+        assert_eq!(ids(&page), vec![FIRST_ID]);
+
+        // Rows picked for a page pick up appended lines.
+        std::fs::write(
+            &third,
+            rollout(
+                THIRD_ID,
+                "2025-10-03",
+                "/work/app",
+                &[("10:00:01", "Add tests"), ("10:05:00", "And docs")],
+            ),
+        )
+        .unwrap();
+        scan_new_rollouts(&mut state, home);
+        let (page, has_more) = list_page(&mut state, home, "/work/app", None, 1, |_| true);
+        assert_eq!(ids(&page), vec![THIRD_ID]);
+        assert_eq!(page[0].first_message.as_deref(), Some("Add tests"));
+        assert!(has_more);
+        let cursor = ConversationListCursor {
+            timestamp: page[0].timestamp.clone().unwrap(),
+            conversation_id: THIRD_ID.to_string(),
+        };
+        let (page, has_more) = list_page(&mut state, home, "/work/app", Some(&cursor), 1, |_| true);
+        assert_eq!(ids(&page), vec![FIRST_ID]);
+        assert!(!has_more);
+
+        let mut content = std::fs::read_to_string(&first).unwrap();
+        content.push_str(&message_line("2025-10-05", "09:00:00", "Later"));
+        std::fs::write(&first, content).unwrap();
+        std::fs::remove_file(&third).unwrap();
+        let (page, _) = list_page(&mut state, home, "/work/app", None, 10, |_| true);
+        assert_eq!(ids(&page), vec![FIRST_ID]);
+        assert_eq!(
+            page[0].last_activity.as_deref(),
+            Some("2025-10-05T09:00:00.000Z")
+        );
+        assert!(!state.rollouts.contains_key(&third));
+
+        let archived = crate::conversation_archive::archive_rollout(home, &first).unwrap();
+        update_rollout(&mut state, home, &first);
+        update_rollout(&mut state, home, &archived);
+        let (page, _) = list_page(&mut state, home, "/work/app", None, 10, |conversation| {
+            !conversation.archived
+        });
+        assert!(page.is_empty());
+        assert!(state.rollouts[&archived].archived);
+    }
+}
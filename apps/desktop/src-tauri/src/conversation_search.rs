@@ -0,0 +1,810 @@
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::Seek;
+use std::io::SeekFrom;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use anyhow::Context;
+use anyhow::Result;
+use codex_core::INTERACTIVE_SESSION_SOURCES;
+use codex_core::parse_turn_item;
+use codex_protocol::ConversationId;
+use codex_protocol::items::TurnItem;
+use codex_protocol::protocol::EventMsg;
+use codex_protocol::protocol::RolloutItem;
+use codex_protocol::protocol::RolloutLine;
+use serde::Deserialize;
+use serde::Serialize;
+use tokio::sync::Mutex;
+use ts_rs::TS;
+
+use crate::atomic_file::write_atomically;
+use crate::conversation_archive::ARCHIVED_SESSIONS_SUBDIR;
+use crate::conversation_archive::SESSIONS_SUBDIR;
+use crate::conversation_listing::IndexedConversation;
+
+/// Bumped whenever the persisted layout or tokenizer changes; older indexes
+/// are rebuilt from scratch.
+const INDEX_VERSION: u32 = 3;
+/// Terms shorter or longer than this are not indexed. Long runs are mostly
+/// hashes and base64 blobs.
+const MIN_TERM_CHARS: usize = 2;
+const MAX_TERM_CHARS: usize = 48;
+/// First user message kept per conversation for result rows.
+pub(crate) const FIRST_MESSAGE_MAX_LEN: usize = 200;
+/// Characters of context kept on each side of the first match in a snippet.
+const SNIPPET_CONTEXT_CHARS: usize = 80;
+const BM25_K1: f32 = 1.2;
+const BM25_B: f32 = 0.75;
+
+/// Where a snippet was taken from.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, TS)]
+#[serde(rename_all = "kebab-case")]
+pub enum SearchEntryKind {
+    UserMessage,
+    AgentMessage,
+    Reasoning,
+}
+
+/// Matched range of a snippet, in UTF-16 code units.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchHighlight {
+    pub start: u32,
+    pub end: u32,
+}
+
+/// Excerpt of the message that best matches a query.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchSnippet {
+    pub kind: SearchEntryKind,
+    pub text: String,
+    pub highlights: Vec<SearchHighlight>,
+}
+
+/// A conversation matching a query, best first.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchMatch {
+    pub conversation: IndexedConversation,
+    pub score: f32,
+    pub snippet: Option<SearchSnippet>,
+}
+
+/// Term counts of one rollout file, up to `indexed_len` bytes.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct IndexedRollout {
+    conversation_id: ConversationId,
+    cwd: PathBuf,
+    timestamp: Option<String>,
+    first_message: Option<String>,
+    model_provider: Option<String>,
+    last_activity: Option<String>,
+    /// Stored under the archived sessions directory.
+    #[serde(default)]
+    archived: bool,
+    interactive: bool,
+    indexed_len: u64,
+    /// Modification time of the file when it was last read.
+    #[serde(default)]
+    modified: Option<SystemTime>,
+    token_count: u32,
+    terms: BTreeMap<String, u32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+struct SearchIndexState {
+    version: u32,
+    rollouts: HashMap<PathBuf, IndexedRollout>,
+}
+
+/// Inverted index over the rollout files in `codex_home`. Rollouts are
+/// append-only, so each refresh only reads the bytes written since the last.
+#[derive(Clone)]
+pub struct ConversationSearchIndex {
+    state: Arc<Mutex<SearchIndexState>>,
+    state_path: PathBuf,
+}
+
+impl ConversationSearchIndex {
+    pub fn new(state_path: PathBuf) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(SearchIndexState {
+                version: INDEX_VERSION,
+                ..SearchIndexState::default()
+            })),
+            state_path,
+        }
+    }
+
+    pub async fn load_state(&self) -> Result<()> {
+        let content = match tokio::fs::read_to_string(&self.state_path).await {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Ok(());
+            }
+            Err(e) => {
+                return Err(e).context("Failed to read conversation index file");
+            }
+        };
+
+        let loaded_state: SearchIndexState =
+            serde_json::from_str(&content).context("Failed to parse conversation index JSON")?;
+        if loaded_state.version != INDEX_VERSION {
+            return Ok(());
+        }
+
+        let mut state = self.state.lock().await;
+        *state = loaded_state;
+
+        Ok(())
+    }
+
+    /// Bring the index up to date with the rollouts under `codex_home`, then
+    /// return up to `limit` interactive conversations matching every term of
+    /// `query` that `include` accepts. The last term also matches as a prefix.
+    pub async fn search(
+        &self,
+        codex_home: &Path,
+        query: &str,
+        limit: usize,
+        include: impl FnMut(&IndexedConversation) -> bool + Send + 'static,
+    ) -> Result<Vec<SearchMatch>> {
+        let query = Query::parse(query);
+        self.with_refreshed(codex_home, move |state| rank(state, &query, limit, include))
+            .await
+    }
+
+    /// Bring the index up to date without searching, so the first search
+    /// does not pay for indexing everything.
+    pub async fn refresh(&self, codex_home: &Path) -> Result<()> {
+        self.with_refreshed(codex_home, |_| ()).await
+    }
+
+    async fn with_refreshed<T: Send + 'static>(
+        &self,
+        codex_home: &Path,
+        read: impl FnOnce(&SearchIndexState) -> T + Send + 'static,
+    ) -> Result<T> {
+        let state = self.state.clone();
+        let state_path = self.state_path.clone();
+        let codex_home = codex_home.to_path_buf();
+
+        tokio::task::spawn_blocking(move || {
+            let mut state = state.blocking_lock();
+            if refresh(&mut state, &codex_home) {
+                save_state(&state_path, &state)?;
+            }
+            Ok(read(&state))
+        })
+        .await
+        .context("Conversation index task failed")?
+    }
+}
+
+fn save_state(state_path: &Path, state: &SearchIndexState) -> Result<()> {
+    let json = serde_json::to_string(state).context("Failed to serialize conversation index")?;
+    write_atomically(state_path, json.as_bytes()).context("Failed to save conversation index")
+}
+
+/// Index new rollouts and appended lines in the live and archived session
+/// directories, and drop rollouts that are gone. Returns whether anything
+/// changed.
+fn refresh(state: &mut SearchIndexState, codex_home: &Path) -> bool {
+    let mut paths = Vec::new();
+    collect_rollouts(&codex_home.join(SESSIONS_SUBDIR), &mut paths);
+    let live_count = paths.len();
+    collect_rollouts(&codex_home.join(ARCHIVED_SESSIONS_SUBDIR), &mut paths);
+    let present: HashSet<&PathBuf> = paths.iter().collect();
+
+    let before = state.rollouts.len();
+    state.rollouts.retain(|path, _| present.contains(path));
+    let mut changed = state.rollouts.len() != before;
+
+    for (position, path) in paths.iter().enumerate() {
+        let Ok(metadata) = std::fs::metadata(path) else {
+            continue;
+        };
+        let len = metadata.len();
+        let modified = metadata.modified().ok();
+        let existing = state.rollouts.remove(path);
+        let previous = existing
+            .as_ref()
+            .map(|rollout| (rollout.indexed_len, rollout.modified));
+        let (rollout, offset) = match existing {
+            Some(rollout) if rollout.indexed_len == len && rollout.modified == modified => {
+                state.rollouts.insert(path.clone(), rollout);
+                continue;
+            }
+            Some(rollout) if rollout.indexed_len < len => {
+                let offset = rollout.indexed_len;
+                (Some(rollout), offset)
+            }
+            // New, or rewritten: shorter than indexed, or the same length
+            // with a different modification time.
+            _ => (None, 0),
+        };
+        let indexed = match index_rollout(path, rollout, offset) {
+            Ok(Some(mut rollout)) => {
+                rollout.archived = position >= live_count;
+                rollout.modified = modified;
+                let indexed = (rollout.indexed_len, rollout.modified);
+                state.rollouts.insert(path.clone(), rollout);
+                Some(indexed)
+            }
+            Ok(None) => None,
+            Err(e) => {
+                log::warn!("Failed to index rollout {}: {}", path.display(), e);
+                None
+            }
+        };
+        changed |= indexed != previous;
+    }
+
+    changed
+}
+
+pub(crate) fn collect_rollouts(dir: &Path, paths: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        if file_type.is_dir() {
+            collect_rollouts(&path, paths);
+        } else if entry
+            .file_name()
+            .to_str()
+            .is_some_and(|name| name.starts_with("rollout-") && name.ends_with(".jsonl"))
+        {
+            paths.push(path);
+        }
+    }
+}
+
+/// Searchable content of one rollout line.
+pub(crate) enum RolloutEntry {
+    Meta {
+        conversation_id: ConversationId,
+        cwd: PathBuf,
+        timestamp: String,
+        model_provider: Option<String>,
+        interactive: bool,
+    },
+    Text(SearchEntryKind, String),
+}
+
+pub(crate) fn parse_line(line: &[u8]) -> Option<RolloutLine> {
+    serde_json::from_slice(line).ok()
+}
+
+pub(crate) fn rollout_entry(item: RolloutItem) -> Option<RolloutEntry> {
+    match item {
+        RolloutItem::SessionMeta(meta_line) => {
+            let meta = meta_line.meta;
+            Some(RolloutEntry::Meta {
+                conversation_id: meta.id,
+                cwd: meta.cwd,
+                timestamp: meta.timestamp,
+                model_provider: meta.model_provider,
+                interactive: INTERACTIVE_SESSION_SOURCES.contains(&meta.source),
+            })
+        }
+        RolloutItem::EventMsg(EventMsg::UserMessage(event)) => Some(RolloutEntry::Text(
+            SearchEntryKind::UserMessage,
+            event.message,
+        )),
+        RolloutItem::EventMsg(EventMsg::AgentMessage(event)) => Some(RolloutEntry::Text(
+            SearchEntryKind::AgentMessage,
+            event.message,
+        )),
+        RolloutItem::EventMsg(EventMsg::AgentReasoning(event)) => {
+            Some(RolloutEntry::Text(SearchEntryKind::Reasoning, event.text))
+        }
+        _ => None,
+    }
+}
+
+/// Text of each user message recorded in a rollout, counted the way codex
+/// counts them when forking a conversation.
+pub(crate) fn rollout_user_messages(path: &Path) -> Result<Vec<String>> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut messages = Vec::new();
+    for line in reader.split(b'\n') {
+        let Some(parsed) = parse_line(&line?) else {
+            continue;
+        };
+        if let RolloutItem::ResponseItem(item) = parsed.item
+            && let Some(TurnItem::UserMessage(user)) = parse_turn_item(&item)
+        {
+            messages.push(user.message());
+        }
+    }
+    Ok(messages)
+}
+
+/// Read the complete lines of `path` past `offset` into `rollout`. Returns
+/// `None` while the session metadata line has not been written yet.
+fn index_rollout(
+    path: &Path,
+    rollout: Option<IndexedRollout>,
+    offset: u64,
+) -> Result<Option<IndexedRollout>> {
+    let mut file = File::open(path)?;
+    file.seek(SeekFrom::Start(offset))?;
+    let mut reader = BufReader::new(file);
+    let mut rollout = rollout;
+    let mut indexed_len = offset;
+    let mut line = Vec::new();
+
+    loop {
+        line.clear();
+        let read = reader.read_until(b'\n', &mut line)?;
+        // Stop before a line that is still being written.
+        if read == 0 || line.last() != Some(&b'\n') {
+            break;
+        }
+        indexed_len += read as u64;
+        let Some(parsed) = parse_line(&line) else {
+            continue;
+        };
+        if let Some(rollout) = rollout.as_mut() {
+            rollout.last_activity = Some(parsed.timestamp.clone());
+        }
+
+        match rollout_entry(parsed.item) {
+            Some(RolloutEntry::Meta {
+                conversation_id,
+                cwd,
+                timestamp,
+                model_provider,
+                interactive,
+            }) if rollout.is_none() => {
+                rollout = Some(IndexedRollout {
+                    conversation_id,
+                    cwd,
+                    timestamp: Some(timestamp),
+                    first_message: None,
+                    model_provider,
+                    last_activity: Some(parsed.timestamp),
+                    archived: false,
+                    interactive,
+                    indexed_len: 0,
+                    modified: None,
+                    token_count: 0,
+                    terms: BTreeMap::new(),
+                });
+            }
+            Some(RolloutEntry::Text(kind, text)) => {
+                let Some(rollout) = rollout.as_mut() else {
+                    continue;
+                };
+                if kind == SearchEntryKind::UserMessage && rollout.first_message.is_none() {
+                    rollout.first_message = Some(truncate_chars(&text, FIRST_MESSAGE_MAX_LEN));
+                }
+                for (_, term) in tokenize(&text) {
+                    *rollout.terms.entry(term).or_default() += 1;
+                    rollout.token_count += 1;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(rollout.map(|mut rollout| {
+        rollout.indexed_len = indexed_len;
+        rollout
+    }))
+}
+
+/// Truncate `text` to `max_chars` characters, marking the cut with `…`.
+pub(crate) fn truncate_chars(text: &str, max_chars: usize) -> String {
+    match text.char_indices().nth(max_chars) {
+        Some((index, _)) => format!("{}…", &text[..index]),
+        None => text.to_string(),
+    }
+}
+
+/// Lowercased words of `text` with their byte ranges. Words are runs of
+/// alphanumerics and `_`; very short and very long runs are skipped.
+fn tokenize(text: &str) -> Vec<((usize, usize), String)> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+    for (index, ch) in text.char_indices().chain([(text.len(), ' ')]) {
+        let is_word = ch.is_alphanumeric() || ch == '_';
+        match (start, is_word) {
+            (None, true) => start = Some(index),
+            (Some(begin), false) => {
+                let word = &text[begin..index];
+                let chars = word.chars().count();
+                if (MIN_TERM_CHARS..=MAX_TERM_CHARS).contains(&chars) {
+                    tokens.push(((begin, index), word.to_lowercase()));
+                }
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    tokens
+}
+
+/// Terms that must all match; `prefix` matches any term starting with it.
+struct Query {
+    exact: Vec<String>,
+    prefix: Option<String>,
+}
+
+impl Query {
+    fn parse(query: &str) -> Self {
+        let mut terms: Vec<String> = tokenize(query).into_iter().map(|(_, term)| term).collect();
+        let ends_in_word = query
+            .chars()
+            .last()
+            .is_some_and(|ch| ch.is_alphanumeric() || ch == '_');
+        let prefix = if ends_in_word { terms.pop() } else { None };
+        terms.sort();
+        terms.dedup();
+        if let Some(prefix) = &prefix {
+            terms.retain(|term| term != prefix);
+        }
+        Self {
+            exact: terms,
+            prefix,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.exact.is_empty() && self.prefix.is_none()
+    }
+
+    fn term_count(&self) -> usize {
+        self.exact.len() + usize::from(self.prefix.is_some())
+    }
+
+    /// Index of the query term `word` satisfies, if any.
+    fn matches(&self, word: &str) -> Option<usize> {
+        if let Some(position) = self.exact.iter().position(|term| term == word) {
+            return Some(position);
+        }
+        self.prefix
+            .as_ref()
+            .filter(|prefix| word.starts_with(prefix.as_str()))
+            .map(|_| self.exact.len())
+    }
+
+    /// Frequency of each query term in `rollout`; the prefix counts every
+    /// term it starts.
+    fn frequencies(&self, rollout: &IndexedRollout) -> Vec<u32> {
+        let mut frequencies: Vec<u32> = self
+            .exact
+            .iter()
+            .map(|term| rollout.terms.get(term).copied().unwrap_or(0))
+            .collect();
+        if let Some(prefix) = &self.prefix {
+            frequencies.push(
+                rollout
+                    .terms
+                    .range(prefix.clone()..)
+                    .take_while(|(term, _)| term.starts_with(prefix.as_str()))
+                    .map(|(_, count)| count)
+                    .sum(),
+            );
+        }
+        frequencies
+    }
+}
+
+/// Score matching conversations with BM25 and attach snippets to the best.
+fn rank(
+    state: &SearchIndexState,
+    query: &Query,
+    limit: usize,
+    mut include: impl FnMut(&IndexedConversation) -> bool,
+) -> Vec<SearchMatch> {
+    if query.is_empty() || limit == 0 {
+        return Vec::new();
+    }
+
+    let rollouts: Vec<(&PathBuf, &IndexedRollout)> = state
+        .rollouts
+        .iter()
+        .filter(|(_, rollout)| rollout.interactive)
+        .collect();
+    if rollouts.is_empty() {
+        return Vec::new();
+    }
+    let average_len = rollouts
+        .iter()
+        .map(|(_, rollout)| rollout.token_count as f32)
+        .sum::<f32>()
+        / rollouts.len() as f32;
+
+    // Document frequency per query term, counted over all conversations so
+    // scores do not depend on the workspace filter.
+    let mut document_frequency = vec![0u32; query.term_count()];
+    let mut candidates = Vec::new();
+    for (path, rollout) in &rollouts {
+        let frequencies = query.frequencies(rollout);
+        for (df, &tf) in document_frequency.iter_mut().zip(&frequencies) {
+            if tf > 0 {
+                *df += 1;
+            }
+        }
+        if frequencies.iter().all(|&tf| tf > 0) {
+            candidates.push((*path, *rollout, frequencies));
+        }
+    }
+
+    let total = rollouts.len() as f32;
+    let mut scored: Vec<(f32, &PathBuf, &IndexedRollout)> = candidates
+        .into_iter()
+        .filter(|(path, rollout, _)| include(&listing(path, rollout)))
+        .map(|(path, rollout, frequencies)| {
+            let length_norm = 1.0 - BM25_B + BM25_B * rollout.token_count as f32 / average_len;
+            let score = frequencies
+                .iter()
+                .zip(&document_frequency)
+                .map(|(&tf, &df)| {
+                    let df = df as f32;
+                    let idf = ((total - df + 0.5) / (df + 0.5) + 1.0).ln();
+                    let tf = tf as f32;
+                    idf * tf * (BM25_K1 + 1.0) / (tf + BM25_K1 * length_norm)
+                })
+                .sum();
+            (score, path, rollout)
+        })
+        .collect();
+    scored.sort_by(|a, b| {
+        b.0.total_cmp(&a.0)
+            .then_with(|| b.2.timestamp.cmp(&a.2.timestamp))
+    });
+    scored.truncate(limit);
+
+    scored
+        .into_iter()
+        .map(|(score, path, rollout)| SearchMatch {
+            conversation: listing(path, rollout),
+            score,
+            snippet: best_snippet(path, rollout.indexed_len, query),
+        })
+        .collect()
+}
+
+fn listing(path: &Path, rollout: &IndexedRollout) -> IndexedConversation {
+    IndexedConversation {
+        conversation_id: rollout.conversation_id,
+        path: path.to_path_buf(),
+        cwd: rollout.cwd.clone(),
+        timestamp: rollout.timestamp.clone(),
+        first_message: rollout.first_message.clone(),
+        model_provider: rollout.model_provider.clone(),
+        last_activity: rollout.last_activity.clone(),
+        archived: rollout.archived,
+    }
+}
+
+/// Excerpt of the indexed entry in `path` that matches the most query terms.
+fn best_snippet(path: &Path, indexed_len: u64, query: &Query) -> Option<SearchSnippet> {
+    let file = File::open(path).ok()?;
+    let mut reader = BufReader::new(file);
+    let mut best: Option<(usize, SearchEntryKind, String)> = None;
+    let mut best_ranges = Vec::new();
+    let mut read_len = 0u64;
+    let mut line = Vec::new();
+
+    while read_len < indexed_len {
+        line.clear();
+        let read = reader.read_until(b'\n', &mut line).ok()?;
+        if read == 0 {
+            break;
+        }
+        read_len += read as u64;
+        let Some(RolloutEntry::Text(kind, text)) =
+            parse_line(&line).and_then(|parsed| rollout_entry(parsed.item))
+        else {
+            continue;
+        };
+
+        let mut matched_terms = HashSet::new();
+        let mut ranges = Vec::new();
+        for (range, word) in tokenize(&text) {
+            if let Some(term) = query.matches(&word) {
+                matched_terms.insert(term);
+                ranges.push(range);
+            }
+        }
+        if matched_terms.len() > best.as_ref().map_or(0, |(count, ..)| *count) {
+            let complete = matched_terms.len() == query.term_count();
+            best = Some((matched_terms.len(), kind, text));
+            best_ranges = ranges;
+            if complete {
+                break;
+            }
+        }
+    }
+
+    let (_, kind, text) = best?;
+    Some(snippet(kind, &text, &best_ranges))
+}
+
+/// Cut a window around the first match and convert the matches inside it to
+/// UTF-16 ranges of the returned text.
+fn snippet(kind: SearchEntryKind, text: &str, ranges: &[(usize, usize)]) -> SearchSnippet {
+    let first = ranges.first().map_or(0, |(start, _)| *start);
+    let window_start = text[..first]
+        .char_indices()
+        .rev()
+        .nth(SNIPPET_CONTEXT_CHARS - 1)
+        .map_or(0, |(index, _)| index);
+    let window_end = text[first..]
+        .char_indices()
+        .nth(2 * SNIPPET_CONTEXT_CHARS)
+        .map_or(text.len(), |(index, _)| first + index);
+
+    let lead = if window_start > 0 { "…" } else { "" };
+    let trail = if window_end < text.len() { "…" } else { "" };
+    let body = &text[window_start..window_end];
+    let utf16_len = |text: &str| text.encode_utf16().count() as u32;
+    let highlights = ranges
+        .iter()
+        .filter(|(start, end)| *start >= window_start && *end <= window_end)
+        .map(|(start, end)| {
+            let offset = utf16_len(lead) + utf16_len(&text[window_start..*start]);
+            SearchHighlight {
+                start: offset,
+                end: offset + utf16_len(&text[*start..*end]),
+            }
+        })
+        .collect();
+
+    SearchSnippet {
+        kind,
+        // Newlines become spaces; both are one UTF-16 unit, so highlights hold.
+        text: format!("{}{}{}", lead, body.replace(['\n', '\r'], " "), trail),
+        highlights,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIRST_ID: &str = "0199a213-81c0-7800-8aa1-bbab2a035a53";
+    const SECOND_ID: &str = "0199a213-81c0-7800-8aa1-bbab2a035a54";
+
+    fn meta_line(id: &str, cwd: &str) -> String {
+        format!(
+            r#"{{"timestamp":"2025-10-01T10:00:00.000Z","type":"session_meta","payload":{{"id":"{id}","timestamp":"2025-10-01T10:00:00.000Z","cwd":"{cwd}","originator":"pasture","cli_version":"0.0.0","instructions":null,"source":"cli"}}}}"#
+        )
+    }
+
+    fn event_line(kind: &str, field: &str, text: &str) -> String {
+        format!(
+            r#"{{"timestamp":"2025-10-01T10:00:01.000Z","type":"event_msg","payload":{{"type":"{kind}","{field}":{}}}}}"#,
+            serde_json::to_string(text).unwrap()
+        )
+    }
+
+    #[test]
+    fn indexes_appended_lines_for_search() {
+        let dir = tempfile::tempdir().unwrap();
+        let day_dir = dir.path().join("sessions/2025/10/01");
+        std::fs::create_dir_all(&day_dir).unwrap();
+        let first = day_dir.join(format!("rollout-2025-10-01T10-00-00-{FIRST_ID}.jsonl"));
+        let second = day_dir.join(format!("rollout-2025-10-01T11-00-00-{SECOND_ID}.jsonl"));
+        let write_lines = |path: &Path, lines: &[String]| {
+            let content: String = lines.iter().map(|line| format!("{line}\n")).collect();
+            std::fs::write(path, content).unwrap();
+        };
+        write_lines(
+            &first,
+            &[
+                meta_line(FIRST_ID, "/work/app"),
+                event_line("user_message", "message", "Fix the flaky parser test"),
+                event_line(
+                    "agent_reasoning",
+                    "text",
+                    "The parser drops the trailing token",
+                ),
+            ],
+        );
+        write_lines(
+            &second,
+            &[
+                meta_line(SECOND_ID, "/work/other"),
+                event_line("user_message", "message", "Rename the config loader"),
+            ],
+        );
+
+        let mut state = SearchIndexState::default();
+        assert!(refresh(&mut state, dir.path()));
+        assert!(!refresh(&mut state, dir.path()));
+
+        let hits = rank(&state, &Query::parse("parser tok"), 10, |_| true);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].conversation.conversation_id.to_string(), FIRST_ID);
+        assert_eq!(
+            hits[0].conversation.first_message.as_deref(),
+            Some("Fix the flaky parser test")
+        );
+        let snippet = hits[0].snippet.as_ref().unwrap();
+        assert_eq!(snippet.kind, SearchEntryKind::Reasoning);
+        assert_eq!(
+            snippet.highlights,
+            vec![
+                SearchHighlight { start: 4, end: 10 },
+                SearchHighlight { start: 30, end: 35 },
+            ]
+        );
+
+        // A line still being written is left for the next refresh.
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&second)
+            .unwrap();
+        let appended = event_line("agent_message", "message", "The parser config is lazy");
+        std::io::Write::write_all(&mut file, format!("{appended}\n{{\"time").as_bytes()).unwrap();
+        assert!(refresh(&mut state, dir.path()));
+        assert!(!refresh(&mut state, dir.path()));
+
+        assert_eq!(rank(&state, &Query::parse("parser"), 10, |_| true).len(), 2);
+        let scoped = rank(&state, &Query::parse("parser"), 10, |conversation| {
+            conversation.cwd == Path::new("/work/other")
+        });
+        assert_eq!(scoped.len(), 1);
+        let snippet = scoped[0].snippet.as_ref().unwrap();
+        assert_eq!(snippet.kind, SearchEntryKind::AgentMessage);
+        assert_eq!(snippet.text, "The parser config is lazy");
+
+        let archived = crate::conversation_archive::archive_rollout(dir.path(), &first).unwrap();
+        assert!(refresh(&mut state, dir.path()));
+        assert!(state.rollouts[&archived].archived);
+        assert!(!state.rollouts[&second].archived);
+
+        std::fs::remove_file(&archived).unwrap();
+        assert!(refresh(&mut state, dir.path()));
+        assert_eq!(state.rollouts.len(), 1);
+    }
+
+    #[test]
+    fn reindexes_rollouts_rewritten_to_the_same_length() {
+        let dir = tempfile::tempdir().unwrap();
+        let day_dir = dir.path().join("sessions/2025/10/01");
+        std::fs::create_dir_all(&day_dir).unwrap();
+        let path = day_dir.join(format!("rollout-2025-10-01T10-00-00-{FIRST_ID}.jsonl"));
+        let write_message = |message: &str| {
+            let content = format!(
+                "{}\n{}\n",
+                meta_line(FIRST_ID, "/work/app"),
+                event_line("user_message", "message", message)
+            );
+            std::fs::write(&path, content).unwrap();
+        };
+
+        write_message("Fix the parser");
+        let mut state = SearchIndexState::default();
+        assert!(refresh(&mut state, dir.path()));
+
+        write_message("Fix the loader");
+        let file = std::fs::File::options().write(true).open(&path).unwrap();
+        let indexed_at = state.rollouts[&path].modified.unwrap();
+        file.set_modified(indexed_at + std::time::Duration::from_secs(1))
+            .unwrap();
+        assert!(refresh(&mut state, dir.path()));
+
+        assert!(rank(&state, &Query::parse("parser"), 10, |_| true).is_empty());
+        assert_eq!(rank(&state, &Query::parse("loader"), 10, |_| true).len(), 1);
+    }
+}
@@ -1,4 +1,5 @@
 use std::collections::HashMap;
+use std::collections::HashSet;
 use std::sync::Arc;
 use std::sync::Weak;
 use tauri::AppHandle;
@@ -6,6 +7,7 @@ use tauri::Emitter;
 use tauri::Manager;
 use tokio::sync::Mutex;
 use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
 use uuid::Uuid;
 
 use chrono::Utc;
@@ -13,6 +15,9 @@ use codex_core::CodexConversation;
 
 use crate::events::CodexEvent;
 use crate::events::ConversationEventPayload;
+use crate::events::SnapshotWarningPayload;
+use crate::review_snapshots::ReviewSnapshots;
+use crate::review_snapshots::SnapshotWarningSink;
 use crate::workspace_manager::WorkspaceManager;
 use codex_protocol::ConversationId;
 use codex_protocol::protocol::Event;
@@ -28,6 +33,8 @@ pub struct EventSubscriptionManager {
     subscription_targets: Arc<Mutex<HashMap<Uuid, String>>>,
     /// Map of subscription ID to the conversation handle we are streaming from
     subscription_conversations: Arc<Mutex<HashMap<Uuid, Weak<CodexConversation>>>>,
+    /// Map of subscription ID to the task forwarding its events
+    subscription_tasks: Arc<Mutex<HashMap<Uuid, JoinHandle<()>>>>,
 }
 
 impl EventSubscriptionManager {
@@ -37,6 +44,7 @@ impl EventSubscriptionManager {
             conversation_subscriptions: Arc::new(Mutex::new(HashMap::new())),
             subscription_targets: Arc::new(Mutex::new(HashMap::new())),
             subscription_conversations: Arc::new(Mutex::new(HashMap::new())),
+            subscription_tasks: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
@@ -116,10 +124,14 @@ impl EventSubscriptionManager {
         let event_manager = self.clone_for_cleanup();
         let targets = self.subscription_targets.clone();
         let handles = self.subscription_conversations.clone();
+        let tasks = self.subscription_tasks.clone();
         let target_label = window_label.clone();
         let conversation_id_for_task = conversation_id;
 
-        tokio::spawn(async move {
+        // Held until the handle is stored so a task that ends right away
+        // cannot miss removing it.
+        let mut running_tasks = self.subscription_tasks.lock().await;
+        let task = tokio::spawn(async move {
             let conversation_id = conversation_id_for_task;
             loop {
                 tokio::select! {
@@ -147,6 +159,8 @@ impl EventSubscriptionManager {
                                 targets.remove(&subscription_id);
                                 let mut handles = handles.lock().await;
                                 handles.remove(&subscription_id);
+                                let mut tasks = tasks.lock().await;
+                                tasks.remove(&subscription_id);
                                 break;
                             }
                         };
@@ -181,6 +195,7 @@ impl EventSubscriptionManager {
                 }
             }
         });
+        running_tasks.insert(subscription_id, task);
 
         subscription_id
     }
@@ -191,6 +206,7 @@ impl EventSubscriptionManager {
             conversation_subscriptions: self.conversation_subscriptions.clone(),
             subscription_targets: self.subscription_targets.clone(),
             subscription_conversations: self.subscription_conversations.clone(),
+            subscription_tasks: self.subscription_tasks.clone(),
         }
     }
 
@@ -216,6 +232,11 @@ impl EventSubscriptionManager {
             handles.remove(&subscription_id);
         }
 
+        {
+            let mut tasks = self.subscription_tasks.lock().await;
+            tasks.remove(&subscription_id);
+        }
+
         match sender {
             Some(sender) => {
                 let _ = sender.send(());
@@ -224,6 +245,38 @@ impl EventSubscriptionManager {
             None => Err(format!("Subscription not found: {}", subscription_id)),
         }
     }
+
+    /// Conversations whose events are currently forwarded to a window.
+    pub async fn subscribed_conversation_ids(&self) -> HashSet<String> {
+        let conv_subs = self.conversation_subscriptions.lock().await;
+        conv_subs.keys().map(|id| id.to_string()).collect()
+    }
+
+    /// Stop forwarding a conversation's events and wait until the event being
+    /// handled, if any, is done, so nothing touches its snapshots afterwards.
+    pub async fn unsubscribe_conversation(&self, conversation_id: &ConversationId) {
+        let Some(subscription_id) = ({
+            let conv_subs = self.conversation_subscriptions.lock().await;
+            conv_subs.get(conversation_id).copied()
+        }) else {
+            return;
+        };
+        let task = {
+            let mut tasks = self.subscription_tasks.lock().await;
+            tasks.remove(&subscription_id)
+        };
+        let _ = self.unsubscribe(subscription_id).await;
+        if let Some(task) = task
+            && let Err(err) = task.await
+        {
+            tracing::warn!(
+                "Subscription {} for conversation {} ended abnormally: {}",
+                subscription_id,
+                conversation_id,
+                err
+            );
+        }
+    }
 }
 
 /// Handle special event types that require additional backend processing
@@ -235,40 +288,31 @@ async fn handle_special_events(
     let Event { id: event_id, msg } = event;
 
     match msg {
+        EventMsg::UserMessage(user_message) => {
+            if let Some(snapshots) = conversation_snapshots(&app_handle, &conversation_id).await {
+                snapshots.record_user_prompt(&user_message.message).await;
+            }
+        }
         EventMsg::TurnDiff(_) => {
-            let Some(workspace_state) = app_handle.try_state::<WorkspaceManager>() else {
-                tracing::debug!("WorkspaceManager unavailable; skipping turn snapshot capture");
+            let Some(snapshots) = conversation_snapshots(&app_handle, &conversation_id).await
+            else {
                 return;
             };
 
-            let workspace_manager: WorkspaceManager = workspace_state.inner().clone();
-            let conversation_key = conversation_id.to_string();
-
-            if let Some(session) = workspace_manager
-                .get_active_conversation(&conversation_key)
-                .await
-            {
-                let snapshots = session.review_snapshots();
-                if let Err(err) = snapshots.ensure_base().await {
-                    tracing::debug!(
-                        "Failed to ensure baseline snapshot for conversation {}: {}",
-                        conversation_id,
-                        err
-                    );
-                    return;
-                }
+            if let Err(err) = snapshots.ensure_base().await {
+                tracing::debug!(
+                    "Failed to ensure baseline snapshot for conversation {}: {}",
+                    conversation_id,
+                    err
+                );
+                return;
+            }
 
-                if let Err(err) = snapshots.record_turn_snapshot(&event_id).await {
-                    tracing::debug!(
-                        "Failed to capture turn snapshot for conversation {}: {}",
-                        conversation_id,
-                        err
-                    );
-                }
-            } else {
+            if let Err(err) = snapshots.record_turn_snapshot(&event_id).await {
                 tracing::debug!(
-                    "No cached conversation session for {}; unable to capture snapshot",
-                    conversation_key
+                    "Failed to capture turn snapshot for conversation {}: {}",
+                    conversation_id,
+                    err
                 );
             }
         }
@@ -278,3 +322,44 @@ async fn handle_special_events(
         }
     }
 }
+
+/// Forward snapshot capture limit warnings to the renderer.
+pub fn snapshot_warning_sink(app_handle: AppHandle) -> SnapshotWarningSink {
+    SnapshotWarningSink::new(move |conversation_id, warning| {
+        let event = CodexEvent::SnapshotLimitExceeded {
+            payload: SnapshotWarningPayload {
+                conversation_id: conversation_id.to_string(),
+                warning,
+            },
+        };
+        if let Err(err) = app_handle.emit("codex-event", event) {
+            tracing::error!("Failed to emit snapshot warning: {}", err);
+        }
+    })
+}
+
+/// Look up the review snapshot tracker of an active conversation.
+async fn conversation_snapshots(
+    app_handle: &AppHandle,
+    conversation_id: &ConversationId,
+) -> Option<ReviewSnapshots> {
+    let Some(workspace_state) = app_handle.try_state::<WorkspaceManager>() else {
+        tracing::debug!("WorkspaceManager unavailable; skipping turn snapshot tracking");
+        return None;
+    };
+
+    let conversation_key = conversation_id.to_string();
+    match workspace_state
+        .get_active_conversation(&conversation_key)
+        .await
+    {
+        Some(session) => Some(session.review_snapshots()),
+        None => {
+            tracing::debug!(
+                "No cached conversation session for {}; unable to track snapshots",
+                conversation_key
+            );
+            None
+        }
+    }
+}
@@ -5,6 +5,7 @@ use ts_rs::TS;
 use codex_protocol::protocol::EventMsg;
 
 use crate::commands::auth::AuthState;
+use crate::review_capture::SnapshotLimitWarning;
 
 /// Payload emitted over the shared codex event channel.
 #[derive(Serialize, Deserialize, Debug, Clone, TS)]
@@ -17,6 +18,14 @@ pub struct ConversationEventPayload {
     pub timestamp: String,
 }
 
+/// Warning raised while capturing a conversation's review snapshots.
+#[derive(Serialize, Deserialize, Debug, Clone, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotWarningPayload {
+    pub conversation_id: String,
+    pub warning: SnapshotLimitWarning,
+}
+
 /// Union of events emitted to the renderer.
 #[derive(Serialize, Deserialize, Debug, Clone, TS)]
 #[serde(tag = "kind", rename_all = "kebab-case")]
@@ -25,4 +34,6 @@ pub enum CodexEvent {
     ConversationEvent { payload: ConversationEventPayload },
     #[serde(rename = "auth-updated")]
     AuthUpdated { payload: AuthState },
+    #[serde(rename = "snapshot-limit-exceeded")]
+    SnapshotLimitExceeded { payload: SnapshotWarningPayload },
 }
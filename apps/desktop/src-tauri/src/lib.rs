@@ -1,14 +1,30 @@
+mod atomic_file;
 mod codex_runtime;
 mod commands;
+mod conversation_archive;
+mod conversation_listing;
+mod conversation_search;
 mod env;
 mod event_listener;
 mod events;
 mod menu;
+mod review_blame;
+mod review_capture;
+mod review_diff;
+mod review_drafts;
+mod review_export;
+mod review_file_snapshots;
+mod review_intraline;
+mod review_retention;
+mod review_revert;
 mod review_snapshots;
 mod workspace_manager;
 
 pub mod ts_export;
 
+use conversation_listing::ConversationListIndex;
+use conversation_search::ConversationSearchIndex;
+use review_drafts::ReviewDrafts;
 use tauri::Manager;
 use workspace_manager::WorkspaceManager;
 
@@ -50,6 +66,8 @@ pub fn run() {
                     .map_err(|e| format!("Failed to initialize Codex runtime: {}", e))?;
                 Ok::<_, String>(runtime)
             })?;
+            let codex_home = codex_runtime.config().codex_home.clone();
+            let event_manager = codex_runtime.event_manager().clone();
             app.manage(codex_runtime);
 
             // Initialize workspace manager
@@ -63,16 +81,116 @@ pub fn run() {
                 app_data_dir.join("workspace-state.json")
             };
 
-            let workspace_manager = WorkspaceManager::new(state_file);
+            let review_snapshots_dir = if cfg!(debug_assertions) {
+                app_data_dir.join("review-snapshots.dev")
+            } else {
+                app_data_dir.join("review-snapshots")
+            };
+
+            let workspace_manager = WorkspaceManager::new(state_file, review_snapshots_dir)
+                .with_snapshot_warning_sink(event_listener::snapshot_warning_sink(
+                    app.handle().clone(),
+                ));
             tauri::async_runtime::block_on(async { workspace_manager.load_state().await })
                 .map_err(|e| {
                     log::error!("Failed to load workspace state: {}", e);
                     e.to_string()
                 })?;
 
+            let review_drafts_file = if cfg!(debug_assertions) {
+                app_data_dir.join("review-drafts.dev.json")
+            } else {
+                app_data_dir.join("review-drafts.json")
+            };
+            let review_drafts = ReviewDrafts::new(review_drafts_file);
+            if let Err(e) =
+                tauri::async_runtime::block_on(async { review_drafts.load_state().await })
+            {
+                log::warn!("Failed to load review drafts; starting empty: {}", e);
+            }
+
+            let retention_manager = workspace_manager.clone();
+            let retention_drafts = review_drafts.clone();
+            tauri::async_runtime::spawn(async move {
+                let Some(days) = retention_manager.get_snapshot_retention_days().await else {
+                    return;
+                };
+                let summary = match retention_manager
+                    .prune_review_snapshots(
+                        review_retention::retention_duration(days),
+                        &event_manager,
+                    )
+                    .await
+                {
+                    Ok(summary) => summary,
+                    Err(e) => {
+                        log::warn!("Failed to prune review snapshots: {}", e);
+                        return;
+                    }
+                };
+                log::info!(
+                    "Pruned review snapshots of {} conversations",
+                    summary.pruned_conversation_ids.len()
+                );
+                if let Err(e) = retention_drafts
+                    .discard_conversations(&summary.pruned_conversation_ids)
+                    .await
+                {
+                    log::warn!(
+                        "Failed to drop review drafts of pruned conversations: {}",
+                        e
+                    );
+                }
+            });
+
             app.manage(workspace_manager);
             log::info!("Workspace manager initialized successfully");
 
+            app.manage(review_drafts);
+
+            let search_index_file = if cfg!(debug_assertions) {
+                app_data_dir.join("conversation-search-index.dev.json")
+            } else {
+                app_data_dir.join("conversation-search-index.json")
+            };
+            let search_index = ConversationSearchIndex::new(search_index_file);
+            if let Err(e) =
+                tauri::async_runtime::block_on(async { search_index.load_state().await })
+            {
+                log::warn!(
+                    "Failed to load conversation search index; rebuilding: {}",
+                    e
+                );
+            }
+
+            let conversation_list_file = if cfg!(debug_assertions) {
+                app_data_dir.join("conversation-list-index.dev.json")
+            } else {
+                app_data_dir.join("conversation-list-index.json")
+            };
+            let conversation_list = ConversationListIndex::new(conversation_list_file);
+            if let Err(e) =
+                tauri::async_runtime::block_on(async { conversation_list.load_state().await })
+            {
+                log::warn!("Failed to load conversation listing; rebuilding: {}", e);
+            }
+
+            // Pick up changes made while the app was closed without holding
+            // up the first listing or search.
+            let background_list = conversation_list.clone();
+            let background_search = search_index.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = background_list.reconcile(&codex_home).await {
+                    log::warn!("Failed to reconcile conversation listing: {}", e);
+                }
+                if let Err(e) = background_search.refresh(&codex_home).await {
+                    log::warn!("Failed to refresh conversation search index: {}", e);
+                }
+            });
+
+            app.manage(conversation_list);
+            app.manage(search_index);
+
             // Build and install the native menu
             let menu =
                 tauri::async_runtime::block_on(async { menu::build_menu(app.handle()).await })
@@ -85,19 +203,44 @@ pub fn run() {
         })
         .invoke_handler(tauri::generate_handler![
             commands::conversations::list_conversations,
+            commands::conversations::search_conversations,
+            commands::conversations::archive_conversations,
+            commands::conversations::unarchive_conversations,
+            commands::conversations::delete_conversations,
             commands::conversations::initialize_conversation,
             commands::conversations::new_conversation,
+            commands::conversations::fork_conversation,
+            commands::conversations::edit_user_message,
             commands::conversations::send_user_message,
             commands::conversations::interrupt_conversation,
             commands::conversations::compact_conversation,
             commands::composer::get_composer_config,
             commands::composer::update_composer_config,
             commands::review::get_turn_diff_range,
+            commands::review::list_turn_diff_files,
+            commands::review::get_turn_diff_files,
             commands::review::list_turn_snapshots,
+            commands::review::reenable_turn_snapshots,
+            commands::review::restore_turn_snapshot,
+            commands::review::get_file_at_snapshot,
+            commands::review::get_turn_blame,
+            commands::review::revert_turn_changes,
+            commands::review::export_turn_patch,
+            commands::review::create_turn_branch,
+            commands::review::get_snapshot_retention,
+            commands::review::set_snapshot_retention,
+            commands::review::prune_review_snapshots,
+            commands::review::add_review_comment,
+            commands::review::update_review_comment,
+            commands::review::delete_review_comment,
+            commands::review::list_review_comments,
+            commands::review::submit_turn_review,
             commands::conversations::add_conversation_listener,
             commands::conversations::remove_conversation_listener,
             commands::approvals::respond_approval,
             commands::workspace::get_workspace_composer_defaults,
+            commands::workspace::get_workspace_snapshot_settings,
+            commands::workspace::set_workspace_snapshot_settings,
             commands::workspace::list_recent_workspaces,
             commands::workspace::open_workspace,
             commands::workspace::create_workspace_window,
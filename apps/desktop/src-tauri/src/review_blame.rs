@@ -0,0 +1,212 @@
+use serde::Deserialize;
+use serde::Serialize;
+use similar::Algorithm;
+use similar::DiffTag;
+use similar::capture_diff_slices;
+use ts_rs::TS;
+
+use crate::review_diff::MAX_SNAPSHOT_FILE_CONTENT_BYTES;
+use crate::review_diff::ReviewDiffError;
+use crate::review_diff::SnapshotSource;
+use crate::review_diff::decode_text;
+use crate::review_diff::read_snapshot_file;
+use crate::review_snapshots::TurnSnapshotSummary;
+
+/// Consecutive lines last changed by the same snapshot.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct TurnBlameHunk {
+    /// First line of the hunk, 1-based.
+    pub start_line: u32,
+    pub line_count: u32,
+    /// Snapshot that last changed these lines; `None` when they are
+    /// unchanged since the conversation base.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub turn_index: Option<u32>,
+}
+
+/// Per-line turn attribution for a file at a snapshot.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct FileTurnBlame {
+    pub path: String,
+    /// `false` when the file does not exist at the snapshot.
+    pub exists: bool,
+    pub binary: bool,
+    pub too_large: bool,
+    pub line_count: u32,
+    /// Empty for missing, binary and oversized files.
+    pub hunks: Vec<TurnBlameHunk>,
+}
+
+/// The file as of one snapshot, with the index into `turns` that last
+/// changed each line.
+#[derive(Default)]
+struct BlameState {
+    bytes: Option<Vec<u8>>,
+    lines: Vec<String>,
+    owners: Vec<Option<usize>>,
+    binary: bool,
+    too_large: bool,
+}
+
+/// Attribute each line of `path` to the turn that last changed it, walking
+/// `turns` (oldest first) from `base_commit`. The file is followed by path
+/// only; a rename shows up as the turn that created the new path.
+pub fn blame_file(
+    source: &SnapshotSource,
+    base_commit: &str,
+    turns: &[TurnSnapshotSummary],
+    path: &str,
+) -> Result<FileTurnBlame, ReviewDiffError> {
+    let mut state = BlameState::default();
+    advance(
+        &mut state,
+        read_snapshot_file(source, base_commit, path)?,
+        None,
+    );
+    for (position, turn) in turns.iter().enumerate() {
+        let bytes = read_snapshot_file(source, &turn.commit_id, path)?;
+        if bytes != state.bytes {
+            advance(&mut state, bytes, Some(position));
+        }
+    }
+
+    let exists = state.bytes.is_some();
+    let hunks = if state.binary || state.too_large {
+        Vec::new()
+    } else {
+        blame_hunks(&state.owners, turns)
+    };
+    Ok(FileTurnBlame {
+        path: path.to_string(),
+        exists,
+        binary: state.binary,
+        too_large: state.too_large,
+        line_count: state.lines.len() as u32,
+        hunks,
+    })
+}
+
+/// Move `state` to the next version of the file. Lines kept from the
+/// previous version keep their owner; the rest belong to `owner`.
+fn advance(state: &mut BlameState, bytes: Option<Vec<u8>>, owner: Option<usize>) {
+    let too_large = bytes
+        .as_ref()
+        .is_some_and(|bytes| bytes.len() > MAX_SNAPSHOT_FILE_CONTENT_BYTES);
+    let text = match &bytes {
+        Some(bytes) if !too_large => decode_text(bytes).map(|(_, text)| text),
+        _ => None,
+    };
+    let binary = bytes.is_some() && !too_large && text.is_none();
+    let lines: Vec<String> = text
+        .as_deref()
+        .map(|text| text.lines().map(str::to_string).collect())
+        .unwrap_or_default();
+
+    let mut owners = vec![owner; lines.len()];
+    for op in capture_diff_slices(Algorithm::Myers, &state.lines, &lines) {
+        let (tag, old_range, new_range) = op.as_tag_tuple();
+        if tag == DiffTag::Equal {
+            owners[new_range].copy_from_slice(&state.owners[old_range]);
+        }
+    }
+
+    *state = BlameState {
+        bytes,
+        lines,
+        owners,
+        binary,
+        too_large,
+    };
+}
+
+fn blame_hunks(owners: &[Option<usize>], turns: &[TurnSnapshotSummary]) -> Vec<TurnBlameHunk> {
+    let mut hunks = Vec::new();
+    let mut start = 0;
+    for (end, owner) in owners.iter().enumerate() {
+        let next = owners.get(end + 1);
+        if next == Some(owner) {
+            continue;
+        }
+        let turn = owner.map(|position| &turns[position]);
+        hunks.push(TurnBlameHunk {
+            start_line: start as u32 + 1,
+            line_count: (end + 1 - start) as u32,
+            event_id: turn.map(|turn| turn.event_id.clone()),
+            turn_index: turn.map(|turn| turn.turn_index),
+        });
+        start = end + 1;
+    }
+    hunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::review_snapshots::SnapshotOrigin;
+    use chrono::Utc;
+    use git2::Repository;
+    use git2::Signature;
+
+    fn commit_file(repo: &Repository, content: &str) -> String {
+        let blob = repo.blob(content.as_bytes()).unwrap();
+        let mut builder = repo.treebuilder(None).unwrap();
+        builder.insert("notes.txt", blob, 0o100644).unwrap();
+        let tree = repo.find_tree(builder.write().unwrap()).unwrap();
+        let signature = Signature::now("Pasture", "pasture@example.com").unwrap();
+        repo.commit(None, &signature, &signature, "snapshot", &tree, &[])
+            .unwrap()
+            .to_string()
+    }
+
+    fn turn(turn_index: u32, commit_id: String) -> TurnSnapshotSummary {
+        TurnSnapshotSummary {
+            event_id: format!("event-{}", turn_index),
+            commit_id,
+            turn_index,
+            captured_at: Utc::now(),
+            prompt: None,
+            stats: None,
+            origin: SnapshotOrigin::Turn,
+        }
+    }
+
+    #[test]
+    fn attributes_lines_to_the_turn_that_last_changed_them() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let base = commit_file(&repo, "one\ntwo\nthree\n");
+        let turns = vec![
+            turn(1, commit_file(&repo, "one\n2\nthree\nfour\n")),
+            turn(2, commit_file(&repo, "one\n2\nthree\nfour\n")),
+            turn(3, commit_file(&repo, "zero\none\n2\nthree\nfour\n")),
+        ];
+
+        let source = SnapshotSource::Git(dir.path().to_path_buf());
+        let blame = blame_file(&source, &base, &turns, "notes.txt").unwrap();
+        assert!(blame.exists);
+        assert_eq!(blame.line_count, 5);
+        let owners: Vec<_> = blame
+            .hunks
+            .iter()
+            .map(|hunk| (hunk.start_line, hunk.line_count, hunk.turn_index))
+            .collect();
+        assert_eq!(
+            owners,
+            vec![
+                (1, 1, Some(3)),
+                (2, 1, None),
+                (3, 1, Some(1)),
+                (4, 1, None),
+                (5, 1, Some(1)),
+            ]
+        );
+
+        let missing = blame_file(&source, &base, &turns, "other.txt").unwrap();
+        assert!(!missing.exists);
+        assert!(missing.hunks.is_empty());
+    }
+}
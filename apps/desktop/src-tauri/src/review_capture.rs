@@ -0,0 +1,307 @@
+use std::io::ErrorKind;
+use std::path::Path;
+
+use git2::Index;
+use git2::IndexEntry;
+use git2::IndexTime;
+use git2::Oid;
+use git2::Repository;
+use git2::Signature;
+use git2::StatusOptions;
+use serde::Deserialize;
+use serde::Serialize;
+use ts_rs::TS;
+
+use crate::review_diff::PathspecFilter;
+use crate::review_diff::ReviewDiffError;
+use crate::review_diff::open_repository;
+use crate::review_file_snapshots::is_executable;
+
+/// Skipped files listed in a [`SnapshotLimitWarning`].
+pub const MAX_REPORTED_PATHS: usize = 20;
+
+/// Per-workspace rules applied when capturing review snapshots.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotCaptureSettings {
+    /// Paths or globs left out of snapshots on top of the ignore files, in
+    /// the same syntax as diff pathspecs. Tracked files keep their committed
+    /// version.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ignore_globs: Vec<String>,
+    /// Files larger than this are left out of snapshots.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_file_bytes: Option<u64>,
+    /// Changed files stop being captured once their combined size passes
+    /// this, and a warning is raised.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_total_bytes: Option<u64>,
+}
+
+impl SnapshotCaptureSettings {
+    pub fn is_empty(&self) -> bool {
+        self.ignore_globs.is_empty()
+            && self.max_file_bytes.is_none()
+            && self.max_total_bytes.is_none()
+    }
+}
+
+/// Files a capture left out because of [`SnapshotCaptureSettings`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SnapshotCaptureReport {
+    /// Changed files matching `ignore_globs`; tracked ones keep their
+    /// committed version in the snapshot.
+    pub skipped_ignored: Vec<String>,
+    /// Files over `max_file_bytes`.
+    pub skipped_large_files: Vec<String>,
+    /// Files left out once `max_total_bytes` was reached.
+    pub skipped_over_total: Vec<String>,
+}
+
+impl SnapshotCaptureReport {
+    /// Every file whose snapshot content may not match the workspace. A
+    /// restore has to leave these alone.
+    pub fn skipped_paths(&self) -> Vec<String> {
+        let mut paths: Vec<String> = self
+            .skipped_ignored
+            .iter()
+            .chain(&self.skipped_large_files)
+            .chain(&self.skipped_over_total)
+            .cloned()
+            .collect();
+        paths.sort();
+        paths.dedup();
+        paths
+    }
+
+    /// Warning to surface when the capture ran into `max_total_bytes`.
+    pub fn limit_warning(
+        &self,
+        settings: &SnapshotCaptureSettings,
+    ) -> Option<SnapshotLimitWarning> {
+        let max_total_bytes = settings.max_total_bytes?;
+        if self.skipped_over_total.is_empty() {
+            return None;
+        }
+        Some(SnapshotLimitWarning {
+            max_total_bytes,
+            skipped_count: self.skipped_over_total.len() as u32,
+            skipped_paths: self
+                .skipped_over_total
+                .iter()
+                .take(MAX_REPORTED_PATHS)
+                .cloned()
+                .collect(),
+        })
+    }
+}
+
+/// A snapshot that reached the workspace's total size limit and left
+/// changed files out.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotLimitWarning {
+    pub max_total_bytes: u64,
+    pub skipped_count: u32,
+    /// The first skipped files, at most [`MAX_REPORTED_PATHS`].
+    pub skipped_paths: Vec<String>,
+}
+
+/// Tracks the capture budget and the files left out of a snapshot.
+pub(crate) struct CaptureFilter {
+    ignored: Option<PathspecFilter>,
+    max_file_bytes: Option<u64>,
+    max_total_bytes: Option<u64>,
+    total_bytes: u64,
+    pub(crate) report: SnapshotCaptureReport,
+}
+
+impl CaptureFilter {
+    pub(crate) fn new(settings: &SnapshotCaptureSettings) -> Result<Self, ReviewDiffError> {
+        Ok(Self {
+            ignored: PathspecFilter::new(&settings.ignore_globs)?,
+            max_file_bytes: settings.max_file_bytes,
+            max_total_bytes: settings.max_total_bytes,
+            total_bytes: 0,
+            report: SnapshotCaptureReport::default(),
+        })
+    }
+
+    pub(crate) fn is_ignored(&self, path: &str) -> bool {
+        self.ignored
+            .as_ref()
+            .is_some_and(|filter| filter.matches(path))
+    }
+
+    /// Whether a file of `size` bytes fits the limits; it counts against the
+    /// total when it does.
+    pub(crate) fn admit(&mut self, path: &str, size: u64) -> bool {
+        if self.max_file_bytes.is_some_and(|limit| size > limit) {
+            self.report.skipped_large_files.push(path.to_string());
+            return false;
+        }
+        if self
+            .max_total_bytes
+            .is_some_and(|limit| self.total_bytes + size > limit)
+        {
+            self.report.skipped_over_total.push(path.to_string());
+            return false;
+        }
+        self.total_bytes += size;
+        true
+    }
+}
+
+/// Snapshot the repository containing `cwd` as a commit on top of `HEAD`
+/// that no ref points at, leaving out what `settings` excludes. Used instead
+/// of a plain ghost commit when the workspace has capture settings.
+pub fn capture_git_snapshot(
+    cwd: &Path,
+    settings: &SnapshotCaptureSettings,
+) -> Result<(String, SnapshotCaptureReport), ReviewDiffError> {
+    let repo = open_repository(cwd)?;
+    let Some(workdir) = repo.workdir().map(Path::to_path_buf) else {
+        return Err(git2::Error::from_str("cannot snapshot a bare repository").into());
+    };
+    let mut filter = CaptureFilter::new(settings)?;
+
+    let head = repo.head().ok().and_then(|head| head.peel_to_commit().ok());
+    let mut index = Index::new()?;
+    if let Some(head) = &head {
+        index.read_tree(&head.tree()?)?;
+    }
+
+    let mut status_options = StatusOptions::new();
+    status_options
+        .include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .include_ignored(false)
+        .exclude_submodules(true);
+    let statuses = repo.statuses(Some(&mut status_options))?;
+    let mut paths: Vec<String> = statuses
+        .iter()
+        .filter_map(|entry| entry.path().map(str::to_string))
+        .collect();
+    paths.sort();
+    paths.dedup();
+
+    for path in paths {
+        if filter.is_ignored(&path) {
+            filter.report.skipped_ignored.push(path);
+            continue;
+        }
+        let full_path = workdir.join(&path);
+        let metadata = match std::fs::symlink_metadata(&full_path) {
+            Ok(metadata) => metadata,
+            Err(err) if err.kind() == ErrorKind::NotFound => {
+                index.remove_path(Path::new(&path))?;
+                continue;
+            }
+            Err(err) => return Err(err.into()),
+        };
+        if metadata.is_dir() || !filter.admit(&path, metadata.len()) {
+            continue;
+        }
+
+        let (oid, mode) = if metadata.file_type().is_symlink() {
+            let target = std::fs::read_link(&full_path)?;
+            let target = target.to_string_lossy();
+            (repo.blob(target.as_bytes())?, 0o120000)
+        } else {
+            let mode = if is_executable(&metadata) {
+                0o100755
+            } else {
+                0o100644
+            };
+            (repo.blob_path(&full_path)?, mode)
+        };
+        index.add(&index_entry(&path, oid, mode, metadata.len()))?;
+    }
+
+    let tree = repo.find_tree(index.write_tree_to(&repo)?)?;
+    let signature = snapshot_signature(&repo)?;
+    let parents: Vec<_> = head.iter().collect();
+    let oid = repo.commit(
+        None,
+        &signature,
+        &signature,
+        "Pasture review snapshot\n",
+        &tree,
+        &parents,
+    )?;
+    Ok((oid.to_string(), filter.report))
+}
+
+fn index_entry(path: &str, id: Oid, mode: u32, size: u64) -> IndexEntry {
+    IndexEntry {
+        ctime: IndexTime::new(0, 0),
+        mtime: IndexTime::new(0, 0),
+        dev: 0,
+        ino: 0,
+        mode,
+        uid: 0,
+        gid: 0,
+        file_size: size as u32,
+        id,
+        flags: 0,
+        flags_extended: 0,
+        path: path.as_bytes().to_vec(),
+    }
+}
+
+fn snapshot_signature(repo: &Repository) -> Result<Signature<'static>, git2::Error> {
+    repo.signature()
+        .or_else(|_| Signature::now("Pasture", "pasture@localhost"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn git_capture_applies_ignore_globs_and_size_limits() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let write = |path: &str, content: &str| {
+            let path = dir.path().join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        };
+        write(".gitignore", "target/\n");
+        write("src/lib.rs", "pub fn one() {}\n");
+        write("target/debug/out", "build output\n");
+        write("dist/bundle.js", "minified\n");
+        write("data/large.bin", &"x".repeat(64));
+        write("notes/a.txt", "aaaa\n");
+        write("notes/b.txt", "bbbb\n");
+
+        let settings = SnapshotCaptureSettings {
+            ignore_globs: vec!["dist".to_string()],
+            max_file_bytes: Some(32),
+            max_total_bytes: Some(20),
+        };
+        let (commit, report) = capture_git_snapshot(dir.path(), &settings).unwrap();
+        let tree = repo
+            .find_commit(Oid::from_str(&commit).unwrap())
+            .unwrap()
+            .tree()
+            .unwrap();
+        let captured = |path: &str| tree.get_path(Path::new(path)).is_ok();
+
+        assert!(captured(".gitignore"));
+        assert!(captured("notes/b.txt"));
+        assert!(!captured("src/lib.rs"));
+        assert!(!captured("target/debug/out"));
+        assert!(!captured("dist/bundle.js"));
+        assert!(!captured("data/large.bin"));
+        assert_eq!(report.skipped_large_files, vec!["data/large.bin"]);
+        assert_eq!(report.skipped_over_total, vec!["src/lib.rs"]);
+        assert_eq!(
+            report.skipped_paths(),
+            vec!["data/large.bin", "dist/bundle.js", "src/lib.rs"]
+        );
+        let warning = report.limit_warning(&settings).unwrap();
+        assert_eq!(warning.skipped_count, 1);
+        assert!(repo.head().is_err());
+    }
+}
@@ -0,0 +1,1739 @@
+use std::path::Path;
+use std::path::PathBuf;
+
+use git2::Delta;
+use git2::DiffFindOptions;
+use git2::DiffFormat;
+use git2::DiffOptions;
+use git2::Oid;
+use git2::Repository;
+use git2::Tree;
+use globset::Glob;
+use globset::GlobSet;
+use globset::GlobSetBuilder;
+use serde::Deserialize;
+use serde::Serialize;
+use ts_rs::TS;
+
+use crate::review_file_snapshots::FileSnapshotStore;
+use crate::review_intraline::highlight_hunk;
+
+const DEV_NULL: &str = "/dev/null";
+
+/// Failures raised while diffing snapshot commits in-process.
+#[derive(Debug, thiserror::Error)]
+pub enum ReviewDiffError {
+    #[error("no git repository found at {path}: {source}")]
+    OpenRepository {
+        path: PathBuf,
+        #[source]
+        source: git2::Error,
+    },
+    #[error("snapshot commit {commit} is not available: {source}")]
+    MissingCommit {
+        commit: String,
+        #[source]
+        source: git2::Error,
+    },
+    #[error("file snapshot {snapshot} is not available: {source}")]
+    MissingSnapshot {
+        snapshot: String,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("unknown git revision {revision}: {source}")]
+    UnknownRevision {
+        revision: String,
+        #[source]
+        source: git2::Error,
+    },
+    #[error("git revision {0} cannot be compared with a workspace outside git")]
+    RevisionWithoutRepository(String),
+    #[error("invalid pathspec {pathspec}: {source}")]
+    InvalidPathspec {
+        pathspec: String,
+        #[source]
+        source: globset::Error,
+    },
+    #[error(transparent)]
+    Git(#[from] git2::Error),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+/// Where the snapshots of a range are stored.
+#[derive(Debug, Clone)]
+pub enum SnapshotSource {
+    /// Ghost commits in the repository containing this directory.
+    Git(PathBuf),
+    /// Manifests in the file snapshot store, for workspaces outside git.
+    Files(FileSnapshotStore),
+}
+
+/// How a file changed between the two sides of a diff.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, TS)]
+#[serde(rename_all = "kebab-case")]
+pub enum DiffChangeKind {
+    Added,
+    Deleted,
+    Modified,
+    Renamed,
+    Copied,
+    ModeChanged,
+}
+
+/// Classification of a single line inside a hunk.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, TS)]
+#[serde(rename_all = "kebab-case")]
+pub enum DiffLineKind {
+    Context,
+    Addition,
+    Removal,
+    /// Markers such as `\ No newline at end of file`.
+    Metadata,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffLine {
+    pub kind: DiffLineKind,
+    pub text: String,
+    pub old_number: Option<u32>,
+    pub new_number: Option<u32>,
+    /// Changed parts of a removed or added line that pairs with a line on
+    /// the other side; empty when the whole line should be highlighted.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub spans: Vec<DiffSpan>,
+}
+
+/// A changed range within a line, in UTF-16 code units so it indexes
+/// JavaScript strings directly.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffSpan {
+    pub start: u32,
+    pub end: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffHunk {
+    pub header: String,
+    pub old_start: u32,
+    pub old_lines: u32,
+    pub new_start: u32,
+    pub new_lines: u32,
+    pub lines: Vec<DiffLine>,
+}
+
+/// One file section of a unified diff.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffFile {
+    pub old_path: Option<String>,
+    pub new_path: Option<String>,
+    pub display_path: String,
+    pub change_kind: DiffChangeKind,
+    pub old_mode: Option<String>,
+    pub new_mode: Option<String>,
+    /// Similarity percentage reported for renames and copies.
+    pub similarity: Option<u32>,
+    pub binary: bool,
+    pub insertions: u32,
+    pub deletions: u32,
+    /// Hunks were omitted because the patch exceeds the size caps.
+    pub collapsed: bool,
+    pub hunks: Vec<DiffHunk>,
+}
+
+/// Parse the output of `git diff` into per-file sections.
+pub fn parse_unified_diff(input: &str) -> Vec<DiffFile> {
+    let mut parser = DiffParser::default();
+    for line in input.lines() {
+        parser.push_line(line);
+    }
+    parser.finish()
+}
+
+#[derive(Default)]
+struct FileBuilder {
+    old_path: Option<String>,
+    new_path: Option<String>,
+    old_mode: Option<String>,
+    new_mode: Option<String>,
+    similarity: Option<u32>,
+    is_new: bool,
+    is_deleted: bool,
+    is_rename: bool,
+    is_copy: bool,
+    binary: bool,
+    insertions: u32,
+    deletions: u32,
+    hunks: Vec<DiffHunk>,
+}
+
+impl FileBuilder {
+    fn build(mut self) -> DiffFile {
+        self.hunks.iter_mut().for_each(highlight_hunk);
+
+        let change_kind = if self.is_new {
+            DiffChangeKind::Added
+        } else if self.is_deleted {
+            DiffChangeKind::Deleted
+        } else if self.is_rename {
+            DiffChangeKind::Renamed
+        } else if self.is_copy {
+            DiffChangeKind::Copied
+        } else if self.old_mode.is_some()
+            && self.old_mode != self.new_mode
+            && self.hunks.is_empty()
+            && !self.binary
+        {
+            DiffChangeKind::ModeChanged
+        } else {
+            DiffChangeKind::Modified
+        };
+
+        let display_path = self
+            .new_path
+            .clone()
+            .or_else(|| self.old_path.clone())
+            .unwrap_or_else(|| "Unknown path".to_string());
+
+        DiffFile {
+            old_path: self.old_path,
+            new_path: self.new_path,
+            display_path,
+            change_kind,
+            old_mode: self.old_mode,
+            new_mode: self.new_mode,
+            similarity: self.similarity,
+            binary: self.binary,
+            insertions: self.insertions,
+            deletions: self.deletions,
+            collapsed: false,
+            hunks: self.hunks,
+        }
+    }
+}
+
+#[derive(Default)]
+struct DiffParser {
+    files: Vec<DiffFile>,
+    current: Option<FileBuilder>,
+    old_remaining: u32,
+    new_remaining: u32,
+    old_line: u32,
+    new_line: u32,
+}
+
+impl DiffParser {
+    fn push_line(&mut self, line: &str) {
+        if self.in_hunk_body() && self.push_hunk_line(line) {
+            return;
+        }
+
+        if let Some(rest) = line.strip_prefix("diff --git ") {
+            self.flush();
+            let (old_path, new_path) = parse_git_header_paths(rest);
+            self.current = Some(FileBuilder {
+                old_path,
+                new_path,
+                ..Default::default()
+            });
+            return;
+        }
+
+        if let Some(rest) = line.strip_prefix("@@") {
+            self.start_hunk(line, rest);
+            return;
+        }
+
+        // Trailing `\ No newline at end of file` arrives after the hunk counters
+        // are exhausted, so it is handled outside `push_hunk_line`.
+        if line.starts_with('\\') {
+            self.push_metadata(line);
+            return;
+        }
+
+        let file = self.current.get_or_insert_with(FileBuilder::default);
+
+        if let Some(rest) = line.strip_prefix("--- ") {
+            file.old_path = parse_marker_path(rest);
+        } else if let Some(rest) = line.strip_prefix("+++ ") {
+            file.new_path = parse_marker_path(rest);
+        } else if let Some(mode) = line.strip_prefix("old mode ") {
+            file.old_mode = Some(mode.trim().to_string());
+        } else if let Some(mode) = line.strip_prefix("new mode ") {
+            file.new_mode = Some(mode.trim().to_string());
+        } else if let Some(mode) = line.strip_prefix("new file mode ") {
+            file.is_new = true;
+            file.old_path = None;
+            file.new_mode = Some(mode.trim().to_string());
+        } else if let Some(mode) = line.strip_prefix("deleted file mode ") {
+            file.is_deleted = true;
+            file.new_path = None;
+            file.old_mode = Some(mode.trim().to_string());
+        } else if let Some(value) = line.strip_prefix("similarity index ") {
+            file.similarity = value.trim().trim_end_matches('%').parse().ok();
+        } else if let Some(path) = line.strip_prefix("rename from ") {
+            file.is_rename = true;
+            file.old_path = Some(unquote_path(path));
+        } else if let Some(path) = line.strip_prefix("rename to ") {
+            file.is_rename = true;
+            file.new_path = Some(unquote_path(path));
+        } else if let Some(path) = line.strip_prefix("copy from ") {
+            file.is_copy = true;
+            file.old_path = Some(unquote_path(path));
+        } else if let Some(path) = line.strip_prefix("copy to ") {
+            file.is_copy = true;
+            file.new_path = Some(unquote_path(path));
+        } else if line.starts_with("Binary files ") || line == "GIT binary patch" {
+            file.binary = true;
+        } else if let Some(rest) = line.strip_prefix("index ") {
+            // `index <old>..<new> <mode>` carries the mode when it is unchanged.
+            if let Some(mode) = rest.split_whitespace().nth(1) {
+                file.old_mode.get_or_insert_with(|| mode.to_string());
+                file.new_mode.get_or_insert_with(|| mode.to_string());
+            }
+        }
+    }
+
+    fn in_hunk_body(&self) -> bool {
+        self.old_remaining > 0 || self.new_remaining > 0
+    }
+
+    /// Returns `false` when the line does not belong to the hunk body, which
+    /// happens when a hunk header under-reports its length.
+    fn push_hunk_line(&mut self, line: &str) -> bool {
+        let (kind, text) = match line.as_bytes().first() {
+            Some(b'+') => (DiffLineKind::Addition, &line[1..]),
+            Some(b'-') => (DiffLineKind::Removal, &line[1..]),
+            Some(b' ') => (DiffLineKind::Context, &line[1..]),
+            // Some tools strip the leading space from blank context lines.
+            None => (DiffLineKind::Context, ""),
+            Some(b'\\') => {
+                self.push_metadata(line);
+                return true;
+            }
+            Some(_) => return false,
+        };
+
+        let (old_number, new_number) = match kind {
+            DiffLineKind::Addition => {
+                self.new_line += 1;
+                self.new_remaining = self.new_remaining.saturating_sub(1);
+                (None, Some(self.new_line))
+            }
+            DiffLineKind::Removal => {
+                self.old_line += 1;
+                self.old_remaining = self.old_remaining.saturating_sub(1);
+                (Some(self.old_line), None)
+            }
+            _ => {
+                self.old_line += 1;
+                self.new_line += 1;
+                self.old_remaining = self.old_remaining.saturating_sub(1);
+                self.new_remaining = self.new_remaining.saturating_sub(1);
+                (Some(self.old_line), Some(self.new_line))
+            }
+        };
+
+        let Some(file) = self.current.as_mut() else {
+            return false;
+        };
+        match kind {
+            DiffLineKind::Addition => file.insertions += 1,
+            DiffLineKind::Removal => file.deletions += 1,
+            _ => {}
+        }
+        if let Some(hunk) = file.hunks.last_mut() {
+            hunk.lines.push(DiffLine {
+                kind,
+                text: text.to_string(),
+                old_number,
+                new_number,
+                spans: Vec::new(),
+            });
+        }
+        true
+    }
+
+    fn start_hunk(&mut self, header: &str, rest: &str) {
+        let Some((old_start, old_lines, new_start, new_lines)) = parse_hunk_ranges(rest) else {
+            return;
+        };
+
+        let file = self.current.get_or_insert_with(FileBuilder::default);
+        file.hunks.push(DiffHunk {
+            header: header.to_string(),
+            old_start,
+            old_lines,
+            new_start,
+            new_lines,
+            lines: Vec::new(),
+        });
+
+        self.old_remaining = old_lines;
+        self.new_remaining = new_lines;
+        self.old_line = old_start.saturating_sub(1);
+        self.new_line = new_start.saturating_sub(1);
+        // Empty sides start at the line *before* the insertion point.
+        if old_lines == 0 {
+            self.old_line = old_start;
+        }
+        if new_lines == 0 {
+            self.new_line = new_start;
+        }
+    }
+
+    fn push_metadata(&mut self, line: &str) {
+        if let Some(hunk) = self.current.as_mut().and_then(|file| file.hunks.last_mut()) {
+            hunk.lines.push(DiffLine {
+                kind: DiffLineKind::Metadata,
+                text: line.to_string(),
+                old_number: None,
+                new_number: None,
+                spans: Vec::new(),
+            });
+        }
+    }
+
+    fn flush(&mut self) {
+        self.old_remaining = 0;
+        self.new_remaining = 0;
+        if let Some(file) = self.current.take() {
+            self.files.push(file.build());
+        }
+    }
+
+    fn finish(mut self) -> Vec<DiffFile> {
+        self.flush();
+        self.files
+    }
+}
+
+/// Parse `-a,b +c,d @@ section` into its four counters.
+fn parse_hunk_ranges(rest: &str) -> Option<(u32, u32, u32, u32)> {
+    let mut parts = rest.split_whitespace();
+    let old = parts.next()?.strip_prefix('-')?;
+    let new = parts.next()?.strip_prefix('+')?;
+    let (old_start, old_lines) = parse_range(old)?;
+    let (new_start, new_lines) = parse_range(new)?;
+    Some((old_start, old_lines, new_start, new_lines))
+}
+
+fn parse_range(range: &str) -> Option<(u32, u32)> {
+    match range.split_once(',') {
+        Some((start, len)) => Some((start.parse().ok()?, len.parse().ok()?)),
+        None => Some((range.parse().ok()?, 1)),
+    }
+}
+
+/// Paths from `--- a/path` / `+++ b/path` marker lines.
+fn parse_marker_path(raw: &str) -> Option<String> {
+    // Git appends a tab when the path contains spaces.
+    let raw = raw.trim_end_matches('\t');
+    if raw == DEV_NULL {
+        return None;
+    }
+    let path = unquote_path(raw);
+    Some(strip_side_prefix(&path).to_string())
+}
+
+/// Paths from the `diff --git a/old b/new` header. The marker lines and
+/// rename headers override these when present, but pure mode changes and
+/// binary files only carry the header.
+fn parse_git_header_paths(rest: &str) -> (Option<String>, Option<String>) {
+    if rest.starts_with('"')
+        && let Some((old, remainder)) = split_quoted(rest)
+    {
+        let new = unquote_path(remainder.trim_start());
+        return (
+            Some(strip_side_prefix(&old).to_string()),
+            Some(strip_side_prefix(&new).to_string()),
+        );
+    }
+
+    // Unquoted paths may contain spaces; when both sides name the same file
+    // the header splits exactly in the middle.
+    let midpoint = rest.len() / 2;
+    if rest.len() % 2 == 1 && rest.is_char_boundary(midpoint) {
+        let (old, new) = (&rest[..midpoint], &rest[midpoint + 1..]);
+        if strip_side_prefix(old) == strip_side_prefix(new) {
+            return (
+                Some(strip_side_prefix(old).to_string()),
+                Some(strip_side_prefix(new).to_string()),
+            );
+        }
+    }
+
+    match rest.split_once(" b/") {
+        Some((old, new)) => (
+            Some(strip_side_prefix(old).to_string()),
+            Some(new.to_string()),
+        ),
+        None => (None, None),
+    }
+}
+
+fn strip_side_prefix(path: &str) -> &str {
+    path.strip_prefix("a/")
+        .or_else(|| path.strip_prefix("b/"))
+        .unwrap_or(path)
+}
+
+/// Split a leading C-quoted string from the remainder of the line.
+fn split_quoted(input: &str) -> Option<(String, &str)> {
+    let bytes = input.as_bytes();
+    let mut index = 1;
+    while index < bytes.len() {
+        match bytes[index] {
+            b'\\' => index += 2,
+            b'"' => {
+                let quoted = &input[..=index];
+                return Some((unquote_path(quoted), &input[index + 1..]));
+            }
+            _ => index += 1,
+        }
+    }
+    None
+}
+
+/// Decode git's C-style quoting (`"dir/\303\251t\303\251.txt"`).
+fn unquote_path(raw: &str) -> String {
+    let Some(inner) = raw
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+    else {
+        return raw.to_string();
+    };
+
+    let mut bytes = Vec::with_capacity(inner.len());
+    let mut chars = inner.bytes().peekable();
+    while let Some(byte) = chars.next() {
+        if byte != b'\\' {
+            bytes.push(byte);
+            continue;
+        }
+        match chars.next() {
+            Some(b'n') => bytes.push(b'\n'),
+            Some(b't') => bytes.push(b'\t'),
+            Some(b'r') => bytes.push(b'\r'),
+            Some(b'a') => bytes.push(0x07),
+            Some(b'b') => bytes.push(0x08),
+            Some(b'f') => bytes.push(0x0c),
+            Some(b'v') => bytes.push(0x0b),
+            Some(digit @ b'0'..=b'7') => {
+                let mut value = u32::from(digit - b'0');
+                for _ in 0..2 {
+                    match chars.peek() {
+                        Some(next @ b'0'..=b'7') => {
+                            value = value * 8 + u32::from(next - b'0');
+                            chars.next();
+                        }
+                        _ => break,
+                    }
+                }
+                bytes.push(value as u8);
+            }
+            Some(other) => bytes.push(other),
+            None => bytes.push(b'\\'),
+        }
+    }
+
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// How whitespace differences are treated, mirroring the `git diff` flags.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, TS)]
+#[serde(rename_all = "kebab-case")]
+pub enum WhitespaceMode {
+    #[default]
+    Show,
+    /// `--ignore-space-at-eol`
+    IgnoreAtEol,
+    /// `--ignore-space-change`
+    IgnoreChange,
+    /// `--ignore-all-space`
+    IgnoreAll,
+}
+
+/// Options applied to every file of a review diff. Defaults match plain
+/// `git diff`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, TS)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ReviewDiffOptions {
+    pub whitespace: WhitespaceMode,
+    /// Pair deleted and added files into renames.
+    pub detect_renames: bool,
+    /// Report added files that were copied from a modified file. Implies
+    /// `detect_renames`, as in git.
+    pub detect_copies: bool,
+    /// Minimum similarity percentage for renames and copies.
+    pub similarity_threshold: u8,
+    /// Unchanged lines shown around each change.
+    pub context_lines: u32,
+    /// Only include files matching one of these. Plain entries match a file
+    /// or everything below a directory; entries containing `*`, `?` or `[`
+    /// are globs over the whole path.
+    pub pathspecs: Vec<String>,
+}
+
+impl Default for ReviewDiffOptions {
+    fn default() -> Self {
+        Self {
+            whitespace: WhitespaceMode::Show,
+            detect_renames: true,
+            detect_copies: false,
+            similarity_threshold: 50,
+            context_lines: 3,
+            pathspecs: Vec::new(),
+        }
+    }
+}
+
+impl ReviewDiffOptions {
+    pub(crate) fn similarity_threshold(&self) -> u8 {
+        self.similarity_threshold.min(100)
+    }
+
+    fn git_diff_options(&self) -> DiffOptions {
+        let mut options = DiffOptions::new();
+        options.context_lines(self.context_lines);
+        match self.whitespace {
+            WhitespaceMode::Show => {}
+            WhitespaceMode::IgnoreAtEol => {
+                options.ignore_whitespace_eol(true);
+            }
+            WhitespaceMode::IgnoreChange => {
+                options.ignore_whitespace_change(true);
+            }
+            WhitespaceMode::IgnoreAll => {
+                options.ignore_whitespace(true);
+            }
+        }
+        options
+    }
+
+    /// `None` when neither renames nor copies are wanted; libgit2 would fall
+    /// back to the repository's `diff.renames` setting for empty options.
+    fn git_find_options(&self) -> Option<DiffFindOptions> {
+        if !self.detect_renames && !self.detect_copies {
+            return None;
+        }
+        let threshold = u16::from(self.similarity_threshold());
+        let mut options = DiffFindOptions::new();
+        options
+            .renames(self.detect_renames)
+            .copies(self.detect_copies)
+            .rename_threshold(threshold)
+            .copy_threshold(threshold)
+            .ignore_whitespace(self.whitespace == WhitespaceMode::IgnoreAll);
+        Some(options)
+    }
+}
+
+/// Compiled [`ReviewDiffOptions::pathspecs`].
+pub(crate) struct PathspecFilter {
+    prefixes: Vec<String>,
+    globs: GlobSet,
+}
+
+impl PathspecFilter {
+    /// `None` when every path is included.
+    pub(crate) fn new(pathspecs: &[String]) -> Result<Option<Self>, ReviewDiffError> {
+        if pathspecs.is_empty() {
+            return Ok(None);
+        }
+
+        let mut prefixes = Vec::new();
+        let mut globs = GlobSetBuilder::new();
+        for pathspec in pathspecs {
+            let pathspec = pathspec.trim_start_matches("./");
+            if pathspec.contains(['*', '?', '[']) {
+                let glob =
+                    Glob::new(pathspec).map_err(|source| ReviewDiffError::InvalidPathspec {
+                        pathspec: pathspec.to_string(),
+                        source,
+                    })?;
+                globs.add(glob);
+            } else {
+                prefixes.push(pathspec.trim_end_matches('/').to_string());
+            }
+        }
+        let globs = globs
+            .build()
+            .map_err(|source| ReviewDiffError::InvalidPathspec {
+                pathspec: pathspecs.join(" "),
+                source,
+            })?;
+        Ok(Some(Self { prefixes, globs }))
+    }
+
+    pub(crate) fn matches(&self, path: &str) -> bool {
+        self.prefixes.iter().any(|prefix| {
+            prefix.is_empty()
+                || path == prefix
+                || path
+                    .strip_prefix(prefix.as_str())
+                    .is_some_and(|rest| rest.starts_with('/'))
+        }) || self.globs.is_match(path)
+    }
+}
+
+/// Text encodings recognised when reading snapshot files.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, TS)]
+#[serde(rename_all = "kebab-case")]
+pub enum FileEncoding {
+    Utf8,
+    /// UTF-8 with a byte order mark, which is stripped from the content.
+    Utf8Bom,
+    Utf16Le,
+    Utf16Be,
+    /// Fallback for text that is not valid UTF-8.
+    Latin1,
+}
+
+/// A file read from a snapshot for display.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotFile {
+    pub path: String,
+    /// Size in bytes as stored in the snapshot.
+    pub size: u64,
+    pub binary: bool,
+    /// Detected encoding; `None` for binary files.
+    pub encoding: Option<FileEncoding>,
+    /// Decoded text, left out for binary files and files over
+    /// [`MAX_SNAPSHOT_FILE_CONTENT_BYTES`].
+    pub content: Option<String>,
+    pub too_large: bool,
+}
+
+/// Largest file whose contents [`snapshot_file`] returns.
+pub const MAX_SNAPSHOT_FILE_CONTENT_BYTES: usize = 4 * 1024 * 1024;
+/// Bytes inspected when deciding whether a file is binary, as git does.
+pub(crate) const BINARY_SNIFF_BYTES: usize = 8000;
+
+/// Patch text for a single file larger than this is collapsed unless the
+/// caller explicitly asks to expand it.
+pub const MAX_FILE_PATCH_BYTES: usize = 256 * 1024;
+/// Hard ceiling for a single file, even when expansion is requested.
+pub const MAX_EXPANDED_FILE_PATCH_BYTES: usize = 8 * 1024 * 1024;
+/// Combined patch text returned for a whole range before further files are
+/// collapsed.
+pub const MAX_RANGE_PATCH_BYTES: usize = 2 * 1024 * 1024;
+
+/// Lightweight description of a changed file, returned before any hunks are
+/// fetched.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffFileSummary {
+    pub old_path: Option<String>,
+    pub new_path: Option<String>,
+    pub display_path: String,
+    pub change_kind: DiffChangeKind,
+    pub binary: bool,
+    pub insertions: u32,
+    pub deletions: u32,
+    /// The file's patch exceeds [`MAX_FILE_PATCH_BYTES`].
+    pub collapsed: bool,
+}
+
+/// Diff between two snapshots, with oversized files collapsed.
+#[derive(Debug, Clone)]
+pub struct SnapshotDiff {
+    pub unified_diff: String,
+    pub files: Vec<DiffFile>,
+}
+
+/// Patch output for one file, split into its header and hunk body so large
+/// bodies can be dropped without losing the file metadata.
+#[derive(Default)]
+pub(crate) struct FilePatch {
+    pub(crate) old_path: Option<String>,
+    pub(crate) new_path: Option<String>,
+    pub(crate) header: Vec<u8>,
+    pub(crate) body: Vec<u8>,
+    pub(crate) body_bytes: usize,
+    pub(crate) insertions: u32,
+    pub(crate) deletions: u32,
+    pub(crate) binary: bool,
+}
+
+impl FilePatch {
+    fn matches(&self, paths: &[String]) -> bool {
+        paths.iter().any(|path| {
+            self.old_path.as_deref() == Some(path.as_str())
+                || self.new_path.as_deref() == Some(path.as_str())
+        })
+    }
+
+    /// A modification whose hunks all disappeared because whitespace is
+    /// ignored; `git diff -w` leaves such files out.
+    fn is_empty_modification(&self) -> bool {
+        self.body_bytes == 0
+            && !self.binary
+            && self.old_path.is_some()
+            && self.old_path == self.new_path
+            && !String::from_utf8_lossy(&self.header).contains("\nold mode ")
+    }
+
+    fn text(&self, include_body: bool) -> String {
+        let mut bytes = self.header.clone();
+        if include_body {
+            bytes.extend_from_slice(&self.body);
+        }
+        String::from_utf8_lossy(&bytes).into_owned()
+    }
+
+    fn into_diff_file(self, collapsed: bool) -> Option<DiffFile> {
+        let mut file = parse_unified_diff(&self.text(!collapsed)).pop()?;
+        file.binary |= self.binary;
+        file.insertions = self.insertions;
+        file.deletions = self.deletions;
+        file.collapsed = collapsed;
+        Some(file)
+    }
+
+    fn summary(&self) -> Option<DiffFileSummary> {
+        let file = parse_unified_diff(&self.text(false)).pop()?;
+        Some(DiffFileSummary {
+            old_path: file.old_path,
+            new_path: file.new_path,
+            display_path: file.display_path,
+            change_kind: file.change_kind,
+            binary: file.binary || self.binary,
+            insertions: self.insertions,
+            deletions: self.deletions,
+            collapsed: self.body_bytes > MAX_FILE_PATCH_BYTES,
+        })
+    }
+}
+
+/// Diff two snapshot commits, matching `git diff --no-color <base> <target>`
+/// for every file that fits within the size caps.
+pub fn diff_range(
+    source: &SnapshotSource,
+    base: &str,
+    target: &str,
+    options: &ReviewDiffOptions,
+) -> Result<SnapshotDiff, ReviewDiffError> {
+    let patches = collect_patches(source, base, target, None, MAX_FILE_PATCH_BYTES, options)?;
+
+    let mut unified_diff = String::new();
+    let mut files = Vec::with_capacity(patches.len());
+    let mut total_bytes = 0;
+    for patch in patches {
+        let collapsed = patch.body_bytes > MAX_FILE_PATCH_BYTES
+            || total_bytes + patch.body_bytes > MAX_RANGE_PATCH_BYTES;
+        if !collapsed {
+            total_bytes += patch.body_bytes;
+        }
+        unified_diff.push_str(&patch.text(!collapsed));
+        files.extend(patch.into_diff_file(collapsed));
+    }
+
+    Ok(SnapshotDiff {
+        unified_diff,
+        files,
+    })
+}
+
+/// Changed files with line stats, without hunk contents.
+pub fn list_changed_files(
+    source: &SnapshotSource,
+    base: &str,
+    target: &str,
+    options: &ReviewDiffOptions,
+) -> Result<Vec<DiffFileSummary>, ReviewDiffError> {
+    // Bodies are only measured, so keep none of them in memory.
+    let patches = collect_patches(source, base, target, None, 0, options)?;
+    Ok(patches.iter().filter_map(FilePatch::summary).collect())
+}
+
+/// Full hunks for the requested files, matched against either side of a
+/// rename. Files above [`MAX_FILE_PATCH_BYTES`] stay collapsed unless
+/// `expand_collapsed` is set.
+pub fn diff_selected_files(
+    source: &SnapshotSource,
+    base: &str,
+    target: &str,
+    paths: &[String],
+    expand_collapsed: bool,
+    options: &ReviewDiffOptions,
+) -> Result<Vec<DiffFile>, ReviewDiffError> {
+    let limit = if expand_collapsed {
+        MAX_EXPANDED_FILE_PATCH_BYTES
+    } else {
+        MAX_FILE_PATCH_BYTES
+    };
+
+    let patches = collect_patches(source, base, target, Some(paths), limit, options)?;
+    Ok(patches
+        .into_iter()
+        .filter(|patch| patch.matches(paths))
+        .filter_map(|patch| {
+            let collapsed = patch.body_bytes > limit;
+            patch.into_diff_file(collapsed)
+        })
+        .collect())
+}
+
+/// Per-file patches for a range. Bodies larger than `body_limit` are measured
+/// but not retained. Pathspecs are applied after rename detection so a file
+/// moved into a matching directory still shows as a rename.
+fn collect_patches(
+    source: &SnapshotSource,
+    base: &str,
+    target: &str,
+    paths: Option<&[String]>,
+    body_limit: usize,
+    options: &ReviewDiffOptions,
+) -> Result<Vec<FilePatch>, ReviewDiffError> {
+    let filter = PathspecFilter::new(&options.pathspecs)?;
+    let mut patches = match source {
+        SnapshotSource::Git(repo_path) => {
+            let repo = open_repository(repo_path)?;
+            collect_git_patches(&repo, base, target, paths, body_limit, options)?
+        }
+        SnapshotSource::Files(store) => {
+            store.collect_patches(base, target, paths, body_limit, options)?
+        }
+    };
+
+    if let Some(filter) = filter {
+        patches.retain(|patch| {
+            [&patch.old_path, &patch.new_path]
+                .into_iter()
+                .flatten()
+                .any(|path| filter.matches(path))
+        });
+    }
+    if options.whitespace != WhitespaceMode::Show {
+        patches.retain(|patch| !patch.is_empty_modification());
+    }
+    Ok(patches)
+}
+
+/// Run the tree diff and split the printed patch per file.
+fn collect_git_patches(
+    repo: &Repository,
+    base: &str,
+    target: &str,
+    paths: Option<&[String]>,
+    body_limit: usize,
+    diff_options: &ReviewDiffOptions,
+) -> Result<Vec<FilePatch>, ReviewDiffError> {
+    let base_tree = commit_tree(repo, base)?;
+    let target_tree = commit_tree(repo, target)?;
+
+    let mut options = diff_options.git_diff_options();
+    if let Some(paths) = paths {
+        // Renames must be detected against the full tree first, so both
+        // sides of a renamed file end up in the pathspec.
+        let mut full = repo.diff_tree_to_tree(
+            Some(&base_tree),
+            Some(&target_tree),
+            Some(&mut diff_options.git_diff_options()),
+        )?;
+        if let Some(mut find_options) = diff_options.git_find_options() {
+            full.find_similar(Some(&mut find_options))?;
+        }
+        options.disable_pathspec_match(true);
+        let mut matched = false;
+        for delta in full.deltas() {
+            let sides: Vec<String> = [delta.old_file().path(), delta.new_file().path()]
+                .into_iter()
+                .flatten()
+                .map(|path| path.to_string_lossy().into_owned())
+                .collect();
+            if sides.iter().any(|side| paths.contains(side)) {
+                matched = true;
+                for side in sides {
+                    options.pathspec(side);
+                }
+            }
+        }
+        if !matched {
+            return Ok(Vec::new());
+        }
+    }
+
+    let mut diff =
+        repo.diff_tree_to_tree(Some(&base_tree), Some(&target_tree), Some(&mut options))?;
+    if let Some(mut find_options) = diff_options.git_find_options() {
+        diff.find_similar(Some(&mut find_options))?;
+    }
+
+    let mut patches: Vec<FilePatch> = Vec::new();
+    diff.print(DiffFormat::Patch, |delta, _hunk, line| {
+        if line.origin() == 'F' {
+            patches.push(FilePatch {
+                old_path: delta
+                    .old_file()
+                    .path()
+                    .map(|path| path.to_string_lossy().into_owned()),
+                new_path: delta
+                    .new_file()
+                    .path()
+                    .map(|path| path.to_string_lossy().into_owned()),
+                header: line.content().to_vec(),
+                ..Default::default()
+            });
+            return true;
+        }
+
+        let Some(patch) = patches.last_mut() else {
+            return true;
+        };
+        match line.origin() {
+            '+' => patch.insertions += 1,
+            '-' => patch.deletions += 1,
+            'B' => patch.binary = true,
+            _ => {}
+        }
+
+        let prefix = usize::from(matches!(line.origin(), '+' | '-' | ' '));
+        patch.body_bytes += prefix + line.content().len();
+        if patch.body_bytes <= body_limit {
+            if prefix == 1 {
+                patch.body.push(line.origin() as u8);
+            }
+            patch.body.extend_from_slice(line.content());
+        }
+        true
+    })?;
+
+    Ok(patches)
+}
+
+/// Files present in `target` but not in `base`, resolved against the
+/// repository's working directory.
+pub fn added_paths(
+    repo_path: &Path,
+    base: &str,
+    target: &str,
+) -> Result<Vec<PathBuf>, ReviewDiffError> {
+    let repo = open_repository(repo_path)?;
+    let base_tree = commit_tree(&repo, base)?;
+    let target_tree = commit_tree(&repo, target)?;
+    let diff = repo.diff_tree_to_tree(Some(&base_tree), Some(&target_tree), None)?;
+
+    let Some(workdir) = repo.workdir() else {
+        return Ok(Vec::new());
+    };
+
+    Ok(diff
+        .deltas()
+        .filter(|delta| delta.status() == Delta::Added)
+        .filter_map(|delta| delta.new_file().path().map(|path| workdir.join(path)))
+        .collect())
+}
+
+/// Contents of `path` in a snapshot, or `None` when the snapshot has no
+/// such file.
+pub fn read_snapshot_file(
+    source: &SnapshotSource,
+    snapshot: &str,
+    path: &str,
+) -> Result<Option<Vec<u8>>, ReviewDiffError> {
+    match source {
+        SnapshotSource::Git(repo_path) => {
+            let repo = open_repository(repo_path)?;
+            let tree = commit_tree(&repo, snapshot)?;
+            let entry = match tree.get_path(Path::new(path)) {
+                Ok(entry) => entry,
+                Err(err) if err.code() == git2::ErrorCode::NotFound => return Ok(None),
+                Err(err) => return Err(err.into()),
+            };
+            match entry.to_object(&repo)?.into_blob() {
+                Ok(blob) => Ok(Some(blob.content().to_vec())),
+                // Directories and submodules have no file contents.
+                Err(_) => Ok(None),
+            }
+        }
+        SnapshotSource::Files(store) => store.read_file(snapshot, path),
+    }
+}
+
+/// Read a file from a snapshot for display, decoding text and leaving out
+/// binary or oversized content. `None` when the snapshot has no such file.
+pub fn snapshot_file(
+    source: &SnapshotSource,
+    snapshot: &str,
+    path: &str,
+) -> Result<Option<SnapshotFile>, ReviewDiffError> {
+    let Some(bytes) = read_snapshot_file(source, snapshot, path)? else {
+        return Ok(None);
+    };
+
+    let too_large = bytes.len() > MAX_SNAPSHOT_FILE_CONTENT_BYTES;
+    let (encoding, content) = if too_large {
+        (sniff_encoding(&bytes), None)
+    } else {
+        match decode_text(&bytes) {
+            Some((encoding, content)) => (Some(encoding), Some(content)),
+            None => (None, None),
+        }
+    };
+    Ok(Some(SnapshotFile {
+        path: path.to_string(),
+        size: bytes.len() as u64,
+        binary: encoding.is_none(),
+        encoding,
+        content,
+        too_large,
+    }))
+}
+
+/// Guess the encoding of a file from its first [`BINARY_SNIFF_BYTES`] without
+/// decoding the rest, the way [`decode_text`] would. `None` for binary files.
+fn sniff_encoding(bytes: &[u8]) -> Option<FileEncoding> {
+    let head = &bytes[..bytes.len().min(BINARY_SNIFF_BYTES)];
+    // A character cut off at the end of the sample does not make it invalid.
+    let truncated = head.len() < bytes.len();
+    let utf8 = |text: &[u8]| match std::str::from_utf8(text) {
+        Ok(_) => true,
+        Err(err) => truncated && err.error_len().is_none(),
+    };
+
+    if let Some(rest) = head.strip_prefix(b"\xEF\xBB\xBF") {
+        return utf8(rest).then_some(FileEncoding::Utf8Bom);
+    }
+    if head.starts_with(b"\xFF\xFE") {
+        return Some(FileEncoding::Utf16Le);
+    }
+    if head.starts_with(b"\xFE\xFF") {
+        return Some(FileEncoding::Utf16Be);
+    }
+
+    if head.contains(&0) {
+        return None;
+    }
+    Some(if utf8(head) {
+        FileEncoding::Utf8
+    } else {
+        FileEncoding::Latin1
+    })
+}
+
+/// Decode file contents, recognising byte order marks. Files with NUL bytes
+/// near the start, or malformed UTF-16, are treated as binary; other bytes
+/// that are not UTF-8 are read as Latin-1.
+pub(crate) fn decode_text(bytes: &[u8]) -> Option<(FileEncoding, String)> {
+    let utf16 = |rest: &[u8], from_bytes: fn([u8; 2]) -> u16| {
+        if !rest.len().is_multiple_of(2) {
+            return None;
+        }
+        let units: Vec<u16> = rest
+            .chunks_exact(2)
+            .map(|pair| from_bytes([pair[0], pair[1]]))
+            .collect();
+        String::from_utf16(&units).ok()
+    };
+
+    if let Some(rest) = bytes.strip_prefix(b"\xEF\xBB\xBF") {
+        return std::str::from_utf8(rest)
+            .ok()
+            .map(|text| (FileEncoding::Utf8Bom, text.to_string()));
+    }
+    if let Some(rest) = bytes.strip_prefix(b"\xFF\xFE") {
+        return utf16(rest, u16::from_le_bytes).map(|text| (FileEncoding::Utf16Le, text));
+    }
+    if let Some(rest) = bytes.strip_prefix(b"\xFE\xFF") {
+        return utf16(rest, u16::from_be_bytes).map(|text| (FileEncoding::Utf16Be, text));
+    }
+
+    if bytes[..bytes.len().min(BINARY_SNIFF_BYTES)].contains(&0) {
+        return None;
+    }
+    match std::str::from_utf8(bytes) {
+        Ok(text) => Some((FileEncoding::Utf8, text.to_string())),
+        Err(_) => Some((
+            FileEncoding::Latin1,
+            bytes.iter().map(|byte| char::from(*byte)).collect(),
+        )),
+    }
+}
+
+/// Directory that snapshot paths are relative to for a workspace at `cwd`.
+pub fn snapshot_root(source: &SnapshotSource, cwd: &Path) -> Result<PathBuf, ReviewDiffError> {
+    match source {
+        SnapshotSource::Git(repo_path) => {
+            let repo = open_repository(repo_path)?;
+            Ok(repo
+                .workdir()
+                .map(Path::to_path_buf)
+                .unwrap_or_else(|| cwd.to_path_buf()))
+        }
+        SnapshotSource::Files(_) => Ok(cwd.to_path_buf()),
+    }
+}
+
+/// Resolve a git revision (`HEAD`, a branch, a tag or a SHA) to the commit
+/// it points at so it can be diffed like a snapshot.
+pub fn resolve_revision(
+    source: &SnapshotSource,
+    revision: &str,
+) -> Result<String, ReviewDiffError> {
+    let SnapshotSource::Git(repo_path) = source else {
+        return Err(ReviewDiffError::RevisionWithoutRepository(
+            revision.to_string(),
+        ));
+    };
+
+    let repo = open_repository(repo_path)?;
+    repo.revparse_single(revision)
+        .and_then(|object| object.peel_to_commit())
+        .map(|commit| commit.id().to_string())
+        .map_err(|source| ReviewDiffError::UnknownRevision {
+            revision: revision.to_string(),
+            source,
+        })
+}
+
+pub(crate) fn open_repository(repo_path: &Path) -> Result<Repository, ReviewDiffError> {
+    Repository::discover(repo_path).map_err(|source| ReviewDiffError::OpenRepository {
+        path: repo_path.to_path_buf(),
+        source,
+    })
+}
+
+fn commit_tree<'repo>(
+    repo: &'repo Repository,
+    commit: &str,
+) -> Result<Tree<'repo>, ReviewDiffError> {
+    Oid::from_str(commit)
+        .and_then(|oid| repo.find_commit(oid))
+        .and_then(|commit| commit.tree())
+        .map_err(|source| ReviewDiffError::MissingCommit {
+            commit: commit.to_string(),
+            source,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git2::Signature;
+
+    fn commit_files(repo: &Repository, files: &[(&str, &str)]) -> String {
+        let mut index = git2::Index::new().unwrap();
+        for (path, content) in files {
+            let blob = repo.blob(content.as_bytes()).unwrap();
+            index
+                .add(&git2::IndexEntry {
+                    ctime: git2::IndexTime::new(0, 0),
+                    mtime: git2::IndexTime::new(0, 0),
+                    dev: 0,
+                    ino: 0,
+                    mode: 0o100644,
+                    uid: 0,
+                    gid: 0,
+                    file_size: content.len() as u32,
+                    id: blob,
+                    flags: 0,
+                    flags_extended: 0,
+                    path: path.as_bytes().to_vec(),
+                })
+                .unwrap();
+        }
+        let tree = repo.find_tree(index.write_tree_to(repo).unwrap()).unwrap();
+        let signature = Signature::now("Pasture", "pasture@example.com").unwrap();
+        repo.commit(None, &signature, &signature, "snapshot", &tree, &[])
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn diff_range_matches_git_patch_output() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let base = commit_files(&repo, &[("a.txt", "one\ntwo\n"), ("gone.txt", "bye\n")]);
+        let target = commit_files(&repo, &[("a.txt", "one\nthree\n"), ("new.txt", "hi")]);
+
+        let source = SnapshotSource::Git(dir.path().to_path_buf());
+        let options = ReviewDiffOptions::default();
+        let diff = diff_range(&source, &base, &target, &options).unwrap();
+        let expected = "\
+diff --git a/a.txt b/a.txt
+index 814f4a4..4c7442b 100644
+--- a/a.txt
++++ b/a.txt
+@@ -1,2 +1,2 @@
+ one
+-two
++three
+diff --git a/gone.txt b/gone.txt
+deleted file mode 100644
+index b023018..0000000
+--- a/gone.txt
++++ /dev/null
+@@ -1 +0,0 @@
+-bye
+diff --git a/new.txt b/new.txt
+new file mode 100644
+index 0000000..32f95c0
+--- /dev/null
++++ b/new.txt
+@@ -0,0 +1 @@
++hi
+\\ No newline at end of file
+";
+        assert_eq!(diff.unified_diff, expected);
+        assert_eq!(diff.files.len(), 3);
+
+        let added = added_paths(dir.path(), &base, &target).unwrap();
+        assert_eq!(added.len(), 1);
+        assert!(added[0].ends_with("new.txt"));
+    }
+
+    #[test]
+    fn large_files_are_collapsed_and_fetched_on_demand() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let large = "x\n".repeat(MAX_FILE_PATCH_BYTES);
+        let base = commit_files(&repo, &[("small.txt", "a\n"), ("old.lock", "")]);
+        let target = commit_files(&repo, &[("small.txt", "b\n"), ("new.lock", &large)]);
+
+        let source = SnapshotSource::Git(dir.path().to_path_buf());
+        let options = ReviewDiffOptions::default();
+        let diff = diff_range(&source, &base, &target, &options).unwrap();
+        let lock = diff
+            .files
+            .iter()
+            .find(|file| file.display_path == "new.lock")
+            .unwrap();
+        assert!(lock.collapsed);
+        assert!(lock.hunks.is_empty());
+        assert_eq!(lock.insertions as usize, MAX_FILE_PATCH_BYTES);
+        assert!(!diff.unified_diff.contains("+x\n"));
+
+        let summaries = list_changed_files(&source, &base, &target, &options).unwrap();
+        let collapsed: Vec<_> = summaries
+            .iter()
+            .map(|file| (file.display_path.as_str(), file.collapsed))
+            .collect();
+        assert!(collapsed.contains(&("new.lock", true)));
+        assert!(collapsed.contains(&("small.txt", false)));
+
+        let selected = diff_selected_files(
+            &source,
+            &base,
+            &target,
+            &["new.lock".to_string()],
+            true,
+            &options,
+        )
+        .unwrap();
+        assert_eq!(selected.len(), 1);
+        assert!(!selected[0].collapsed);
+        assert_eq!(selected[0].hunks[0].lines.len(), MAX_FILE_PATCH_BYTES);
+
+        let unknown = diff_selected_files(
+            &source,
+            &base,
+            &target,
+            &["missing".to_string()],
+            false,
+            &options,
+        )
+        .unwrap();
+        assert!(unknown.is_empty());
+    }
+
+    #[test]
+    fn selected_files_match_either_side_of_a_rename() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let content = "line one\nline two\nline three\nline four\n";
+        let base = commit_files(&repo, &[("before.txt", content)]);
+        let target = commit_files(&repo, &[("after.txt", content)]);
+
+        let selected = diff_selected_files(
+            &SnapshotSource::Git(dir.path().to_path_buf()),
+            &base,
+            &target,
+            &["after.txt".to_string()],
+            false,
+            &ReviewDiffOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(selected.len(), 1);
+        assert_eq!(selected[0].change_kind, DiffChangeKind::Renamed);
+        assert_eq!(selected[0].old_path.as_deref(), Some("before.txt"));
+    }
+
+    #[test]
+    fn reads_snapshot_files_with_encoding_detection() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let snapshot = commit_files(
+            &repo,
+            &[
+                ("plain.txt", "héllo\n"),
+                ("bom.txt", "\u{feff}hi"),
+                ("nul.bin", "a\0b"),
+            ],
+        );
+        let source = SnapshotSource::Git(dir.path().to_path_buf());
+
+        let plain = snapshot_file(&source, &snapshot, "plain.txt")
+            .unwrap()
+            .unwrap();
+        assert_eq!(plain.encoding, Some(FileEncoding::Utf8));
+        assert_eq!(plain.content.as_deref(), Some("héllo\n"));
+        let bom = snapshot_file(&source, &snapshot, "bom.txt")
+            .unwrap()
+            .unwrap();
+        assert_eq!(bom.encoding, Some(FileEncoding::Utf8Bom));
+        assert_eq!(bom.content.as_deref(), Some("hi"));
+        let binary = snapshot_file(&source, &snapshot, "nul.bin")
+            .unwrap()
+            .unwrap();
+        assert!(binary.binary);
+        assert_eq!(binary.content, None);
+        assert!(
+            snapshot_file(&source, &snapshot, "missing.txt")
+                .unwrap()
+                .is_none()
+        );
+
+        let utf16: Vec<u8> = [0xFF, 0xFE, b'o', 0, b'k', 0].to_vec();
+        assert_eq!(
+            decode_text(&utf16),
+            Some((FileEncoding::Utf16Le, "ok".to_string()))
+        );
+        assert_eq!(
+            decode_text(b"caf\xE9"),
+            Some((FileEncoding::Latin1, "café".to_string()))
+        );
+
+        // The sample ends halfway through an "é"; bytes past it are not read.
+        let mut large = format!("a{}", "é".repeat(BINARY_SNIFF_BYTES)).into_bytes();
+        large.push(0xE9);
+        assert_eq!(sniff_encoding(&large), Some(FileEncoding::Utf8));
+        assert_eq!(sniff_encoding(b"caf\xE9"), Some(FileEncoding::Latin1));
+        assert_eq!(
+            sniff_encoding(b"\xEF\xBB\xBFa\0b"),
+            Some(FileEncoding::Utf8Bom)
+        );
+        assert_eq!(sniff_encoding(b"a\0b"), None);
+    }
+
+    #[test]
+    fn diff_range_reports_missing_snapshots() {
+        let dir = tempfile::tempdir().unwrap();
+        Repository::init(dir.path()).unwrap();
+        let missing = "0123456789012345678901234567890123456789";
+        let source = SnapshotSource::Git(dir.path().to_path_buf());
+        let err = diff_range(&source, missing, missing, &ReviewDiffOptions::default()).unwrap_err();
+        assert!(matches!(err, ReviewDiffError::MissingCommit { .. }));
+    }
+
+    #[test]
+    fn parses_modified_file_with_line_numbers() {
+        let diff = "\
+diff --git a/src/lib.rs b/src/lib.rs
+index 1111111..2222222 100644
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1,3 +1,4 @@ fn main() {
+ keep
+-old
++new
++extra
+ tail
+";
+        let files = parse_unified_diff(diff);
+        assert_eq!(files.len(), 1);
+        let file = &files[0];
+        assert_eq!(file.change_kind, DiffChangeKind::Modified);
+        assert_eq!(file.display_path, "src/lib.rs");
+        assert_eq!(file.old_mode.as_deref(), Some("100644"));
+        assert_eq!((file.insertions, file.deletions), (2, 1));
+
+        let hunk = &file.hunks[0];
+        assert_eq!(
+            (
+                hunk.old_start,
+                hunk.old_lines,
+                hunk.new_start,
+                hunk.new_lines
+            ),
+            (1, 3, 1, 4)
+        );
+        let numbers: Vec<_> = hunk
+            .lines
+            .iter()
+            .map(|line| (line.kind, line.old_number, line.new_number))
+            .collect();
+        assert_eq!(
+            numbers,
+            vec![
+                (DiffLineKind::Context, Some(1), Some(1)),
+                (DiffLineKind::Removal, Some(2), None),
+                (DiffLineKind::Addition, None, Some(2)),
+                (DiffLineKind::Addition, None, Some(3)),
+                (DiffLineKind::Context, Some(3), Some(4)),
+            ]
+        );
+    }
+
+    #[test]
+    fn removed_lines_that_look_like_headers_stay_in_the_hunk() {
+        let diff = "\
+diff --git a/notes.md b/notes.md
+--- a/notes.md
++++ b/notes.md
+@@ -1,2 +1,2 @@
+---- divider
++++++ heading
+ body
+";
+        let files = parse_unified_diff(diff);
+        let hunk = &files[0].hunks[0];
+        assert_eq!(hunk.lines.len(), 3);
+        assert_eq!(hunk.lines[0].kind, DiffLineKind::Removal);
+        assert_eq!(hunk.lines[0].text, "--- divider");
+        assert_eq!(hunk.lines[1].kind, DiffLineKind::Addition);
+        assert_eq!(hunk.lines[1].text, "++++ heading");
+        assert_eq!(files[0].old_path.as_deref(), Some("notes.md"));
+    }
+
+    #[test]
+    fn classifies_added_deleted_and_binary_files() {
+        let diff = "\
+diff --git a/new.txt b/new.txt
+new file mode 100644
+index 0000000..3333333
+--- /dev/null
++++ b/new.txt
+@@ -0,0 +1 @@
++hello
+\\ No newline at end of file
+diff --git a/gone.txt b/gone.txt
+deleted file mode 100755
+index 4444444..0000000
+--- a/gone.txt
++++ /dev/null
+@@ -1 +0,0 @@
+-bye
+diff --git a/logo.png b/logo.png
+index 5555555..6666666 100644
+Binary files a/logo.png and b/logo.png differ
+";
+        let files = parse_unified_diff(diff);
+        assert_eq!(files.len(), 3);
+
+        assert_eq!(files[0].change_kind, DiffChangeKind::Added);
+        assert_eq!(files[0].old_path, None);
+        assert_eq!(files[0].new_path.as_deref(), Some("new.txt"));
+        assert_eq!(files[0].hunks[0].lines[0].new_number, Some(1));
+        assert_eq!(files[0].hunks[0].lines[1].kind, DiffLineKind::Metadata);
+
+        assert_eq!(files[1].change_kind, DiffChangeKind::Deleted);
+        assert_eq!(files[1].new_path, None);
+        assert_eq!(files[1].old_mode.as_deref(), Some("100755"));
+        assert_eq!(files[1].deletions, 1);
+
+        assert_eq!(files[2].change_kind, DiffChangeKind::Modified);
+        assert!(files[2].binary);
+        assert!(files[2].hunks.is_empty());
+    }
+
+    #[test]
+    fn classifies_renames_and_mode_changes() {
+        let diff = "\
+diff --git a/old name.txt b/new name.txt
+similarity index 90%
+rename from old name.txt
+rename to new name.txt
+index 7777777..8888888 100644
+--- a/old name.txt\t
++++ b/new name.txt\t
+@@ -1 +1 @@
+-a
++b
+diff --git a/run.sh b/run.sh
+old mode 100644
+new mode 100755
+";
+        let files = parse_unified_diff(diff);
+        assert_eq!(files.len(), 2);
+
+        assert_eq!(files[0].change_kind, DiffChangeKind::Renamed);
+        assert_eq!(files[0].similarity, Some(90));
+        assert_eq!(files[0].old_path.as_deref(), Some("old name.txt"));
+        assert_eq!(files[0].new_path.as_deref(), Some("new name.txt"));
+
+        assert_eq!(files[1].change_kind, DiffChangeKind::ModeChanged);
+        assert_eq!(files[1].old_path.as_deref(), Some("run.sh"));
+        assert_eq!(files[1].new_mode.as_deref(), Some("100755"));
+    }
+
+    #[test]
+    fn decodes_quoted_paths() {
+        let diff = "\
+diff --git \"a/caf\\303\\251.txt\" \"b/caf\\303\\251.txt\"
+new file mode 100644
+--- /dev/null
++++ \"b/caf\\303\\251.txt\"
+@@ -0,0 +1 @@
++x
+";
+        let files = parse_unified_diff(diff);
+        assert_eq!(files[0].display_path, "café.txt");
+    }
+
+    #[test]
+    fn resolves_git_revisions_to_commits() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let commit = commit_files(&repo, &[("a.txt", "one\n")]);
+        let target = repo.find_commit(Oid::from_str(&commit).unwrap()).unwrap();
+        repo.branch("feature", &target, false).unwrap();
+        repo.tag_lightweight("v1", target.as_object(), false)
+            .unwrap();
+
+        let source = SnapshotSource::Git(dir.path().to_path_buf());
+        assert_eq!(resolve_revision(&source, "feature").unwrap(), commit);
+        assert_eq!(resolve_revision(&source, "v1").unwrap(), commit);
+        assert_eq!(resolve_revision(&source, &commit[..10]).unwrap(), commit);
+        assert!(matches!(
+            resolve_revision(&source, "missing"),
+            Err(ReviewDiffError::UnknownRevision { .. })
+        ));
+    }
+
+    #[test]
+    fn diff_options_shape_git_snapshots_like_file_snapshots() {
+        use crate::review_capture::SnapshotCaptureSettings;
+        use crate::review_file_snapshots::FileSnapshotStore;
+
+        let moved: String = (1..=10).map(|n| format!("line {}\n", n)).collect();
+        let copied: String = (1..=10).map(|n| format!("source {}\n", n)).collect();
+        let base_files = [
+            ("old/moved.txt", moved.clone()),
+            ("spaces.txt", "a b\nc\n".to_string()),
+            ("source.txt", copied.clone()),
+        ];
+        let target_files = [
+            ("new/moved.txt", moved.replace("line 10", "line ten")),
+            ("spaces.txt", "a   b  \nc\n".to_string()),
+            ("source.txt", copied.replace("source 1\n", "source one\n")),
+            ("copy.txt", copied.clone()),
+        ];
+
+        let repo_dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(repo_dir.path()).unwrap();
+        let commit = |files: &[(&str, String)]| {
+            let files: Vec<(&str, &str)> = files
+                .iter()
+                .map(|(path, content)| (*path, content.as_str()))
+                .collect();
+            commit_files(&repo, &files)
+        };
+        let git_base = commit(&base_files);
+        let git_target = commit(&target_files);
+        let git = SnapshotSource::Git(repo_dir.path().to_path_buf());
+
+        let workspace = tempfile::tempdir().unwrap();
+        let store_dir = tempfile::tempdir().unwrap();
+        let store = FileSnapshotStore::new(store_dir.path().to_path_buf());
+        let settings = SnapshotCaptureSettings::default();
+        let write_all = |files: &[(&str, String)]| {
+            for (path, content) in files {
+                let path = workspace.path().join(path);
+                std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+                std::fs::write(path, content).unwrap();
+            }
+        };
+        write_all(&base_files);
+        let files_base = store.capture(workspace.path(), &settings).unwrap().0;
+        std::fs::remove_dir_all(workspace.path().join("old")).unwrap();
+        write_all(&target_files);
+        let files_target = store.capture(workspace.path(), &settings).unwrap().0;
+        let files = SnapshotSource::Files(store.clone());
+
+        // Path, change kind and hunk ranges; modes and function context in
+        // hunk headers are only reported by git.
+        fn shape_of(
+            source: &SnapshotSource,
+            base: &str,
+            target: &str,
+            options: &ReviewDiffOptions,
+        ) -> Vec<(String, DiffChangeKind, Vec<[u32; 4]>)> {
+            diff_range(source, base, target, options)
+                .unwrap()
+                .files
+                .into_iter()
+                .map(|file| {
+                    let ranges = file
+                        .hunks
+                        .iter()
+                        .map(|hunk| {
+                            [
+                                hunk.old_start,
+                                hunk.old_lines,
+                                hunk.new_start,
+                                hunk.new_lines,
+                            ]
+                        })
+                        .collect();
+                    (file.display_path, file.change_kind, ranges)
+                })
+                .collect()
+        }
+        let shape = |options: &ReviewDiffOptions| {
+            let git_shape = shape_of(&git, &git_base, &git_target, options);
+            assert_eq!(
+                git_shape,
+                shape_of(&files, &files_base, &files_target, options)
+            );
+            git_shape
+        };
+        let entry =
+            |path: &str, kind, ranges: &[[u32; 4]]| (path.to_string(), kind, ranges.to_vec());
+
+        let mut options = ReviewDiffOptions {
+            context_lines: 1,
+            ..Default::default()
+        };
+        assert_eq!(
+            shape(&options),
+            vec![
+                entry("copy.txt", DiffChangeKind::Added, &[[0, 0, 1, 10]]),
+                entry("new/moved.txt", DiffChangeKind::Renamed, &[[9, 2, 9, 2]]),
+                entry("source.txt", DiffChangeKind::Modified, &[[1, 2, 1, 2]]),
+                entry("spaces.txt", DiffChangeKind::Modified, &[[1, 2, 1, 2]]),
+            ]
+        );
+
+        // Like `git diff -C`, copy detection also finds renames.
+        options.context_lines = 3;
+        options.detect_renames = false;
+        options.detect_copies = true;
+        options.whitespace = WhitespaceMode::IgnoreChange;
+        assert_eq!(
+            shape(&options),
+            vec![
+                entry("copy.txt", DiffChangeKind::Copied, &[]),
+                entry("new/moved.txt", DiffChangeKind::Renamed, &[[7, 4, 7, 4]]),
+                entry("source.txt", DiffChangeKind::Modified, &[[1, 4, 1, 4]]),
+            ]
+        );
+
+        options.detect_copies = false;
+        options.pathspecs = vec!["old".to_string(), "s*.txt".to_string()];
+        assert_eq!(
+            shape(&options),
+            vec![
+                entry("old/moved.txt", DiffChangeKind::Deleted, &[[1, 10, 0, 0]]),
+                entry("source.txt", DiffChangeKind::Modified, &[[1, 4, 1, 4]]),
+            ]
+        );
+    }
+}
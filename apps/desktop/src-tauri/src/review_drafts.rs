@@ -0,0 +1,487 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::Context;
+use anyhow::Result;
+use anyhow::anyhow;
+use chrono::Utc;
+use serde::Deserialize;
+use serde::Serialize;
+use tokio::fs;
+use tokio::sync::RwLock;
+use ts_rs::TS;
+use uuid::Uuid;
+
+use crate::review_diff::DiffFile;
+use crate::review_diff::DiffLine;
+use crate::review_diff::DiffLineKind;
+
+/// Side of the diff a review comment is anchored to.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, TS)]
+#[serde(rename_all = "kebab-case")]
+pub enum ReviewCommentSide {
+    /// Line numbered in the base snapshot (removed lines).
+    Old,
+    /// Line numbered in the target snapshot (added and context lines).
+    New,
+}
+
+/// Draft review comment attached to a line in a snapshot range.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct ReviewComment {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base_event_id: Option<String>,
+    pub target_event_id: String,
+    pub file_path: String,
+    pub side: ReviewCommentSide,
+    pub line: u32,
+    pub body: String,
+    /// Text of the commented line when the comment was written.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context_line: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+    /// Submission that sent this comment to the agent, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub submission_id: Option<String>,
+}
+
+impl ReviewComment {
+    fn matches_range(&self, base_event_id: Option<&str>, target_event_id: &str) -> bool {
+        self.base_event_id.as_deref() == base_event_id && self.target_event_id == target_event_id
+    }
+}
+
+/// Comments from one range that were sent to the agent together.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct ReviewSubmission {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base_event_id: Option<String>,
+    pub target_event_id: String,
+    pub comment_ids: Vec<String>,
+    pub submitted_at: String,
+}
+
+/// Fields supplied when creating a review comment.
+#[derive(Debug, Clone)]
+pub struct NewReviewComment {
+    pub base_event_id: Option<String>,
+    pub target_event_id: String,
+    pub file_path: String,
+    pub side: ReviewCommentSide,
+    pub line: u32,
+    pub body: String,
+    pub context_line: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ReviewDraftState {
+    #[serde(default)]
+    conversations: HashMap<String, Vec<ReviewComment>>,
+    #[serde(default)]
+    submissions: HashMap<String, Vec<ReviewSubmission>>,
+}
+
+/// Review comment drafts for every conversation, persisted to a single file.
+#[derive(Clone)]
+pub struct ReviewDrafts {
+    state: Arc<RwLock<ReviewDraftState>>,
+    state_path: PathBuf,
+}
+
+impl ReviewDrafts {
+    pub fn new(state_path: PathBuf) -> Self {
+        Self {
+            state: Arc::new(RwLock::new(ReviewDraftState::default())),
+            state_path,
+        }
+    }
+
+    pub async fn load_state(&self) -> Result<()> {
+        let content = match fs::read_to_string(&self.state_path).await {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Ok(());
+            }
+            Err(e) => {
+                return Err(e).context("Failed to read review drafts file");
+            }
+        };
+
+        let loaded_state: ReviewDraftState =
+            serde_json::from_str(&content).context("Failed to parse review drafts JSON")?;
+
+        let mut state = self.state.write().await;
+        *state = loaded_state;
+
+        Ok(())
+    }
+
+    /// Comments for a conversation, limited to one range when a target is given.
+    pub async fn list_comments(
+        &self,
+        conversation_id: &str,
+        range: Option<(Option<&str>, &str)>,
+    ) -> Vec<ReviewComment> {
+        let state = self.state.read().await;
+        state
+            .conversations
+            .get(conversation_id)
+            .map(|comments| {
+                comments
+                    .iter()
+                    .filter(|comment| match range {
+                        Some((base, target)) => comment.matches_range(base, target),
+                        None => true,
+                    })
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    pub async fn add_comment(
+        &self,
+        conversation_id: &str,
+        input: NewReviewComment,
+    ) -> Result<ReviewComment> {
+        let now = Utc::now().to_rfc3339();
+        let comment = ReviewComment {
+            id: Uuid::new_v4().to_string(),
+            base_event_id: input.base_event_id,
+            target_event_id: input.target_event_id,
+            file_path: input.file_path,
+            side: input.side,
+            line: input.line,
+            body: input.body,
+            context_line: input.context_line,
+            created_at: now.clone(),
+            updated_at: now,
+            submission_id: None,
+        };
+
+        let mut state = self.state.write().await;
+        state
+            .conversations
+            .entry(conversation_id.to_string())
+            .or_default()
+            .push(comment.clone());
+        self.save_state(&state).await?;
+
+        Ok(comment)
+    }
+
+    pub async fn update_comment(
+        &self,
+        conversation_id: &str,
+        comment_id: &str,
+        body: String,
+    ) -> Result<ReviewComment> {
+        let mut state = self.state.write().await;
+        let comment = state
+            .conversations
+            .get_mut(conversation_id)
+            .and_then(|comments| comments.iter_mut().find(|comment| comment.id == comment_id))
+            .ok_or_else(|| anyhow!("Unknown review comment: {}", comment_id))?;
+        if comment.submission_id.is_some() {
+            return Err(anyhow!(
+                "Review comment {} was already submitted",
+                comment_id
+            ));
+        }
+        comment.body = body;
+        comment.updated_at = Utc::now().to_rfc3339();
+        let updated = comment.clone();
+        self.save_state(&state).await?;
+
+        Ok(updated)
+    }
+
+    pub async fn delete_comment(&self, conversation_id: &str, comment_id: &str) -> Result<()> {
+        let mut state = self.state.write().await;
+        let comments = state
+            .conversations
+            .get_mut(conversation_id)
+            .ok_or_else(|| anyhow!("Unknown review comment: {}", comment_id))?;
+        let before = comments.len();
+        comments.retain(|comment| comment.id != comment_id);
+        if comments.len() == before {
+            return Err(anyhow!("Unknown review comment: {}", comment_id));
+        }
+        if comments.is_empty() {
+            state.conversations.remove(conversation_id);
+        }
+        self.save_state(&state).await
+    }
+
+    /// Drop every comment and submission of a conversation.
+    pub async fn discard_conversation(&self, conversation_id: &str) -> Result<()> {
+        self.discard_conversations(&[conversation_id.to_string()])
+            .await
+    }
+
+    /// Drop the drafts and submissions of several conversations at once.
+    pub async fn discard_conversations(&self, conversation_ids: &[String]) -> Result<()> {
+        let mut state = self.state.write().await;
+        let mut changed = false;
+        for conversation_id in conversation_ids {
+            changed |= state.conversations.remove(conversation_id).is_some();
+            changed |= state.submissions.remove(conversation_id).is_some();
+        }
+        if !changed {
+            return Ok(());
+        }
+        self.save_state(&state).await
+    }
+
+    pub async fn list_submissions(&self, conversation_id: &str) -> Vec<ReviewSubmission> {
+        let state = self.state.read().await;
+        state
+            .submissions
+            .get(conversation_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Mark the given comments as sent and record the submission.
+    pub async fn record_submission(
+        &self,
+        conversation_id: &str,
+        base_event_id: Option<String>,
+        target_event_id: String,
+        comment_ids: Vec<String>,
+    ) -> Result<ReviewSubmission> {
+        let submission = ReviewSubmission {
+            id: Uuid::new_v4().to_string(),
+            base_event_id,
+            target_event_id,
+            comment_ids,
+            submitted_at: Utc::now().to_rfc3339(),
+        };
+
+        let mut state = self.state.write().await;
+        if let Some(comments) = state.conversations.get_mut(conversation_id) {
+            for comment in comments
+                .iter_mut()
+                .filter(|comment| submission.comment_ids.contains(&comment.id))
+            {
+                comment.submission_id = Some(submission.id.clone());
+            }
+        }
+        state
+            .submissions
+            .entry(conversation_id.to_string())
+            .or_default()
+            .push(submission.clone());
+        self.save_state(&state).await?;
+
+        Ok(submission)
+    }
+
+    /// Write the state while the caller still holds the write lock so saves land in order.
+    async fn save_state(&self, state: &ReviewDraftState) -> Result<()> {
+        let json =
+            serde_json::to_string_pretty(state).context("Failed to serialize review drafts")?;
+
+        if let Some(parent) = self.state_path.parent() {
+            fs::create_dir_all(parent)
+                .await
+                .context("Failed to create review drafts directory")?;
+        }
+
+        fs::write(&self.state_path, json)
+            .await
+            .context("Failed to write review drafts file")?;
+
+        Ok(())
+    }
+}
+
+/// Build the consolidated review message, quoting each commented line from `files`.
+///
+/// Comments whose line no longer appears in the diff fall back to the line text
+/// captured when they were written.
+pub fn build_review_message(
+    turn_label: &str,
+    comments: &[ReviewComment],
+    files: &[DiffFile],
+) -> String {
+    let mut message = format!("Here is my consolidated review of {}:\n", turn_label);
+    for comment in comments {
+        let line_label = match comment.side {
+            ReviewCommentSide::New => format!("line {}", comment.line),
+            ReviewCommentSide::Old => format!("removed line {}", comment.line),
+        };
+        message.push_str(&format!(
+            "- {} ({}): {}",
+            comment.file_path, line_label, comment.body
+        ));
+
+        let context = match find_commented_line(comment, files) {
+            Some(line) => Some(format!("{}{}", line_prefix(line.kind), line.text)),
+            None => comment.context_line.clone(),
+        };
+        if let Some(context) = context.filter(|context| !context.trim().is_empty()) {
+            message.push_str(&format!("\n    Context: {}", context));
+        }
+        message.push('\n');
+    }
+    message.push_str("\nPlease address each comment before continuing.");
+    message
+}
+
+fn find_commented_line<'a>(comment: &ReviewComment, files: &'a [DiffFile]) -> Option<&'a DiffLine> {
+    let path = comment.file_path.as_str();
+    files
+        .iter()
+        .filter(|file| {
+            file.display_path == path
+                || file.new_path.as_deref() == Some(path)
+                || file.old_path.as_deref() == Some(path)
+        })
+        .flat_map(|file| file.hunks.iter())
+        .flat_map(|hunk| hunk.lines.iter())
+        .find(|line| match comment.side {
+            ReviewCommentSide::New => line.new_number == Some(comment.line),
+            ReviewCommentSide::Old => {
+                line.kind != DiffLineKind::Addition && line.old_number == Some(comment.line)
+            }
+        })
+        .filter(|line| line.kind != DiffLineKind::Metadata)
+}
+
+fn line_prefix(kind: DiffLineKind) -> &'static str {
+    match kind {
+        DiffLineKind::Addition => "+",
+        DiffLineKind::Removal => "-",
+        DiffLineKind::Context | DiffLineKind::Metadata => " ",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn draft(target_event_id: &str, body: &str) -> NewReviewComment {
+        NewReviewComment {
+            base_event_id: None,
+            target_event_id: target_event_id.to_string(),
+            file_path: "src/lib.rs".to_string(),
+            side: ReviewCommentSide::New,
+            line: 12,
+            body: body.to_string(),
+            context_line: Some("let value = 1;".to_string()),
+        }
+    }
+
+    #[tokio::test]
+    async fn drafts_survive_reload_and_filter_by_range() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("review-drafts.json");
+
+        let drafts = ReviewDrafts::new(path.clone());
+        let first = drafts
+            .add_comment("conv", draft("turn-1", "first"))
+            .await
+            .unwrap();
+        let second = drafts
+            .add_comment("conv", draft("turn-2", "second"))
+            .await
+            .unwrap();
+        drafts
+            .update_comment("conv", &first.id, "first, edited".to_string())
+            .await
+            .unwrap();
+
+        let reloaded = ReviewDrafts::new(path);
+        reloaded.load_state().await.unwrap();
+        let turn_one = reloaded.list_comments("conv", Some((None, "turn-1"))).await;
+        assert_eq!(turn_one.len(), 1);
+        assert_eq!(turn_one[0].body, "first, edited");
+        assert_eq!(reloaded.list_comments("conv", None).await.len(), 2);
+
+        reloaded.delete_comment("conv", &second.id).await.unwrap();
+        assert!(reloaded.delete_comment("conv", &second.id).await.is_err());
+        assert!(
+            reloaded
+                .list_comments("conv", Some((None, "turn-2")))
+                .await
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn submitted_comments_are_marked_and_locked() {
+        let dir = tempfile::tempdir().unwrap();
+        let drafts = ReviewDrafts::new(dir.path().join("review-drafts.json"));
+        let comment = drafts
+            .add_comment("conv", draft("turn-1", "fix"))
+            .await
+            .unwrap();
+
+        let submission = drafts
+            .record_submission("conv", None, "turn-1".to_string(), vec![comment.id.clone()])
+            .await
+            .unwrap();
+
+        let stored = drafts.list_comments("conv", None).await;
+        assert_eq!(
+            stored[0].submission_id.as_deref(),
+            Some(submission.id.as_str())
+        );
+        assert_eq!(drafts.list_submissions("conv").await, vec![submission]);
+        assert!(
+            drafts
+                .update_comment("conv", &comment.id, "again".to_string())
+                .await
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn review_message_quotes_lines_from_the_diff() {
+        let files = crate::review_diff::parse_unified_diff(
+            "diff --git a/src/lib.rs b/src/lib.rs\n\
+             --- a/src/lib.rs\n\
+             +++ b/src/lib.rs\n\
+             @@ -10,3 +10,3 @@\n\
+             \x20fn main() {\n\
+             -    let value = 0;\n\
+             +    let value = 1;\n\
+             \x20}\n",
+        );
+        let comment = |side, line, body: &str, context_line: Option<&str>| ReviewComment {
+            id: body.to_string(),
+            base_event_id: None,
+            target_event_id: "turn-1".to_string(),
+            file_path: "src/lib.rs".to_string(),
+            side,
+            line,
+            body: body.to_string(),
+            context_line: context_line.map(str::to_string),
+            created_at: String::new(),
+            updated_at: String::new(),
+            submission_id: None,
+        };
+        let comments = vec![
+            comment(ReviewCommentSide::New, 11, "why one?", None),
+            comment(ReviewCommentSide::Old, 11, "keep zero", None),
+            comment(ReviewCommentSide::New, 40, "stale", Some("old text")),
+        ];
+
+        assert_eq!(
+            build_review_message("turn 2", &comments, &files),
+            "Here is my consolidated review of turn 2:\n\
+             - src/lib.rs (line 11): why one?\n    Context: +    let value = 1;\n\
+             - src/lib.rs (removed line 11): keep zero\n    Context: -    let value = 0;\n\
+             - src/lib.rs (line 40): stale\n    Context: old text\n\
+             \nPlease address each comment before continuing."
+        );
+    }
+}
@@ -0,0 +1,342 @@
+use std::path::Path;
+
+use git2::Branch;
+use git2::BranchType;
+use git2::Commit;
+use git2::DiffFindOptions;
+use git2::DiffOptions;
+use git2::Email;
+use git2::EmailCreateOptions;
+use git2::Oid;
+use git2::Repository;
+use git2::Signature;
+use serde::Deserialize;
+use serde::Serialize;
+use ts_rs::TS;
+
+use crate::review_diff::ReviewDiffError;
+use crate::review_diff::open_repository;
+use crate::review_snapshots::TurnSnapshotSummary;
+
+/// Longest commit subject generated from a turn prompt, in characters.
+const SUMMARY_MAX_LEN: usize = 72;
+
+/// Failures raised while turning snapshots into regular git history.
+#[derive(Debug, thiserror::Error)]
+pub enum ReviewExportError {
+    #[error("invalid branch name: {0}")]
+    InvalidBranchName(String),
+    #[error("branch {0} already exists")]
+    BranchExists(String),
+    #[error("the selected turns made no changes to export")]
+    NoChanges,
+    #[error("snapshot commit {commit} is not available: {source}")]
+    MissingCommit {
+        commit: String,
+        #[source]
+        source: git2::Error,
+    },
+    #[error(transparent)]
+    Diff(#[from] ReviewDiffError),
+    #[error(transparent)]
+    Git(#[from] git2::Error),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+/// A turn snapshot replayed as a regular commit.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportedTurnCommit {
+    pub turn_index: u32,
+    pub event_id: String,
+    pub snapshot_commit_id: String,
+    pub commit_id: String,
+    pub summary: String,
+}
+
+/// Commits replayed for a range, all parented on `root`.
+struct ReplayedTurns<'repo> {
+    root: Commit<'repo>,
+    commits: Vec<(Commit<'repo>, ExportedTurnCommit)>,
+}
+
+/// Branch name used when the caller does not pick one.
+pub fn default_branch_name(conversation_id: &str, turns: &[TurnSnapshotSummary]) -> String {
+    match (turns.first(), turns.last()) {
+        (Some(first), Some(last)) if first.turn_index != last.turn_index => format!(
+            "pasture/{}/turns-{}-{}",
+            conversation_id, first.turn_index, last.turn_index
+        ),
+        (_, Some(last)) => format!("pasture/{}/turn-{}", conversation_id, last.turn_index),
+        _ => format!("pasture/{}", conversation_id),
+    }
+}
+
+/// Write the turns as a `git format-patch` style mbox series that
+/// `git am` can apply.
+pub fn write_patch_series(
+    repo_path: &Path,
+    conversation_id: &str,
+    base_commit: &str,
+    turns: &[TurnSnapshotSummary],
+    output_path: &Path,
+) -> Result<Vec<ExportedTurnCommit>, ReviewExportError> {
+    let repo = open_repository(repo_path)?;
+    let replayed = replay_turns(&repo, conversation_id, base_commit, turns)?;
+
+    let count = replayed.commits.len();
+    let mut series = Vec::new();
+    let mut parent_tree = replayed.root.tree()?;
+    for (position, (commit, _)) in replayed.commits.iter().enumerate() {
+        let tree = commit.tree()?;
+        let mut diff_options = DiffOptions::new();
+        diff_options.show_binary(true);
+        let mut diff =
+            repo.diff_tree_to_tree(Some(&parent_tree), Some(&tree), Some(&mut diff_options))?;
+        let mut find_options = DiffFindOptions::new();
+        find_options.renames(true);
+        diff.find_similar(Some(&mut find_options))?;
+
+        let email = Email::from_diff(
+            &diff,
+            position + 1,
+            count,
+            &commit.id(),
+            commit.summary().unwrap_or_default(),
+            commit.body().unwrap_or_default(),
+            &commit.author(),
+            &mut EmailCreateOptions::new(),
+        )?;
+        series.extend_from_slice(email.as_slice());
+        parent_tree = tree;
+    }
+
+    std::fs::write(output_path, series)?;
+    Ok(replayed.commits.into_iter().map(|(_, turn)| turn).collect())
+}
+
+/// Replay the turns as commits and point a local branch at the last one.
+/// Returns the branch head and the replayed commits.
+pub fn create_turn_branch(
+    repo_path: &Path,
+    conversation_id: &str,
+    base_commit: &str,
+    turns: &[TurnSnapshotSummary],
+    branch_name: &str,
+    force: bool,
+) -> Result<(String, Vec<ExportedTurnCommit>), ReviewExportError> {
+    if !Branch::name_is_valid(branch_name)? {
+        return Err(ReviewExportError::InvalidBranchName(
+            branch_name.to_string(),
+        ));
+    }
+
+    let repo = open_repository(repo_path)?;
+    if !force && repo.find_branch(branch_name, BranchType::Local).is_ok() {
+        return Err(ReviewExportError::BranchExists(branch_name.to_string()));
+    }
+
+    let replayed = replay_turns(&repo, conversation_id, base_commit, turns)?;
+    let Some((head, _)) = replayed.commits.last() else {
+        return Err(ReviewExportError::NoChanges);
+    };
+    repo.branch(branch_name, head, force)?;
+
+    let head_id = head.id().to_string();
+    Ok((
+        head_id,
+        replayed.commits.into_iter().map(|(_, turn)| turn).collect(),
+    ))
+}
+
+/// Create one commit per turn that changed the workspace. The commits are
+/// not referenced by anything until a caller points a ref at them.
+fn replay_turns<'repo>(
+    repo: &'repo Repository,
+    conversation_id: &str,
+    base_commit: &str,
+    turns: &[TurnSnapshotSummary],
+) -> Result<ReplayedTurns<'repo>, ReviewExportError> {
+    let signature = export_signature(repo)?;
+    let base = find_commit(repo, base_commit)?;
+    let root = export_root(repo, &base, &signature)?;
+
+    let mut parent = root.clone();
+    let mut commits = Vec::new();
+    for turn in turns {
+        let tree = find_commit(repo, &turn.commit_id)?.tree()?;
+        // Turns that left the workspace untouched would only add empty commits.
+        if tree.id() == parent.tree_id() {
+            continue;
+        }
+
+        let summary = turn_summary(turn);
+        let message = turn_message(&summary, turn, conversation_id);
+        let oid = repo.commit(None, &signature, &signature, &message, &tree, &[&parent])?;
+        let commit = repo.find_commit(oid)?;
+        commits.push((
+            commit.clone(),
+            ExportedTurnCommit {
+                turn_index: turn.turn_index,
+                event_id: turn.event_id.clone(),
+                snapshot_commit_id: turn.commit_id.clone(),
+                commit_id: oid.to_string(),
+                summary,
+            },
+        ));
+        parent = commit;
+    }
+
+    if commits.is_empty() {
+        return Err(ReviewExportError::NoChanges);
+    }
+    Ok(ReplayedTurns { root, commits })
+}
+
+/// The base snapshot is a ghost commit on top of the `HEAD` it was taken
+/// from. That `HEAD` is reused when the workspace was clean; otherwise the
+/// uncommitted state gets its own commit so turn commits only carry the
+/// agent's changes.
+fn export_root<'repo>(
+    repo: &'repo Repository,
+    base: &Commit<'repo>,
+    signature: &Signature<'_>,
+) -> Result<Commit<'repo>, ReviewExportError> {
+    let head = base.parent(0).ok();
+    if let Some(head) = &head
+        && head.tree_id() == base.tree_id()
+    {
+        return Ok(head.clone());
+    }
+
+    let parents: Vec<&Commit<'repo>> = head.iter().collect();
+    let oid = repo.commit(
+        None,
+        signature,
+        signature,
+        "Workspace changes before the exported turns\n",
+        &base.tree()?,
+        &parents,
+    )?;
+    Ok(repo.find_commit(oid)?)
+}
+
+fn find_commit<'repo>(
+    repo: &'repo Repository,
+    commit: &str,
+) -> Result<Commit<'repo>, ReviewExportError> {
+    Oid::from_str(commit)
+        .and_then(|oid| repo.find_commit(oid))
+        .map_err(|source| ReviewExportError::MissingCommit {
+            commit: commit.to_string(),
+            source,
+        })
+}
+
+/// The user's configured identity, falling back to the app's.
+fn export_signature(repo: &Repository) -> Result<Signature<'static>, git2::Error> {
+    repo.signature()
+        .or_else(|_| Signature::now("Pasture", "pasture@localhost"))
+}
+
+fn turn_summary(turn: &TurnSnapshotSummary) -> String {
+    let prompt_line = turn
+        .prompt
+        .as_deref()
+        .and_then(|prompt| prompt.lines().map(str::trim).find(|line| !line.is_empty()));
+    let Some(line) = prompt_line else {
+        return format!("Turn {}", turn.turn_index);
+    };
+
+    let summary = format!("Turn {}: {}", turn.turn_index, line);
+    match summary.char_indices().nth(SUMMARY_MAX_LEN) {
+        Some((truncate_at, _)) => format!("{}…", summary[..truncate_at].trim_end()),
+        None => summary,
+    }
+}
+
+fn turn_message(summary: &str, turn: &TurnSnapshotSummary, conversation_id: &str) -> String {
+    let mut message = format!("{}\n\n", summary);
+    if let Some(prompt) = turn.prompt.as_deref().map(str::trim)
+        && !prompt.is_empty()
+    {
+        message.push_str(prompt);
+        message.push_str("\n\n");
+    }
+    message.push_str(&format!(
+        "Pasture-Conversation: {}\nPasture-Snapshot: {}\n",
+        conversation_id, turn.commit_id
+    ));
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::review_snapshots::SnapshotOrigin;
+    use chrono::Utc;
+
+    fn commit_file(repo: &Repository, content: &str, parents: &[&Commit<'_>]) -> String {
+        let blob = repo.blob(content.as_bytes()).unwrap();
+        let mut builder = repo.treebuilder(None).unwrap();
+        builder.insert("notes.txt", blob, 0o100644).unwrap();
+        let tree = repo.find_tree(builder.write().unwrap()).unwrap();
+        let signature = Signature::now("Pasture", "pasture@example.com").unwrap();
+        repo.commit(None, &signature, &signature, "snapshot", &tree, parents)
+            .unwrap()
+            .to_string()
+    }
+
+    fn turn(turn_index: u32, commit_id: String, prompt: &str) -> TurnSnapshotSummary {
+        TurnSnapshotSummary {
+            event_id: format!("event-{}", turn_index),
+            commit_id,
+            turn_index,
+            captured_at: Utc::now(),
+            prompt: Some(prompt.to_string()),
+            stats: None,
+            origin: SnapshotOrigin::Turn,
+        }
+    }
+
+    #[test]
+    fn exports_turns_as_branch_and_patch_series() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let head = commit_file(&repo, "one\n", &[]);
+        let head_commit = repo.find_commit(Oid::from_str(&head).unwrap()).unwrap();
+        let base = commit_file(&repo, "one\n", &[&head_commit]);
+        let turns = vec![
+            turn(1, commit_file(&repo, "one\ntwo\n", &[]), "Add two"),
+            turn(2, commit_file(&repo, "one\ntwo\n", &[]), "Do nothing"),
+            turn(3, commit_file(&repo, "one\nthree\n", &[]), "Swap in three"),
+        ];
+
+        let name = default_branch_name("conv", &turns);
+        assert_eq!(name, "pasture/conv/turns-1-3");
+        let (branch_head, commits) =
+            create_turn_branch(dir.path(), "conv", &base, &turns, &name, false).unwrap();
+        assert_eq!(commits.len(), 2);
+        assert_eq!(commits[1].summary, "Turn 3: Swap in three");
+
+        let branch = repo.find_branch(&name, BranchType::Local).unwrap();
+        let tip = branch.get().peel_to_commit().unwrap();
+        assert_eq!(tip.id().to_string(), branch_head);
+        assert_eq!(
+            tip.parent(0).unwrap().parent(0).unwrap().id(),
+            head_commit.id()
+        );
+        assert!(matches!(
+            create_turn_branch(dir.path(), "conv", &base, &turns, &name, false),
+            Err(ReviewExportError::BranchExists(_))
+        ));
+
+        let patch_path = dir.path().join("turns.patch");
+        write_patch_series(dir.path(), "conv", &base, &turns, &patch_path).unwrap();
+        let patch = std::fs::read_to_string(&patch_path).unwrap();
+        assert!(patch.contains("Subject: [PATCH 1/2] Turn 1: Add two"));
+        assert!(patch.contains("Subject: [PATCH 2/2] Turn 3: Swap in three"));
+        assert!(patch.contains("-two\n+three\n"));
+    }
+}
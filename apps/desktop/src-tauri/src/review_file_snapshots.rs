@@ -0,0 +1,954 @@
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::collections::HashSet;
+use std::io::ErrorKind;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::RwLock;
+use std::time::Duration;
+use std::time::SystemTime;
+
+use anyhow::Context;
+use anyhow::Result;
+use anyhow::bail;
+use ignore::WalkBuilder;
+use serde::Deserialize;
+use serde::Serialize;
+use sha2::Digest;
+use sha2::Sha256;
+use similar::Algorithm;
+use similar::DiffTag;
+use similar::TextDiff;
+use similar::capture_diff_slices;
+use similar::group_diff_ops;
+use similar::udiff::UnifiedHunkHeader;
+
+use crate::atomic_file::TEMP_FILE_PREFIX;
+use crate::atomic_file::write_atomically;
+use crate::review_capture::CaptureFilter;
+use crate::review_capture::SnapshotCaptureReport;
+use crate::review_capture::SnapshotCaptureSettings;
+use crate::review_diff::BINARY_SNIFF_BYTES;
+use crate::review_diff::FilePatch;
+use crate::review_diff::ReviewDiffError;
+use crate::review_diff::ReviewDiffOptions;
+use crate::review_diff::WhitespaceMode;
+
+/// Files larger than this are left out of file snapshots.
+pub const MAX_SNAPSHOT_FILE_BYTES: u64 = 5 * 1024 * 1024;
+/// Capturing stops with an error once a snapshot grows past this many files.
+pub const MAX_SNAPSHOT_FILES: usize = 20_000;
+/// Capturing stops with an error once the included files exceed this size.
+pub const MAX_SNAPSHOT_TOTAL_BYTES: u64 = 256 * 1024 * 1024;
+
+/// Content comparisons made per diff when looking for renames and copies;
+/// beyond this only identical files are paired.
+const MAX_SIMILARITY_PAIRS: usize = 1000;
+/// Files larger than this are only paired when identical.
+const MAX_SIMILARITY_FILE_BYTES: u64 = 1024 * 1024;
+
+/// Snapshot store for workspaces that are not git repositories.
+///
+/// File contents are stored once under `objects/` keyed by their SHA-256, and
+/// each snapshot is a manifest under `manifests/` whose id is the hash of the
+/// manifest itself. Files are selected with the usual ignore rules
+/// (`.gitignore`, `.ignore`, global excludes) even outside a repository.
+#[derive(Debug, Clone)]
+pub struct FileSnapshotStore {
+    root: PathBuf,
+    /// Held for reading by captures and for writing by garbage collection.
+    gc_lock: Arc<RwLock<()>>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Manifest {
+    files: BTreeMap<String, ManifestEntry>,
+    /// Files left out because they exceeded [`MAX_SNAPSHOT_FILE_BYTES`] or
+    /// the workspace's capture limits.
+    #[serde(default)]
+    skipped: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ManifestEntry {
+    hash: String,
+    size: u64,
+    #[serde(default)]
+    executable: bool,
+}
+
+impl FileSnapshotStore {
+    pub fn new(root: PathBuf) -> Self {
+        Self {
+            root,
+            gc_lock: Arc::new(RwLock::new(())),
+        }
+    }
+
+    /// Copy the workspace into the store, leaving out what `settings`
+    /// excludes, and return the snapshot id.
+    pub fn capture(
+        &self,
+        cwd: &Path,
+        settings: &SnapshotCaptureSettings,
+    ) -> Result<(String, SnapshotCaptureReport)> {
+        let _capturing = self
+            .gc_lock
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mut manifest = Manifest::default();
+        let mut total_bytes = 0u64;
+        let mut filter = CaptureFilter::new(settings)?;
+
+        let walker = WalkBuilder::new(cwd)
+            .hidden(false)
+            .require_git(false)
+            .follow_links(false)
+            .filter_entry(|entry| entry.file_name() != ".git")
+            .build();
+        for entry in walker {
+            let entry = entry.context("Failed to walk workspace")?;
+            if !entry.file_type().is_some_and(|kind| kind.is_file()) {
+                continue;
+            }
+            let Some(relative) = relative_path(cwd, entry.path()) else {
+                continue;
+            };
+            if filter.is_ignored(&relative) {
+                continue;
+            }
+
+            let metadata = entry
+                .metadata()
+                .with_context(|| format!("Failed to stat {}", entry.path().display()))?;
+            if metadata.len() > MAX_SNAPSHOT_FILE_BYTES {
+                manifest.skipped.push(relative);
+                continue;
+            }
+            if !filter.admit(&relative, metadata.len()) {
+                manifest.skipped.push(relative);
+                continue;
+            }
+
+            let bytes = match std::fs::read(entry.path()) {
+                Ok(bytes) => bytes,
+                // Removed between listing and reading.
+                Err(err) if err.kind() == ErrorKind::NotFound => continue,
+                Err(err) => {
+                    return Err(err)
+                        .with_context(|| format!("Failed to read {}", entry.path().display()));
+                }
+            };
+            total_bytes += bytes.len() as u64;
+            if manifest.files.len() >= MAX_SNAPSHOT_FILES || total_bytes > MAX_SNAPSHOT_TOTAL_BYTES
+            {
+                bail!(
+                    "Workspace exceeds file snapshot limits ({} files, {} bytes)",
+                    MAX_SNAPSHOT_FILES,
+                    MAX_SNAPSHOT_TOTAL_BYTES
+                );
+            }
+
+            let hash = self.write_object(&bytes)?;
+            manifest.files.insert(
+                relative,
+                ManifestEntry {
+                    hash,
+                    size: bytes.len() as u64,
+                    executable: is_executable(&metadata),
+                },
+            );
+        }
+
+        manifest.skipped.sort();
+        let json = serde_json::to_vec_pretty(&manifest).context("Failed to serialize manifest")?;
+        let id = hash_bytes(&json);
+        let path = self.manifest_path(&id);
+        if path.exists() {
+            // Count a reused manifest as new so collection spares it until
+            // the conversation has recorded it.
+            std::fs::File::options()
+                .write(true)
+                .open(&path)
+                .and_then(|file| file.set_modified(SystemTime::now()))
+                .with_context(|| format!("Failed to update {}", path.display()))?;
+        } else {
+            write_atomically(&path, &json)?;
+        }
+        Ok((id, filter.report))
+    }
+
+    /// Per-file patches between two snapshots, formatted like `git diff`.
+    pub(crate) fn collect_patches(
+        &self,
+        base: &str,
+        target: &str,
+        paths: Option<&[String]>,
+        body_limit: usize,
+        options: &ReviewDiffOptions,
+    ) -> Result<Vec<FilePatch>, ReviewDiffError> {
+        let base_manifest = self.load_manifest(base)?;
+        let target_manifest = self.load_manifest(target)?;
+
+        let mut deleted = Vec::new();
+        let mut modified = Vec::new();
+        for (path, entry) in &base_manifest.files {
+            match target_manifest.files.get(path) {
+                None => deleted.push(path.as_str()),
+                Some(new) if new != entry => modified.push(path.as_str()),
+                Some(_) => {}
+            }
+        }
+        let added: Vec<&str> = target_manifest
+            .files
+            .keys()
+            .filter(|path| !base_manifest.files.contains_key(*path))
+            .map(String::as_str)
+            .collect();
+
+        let pairs = self.find_similar(
+            &base_manifest,
+            &target_manifest,
+            &deleted,
+            &modified,
+            &added,
+            options,
+        )?;
+        let mut changes: Vec<FileChange> = pairs.clone();
+        changes.extend(
+            deleted
+                .iter()
+                .filter(|path| {
+                    !pairs.iter().any(|pair| {
+                        pair.relation == Some(ChangeRelation::Rename) && pair.old_path == Some(path)
+                    })
+                })
+                .map(|path| FileChange::plain(Some(path), None)),
+        );
+        changes.extend(
+            added
+                .iter()
+                .filter(|path| !pairs.iter().any(|pair| pair.new_path == Some(path)))
+                .map(|path| FileChange::plain(None, Some(path))),
+        );
+        changes.extend(
+            modified
+                .iter()
+                .map(|path| FileChange::plain(Some(path), Some(path))),
+        );
+        changes.sort_by_key(|change| change.new_path.or(change.old_path));
+
+        let mut patches = Vec::new();
+        for change in changes {
+            let selected = paths.is_none_or(|paths| {
+                [change.old_path, change.new_path]
+                    .into_iter()
+                    .flatten()
+                    .any(|path| paths.iter().any(|selected| selected == path))
+            });
+            if selected {
+                patches.push(self.file_patch(
+                    &change,
+                    &base_manifest,
+                    &target_manifest,
+                    body_limit,
+                    options,
+                )?);
+            }
+        }
+        Ok(patches)
+    }
+
+    /// Pair added files with the deleted (renames) or modified (copies) files
+    /// they resemble most, like `git diff -M -C`.
+    fn find_similar<'a>(
+        &self,
+        base: &'a Manifest,
+        target: &'a Manifest,
+        deleted: &[&'a str],
+        modified: &[&'a str],
+        added: &[&'a str],
+        options: &ReviewDiffOptions,
+    ) -> Result<Vec<FileChange<'a>>, ReviewDiffError> {
+        let threshold = u32::from(options.similarity_threshold());
+        let mut budget = MAX_SIMILARITY_PAIRS;
+        let mut pairs: Vec<FileChange<'a>> = Vec::new();
+
+        for new_path in added {
+            let new_entry = &target.files[*new_path];
+            // Renames come first so they win ties with copies.
+            let mut candidates = Vec::new();
+            if options.detect_renames || options.detect_copies {
+                candidates.extend(
+                    deleted
+                        .iter()
+                        .filter(|path| {
+                            !pairs.iter().any(|pair| {
+                                pair.relation == Some(ChangeRelation::Rename)
+                                    && pair.old_path == Some(**path)
+                            })
+                        })
+                        .map(|path| (*path, ChangeRelation::Rename)),
+                );
+            }
+            if options.detect_copies {
+                candidates.extend(
+                    modified
+                        .iter()
+                        .chain(deleted)
+                        .map(|path| (*path, ChangeRelation::Copy)),
+                );
+            }
+
+            let mut best: Option<(&str, ChangeRelation, u32)> = None;
+            for (old_path, relation) in candidates {
+                let old_entry = &base.files[old_path];
+                let score = if old_entry.hash == new_entry.hash {
+                    Some(100)
+                } else if budget > 0 {
+                    budget -= 1;
+                    self.similarity(old_entry, new_entry)?
+                } else {
+                    None
+                };
+                if let Some(score) = score
+                    && score >= threshold
+                    && best.is_none_or(|(_, _, best_score)| score > best_score)
+                {
+                    best = Some((old_path, relation, score));
+                }
+            }
+
+            if let Some((old_path, relation, score)) = best {
+                pairs.push(FileChange {
+                    old_path: Some(old_path),
+                    new_path: Some(new_path),
+                    relation: Some(relation),
+                    similarity: score,
+                });
+            }
+        }
+        Ok(pairs)
+    }
+
+    /// Percentage of matching lines, or `None` for binary or very large files.
+    fn similarity(
+        &self,
+        old: &ManifestEntry,
+        new: &ManifestEntry,
+    ) -> Result<Option<u32>, ReviewDiffError> {
+        if old.size.max(new.size) > MAX_SIMILARITY_FILE_BYTES {
+            return Ok(None);
+        }
+        let old_bytes = self.read_object(&old.hash)?;
+        let new_bytes = self.read_object(&new.hash)?;
+        let (Some(old_text), Some(new_text)) = (as_text(&old_bytes), as_text(&new_bytes)) else {
+            return Ok(None);
+        };
+        let ratio = TextDiff::from_lines(old_text, new_text).ratio();
+        Ok(Some((ratio * 100.0) as u32))
+    }
+
+    /// Files in `target` that are missing from `base`, resolved against `cwd`.
+    pub fn added_paths(
+        &self,
+        cwd: &Path,
+        base: &str,
+        target: &str,
+    ) -> Result<Vec<PathBuf>, ReviewDiffError> {
+        let base_manifest = self.load_manifest(base)?;
+        let target_manifest = self.load_manifest(target)?;
+        Ok(target_manifest
+            .files
+            .keys()
+            .filter(|path| !base_manifest.files.contains_key(*path))
+            .map(|path| cwd.join(path))
+            .collect())
+    }
+
+    /// Contents of `path` in `snapshot`, or `None` when it was not captured.
+    pub fn read_file(
+        &self,
+        snapshot: &str,
+        path: &str,
+    ) -> Result<Option<Vec<u8>>, ReviewDiffError> {
+        let manifest = self.load_manifest(snapshot)?;
+        match manifest.files.get(path) {
+            Some(entry) => Ok(Some(self.read_object(&entry.hash)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Rewrite every file recorded in `snapshot` whose content differs on disk.
+    /// Files skipped for size are left untouched.
+    pub fn restore(&self, cwd: &Path, snapshot: &str) -> Result<()> {
+        let manifest = self.load_manifest(snapshot)?;
+        for (relative, entry) in &manifest.files {
+            let path = cwd.join(relative);
+            let unchanged = std::fs::read(&path)
+                .map(|bytes| hash_bytes(&bytes) == entry.hash)
+                .unwrap_or(false);
+            if !unchanged {
+                if let Some(parent) = path.parent() {
+                    std::fs::create_dir_all(parent)
+                        .with_context(|| format!("Failed to create {}", parent.display()))?;
+                }
+                let bytes = self.read_object(&entry.hash)?;
+                std::fs::write(&path, bytes)
+                    .with_context(|| format!("Failed to write {}", path.display()))?;
+            }
+            set_executable(&path, entry.executable)?;
+        }
+        Ok(())
+    }
+
+    /// Delete manifests that are not `live` and the objects no remaining
+    /// manifest refers to. Manifests written within the last `grace` are kept
+    /// unless listed in `discarded`, since a conversation records a capture
+    /// only after the store has written it. Returns the number of manifests
+    /// removed.
+    pub fn collect_garbage(
+        &self,
+        live: &HashSet<String>,
+        discarded: &HashSet<String>,
+        grace: Duration,
+    ) -> Result<u32> {
+        // Objects are written before the manifest naming them, so no capture
+        // may run while unreferenced objects are swept.
+        let _collecting = self
+            .gc_lock
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let cutoff = SystemTime::now()
+            .checked_sub(grace)
+            .unwrap_or(SystemTime::UNIX_EPOCH);
+        let is_old = |path: &Path| {
+            std::fs::symlink_metadata(path)
+                .and_then(|metadata| metadata.modified())
+                .is_ok_and(|modified| modified <= cutoff)
+        };
+
+        let mut reachable = HashSet::new();
+        let mut removed = 0;
+        for path in dir_entries(&self.root.join("manifests"))? {
+            let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+            let Some(id) = name.strip_suffix(".json") else {
+                if name.starts_with(TEMP_FILE_PREFIX) && is_old(&path) {
+                    remove_file(&path)?;
+                }
+                continue;
+            };
+            if live.contains(id) || (!discarded.contains(id) && !is_old(&path)) {
+                // Sweeping objects without knowing what a kept manifest
+                // references would break it, so any read error aborts.
+                let manifest = self.load_manifest(id)?;
+                reachable.extend(manifest.files.into_values().map(|entry| entry.hash));
+            } else {
+                remove_file(&path)?;
+                removed += 1;
+            }
+        }
+
+        for dir in dir_entries(&self.root.join("objects"))? {
+            let Some(prefix) = dir.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+            let prefix = prefix.to_string();
+            for path in dir_entries(&dir)? {
+                let Some(rest) = path.file_name().and_then(|name| name.to_str()) else {
+                    continue;
+                };
+                let unused = if rest.starts_with(TEMP_FILE_PREFIX) {
+                    is_old(&path)
+                } else {
+                    !reachable.contains(&format!("{prefix}{rest}"))
+                };
+                if unused {
+                    remove_file(&path)?;
+                }
+            }
+            // Only succeeds once the directory is empty.
+            let _ = std::fs::remove_dir(&dir);
+        }
+        Ok(removed)
+    }
+
+    fn file_patch(
+        &self,
+        change: &FileChange,
+        base_manifest: &Manifest,
+        target_manifest: &Manifest,
+        body_limit: usize,
+        options: &ReviewDiffOptions,
+    ) -> Result<FilePatch, ReviewDiffError> {
+        let old = change
+            .old_path
+            .and_then(|path| base_manifest.files.get(path));
+        let new = change
+            .new_path
+            .and_then(|path| target_manifest.files.get(path));
+        let old_name = change.old_path.or(change.new_path).unwrap_or_default();
+        let new_name = change.new_path.or(change.old_path).unwrap_or_default();
+        let old_mode = old.map(ManifestEntry::mode);
+        let new_mode = new.map(ManifestEntry::mode);
+
+        let mut header = format!("diff --git a/{old_name} b/{new_name}\n");
+        match (old_mode, new_mode) {
+            (None, Some(mode)) => header.push_str(&format!("new file mode {}\n", mode)),
+            (Some(mode), None) => header.push_str(&format!("deleted file mode {}\n", mode)),
+            (Some(old_mode), Some(new_mode)) if old_mode != new_mode => {
+                header.push_str(&format!("old mode {}\nnew mode {}\n", old_mode, new_mode));
+            }
+            _ => {}
+        }
+        if let Some(relation) = change.relation {
+            let verb = match relation {
+                ChangeRelation::Rename => "rename",
+                ChangeRelation::Copy => "copy",
+            };
+            header.push_str(&format!(
+                "similarity index {}%\n{verb} from {old_name}\n{verb} to {new_name}\n",
+                change.similarity
+            ));
+        }
+
+        let mut patch = FilePatch {
+            old_path: old.map(|_| old_name.to_string()),
+            new_path: new.map(|_| new_name.to_string()),
+            ..Default::default()
+        };
+
+        let old_bytes = match old {
+            Some(entry) => self.read_object(&entry.hash)?,
+            None => Vec::new(),
+        };
+        let new_bytes = match new {
+            Some(entry) => self.read_object(&entry.hash)?,
+            None => Vec::new(),
+        };
+        if old_bytes == new_bytes {
+            // Mode-only change or exact rename.
+            patch.header = header.into_bytes();
+            return Ok(patch);
+        }
+
+        let old_label = old.map_or_else(|| "/dev/null".to_string(), |_| format!("a/{}", old_name));
+        let new_label = new.map_or_else(|| "/dev/null".to_string(), |_| format!("b/{}", new_name));
+        let (Some(old_text), Some(new_text)) = (as_text(&old_bytes), as_text(&new_bytes)) else {
+            patch.binary = true;
+            patch.header = header.into_bytes();
+            let body = format!("Binary files {} and {} differ\n", old_label, new_label);
+            patch.body_bytes = body.len();
+            if patch.body_bytes <= body_limit {
+                patch.body = body.into_bytes();
+            }
+            return Ok(patch);
+        };
+
+        header.push_str(&format!("--- {}\n+++ {}\n", old_label, new_label));
+        patch.header = header.into_bytes();
+
+        let old_lines: Vec<&str> = old_text.split_inclusive('\n').collect();
+        let new_lines: Vec<&str> = new_text.split_inclusive('\n').collect();
+        let old_keys: Vec<Cow<str>> = old_lines
+            .iter()
+            .map(|line| normalize_line(line, options.whitespace))
+            .collect();
+        let new_keys: Vec<Cow<str>> = new_lines
+            .iter()
+            .map(|line| normalize_line(line, options.whitespace))
+            .collect();
+        let ops = capture_diff_slices(Algorithm::Myers, &old_keys, &new_keys);
+
+        for group in group_diff_ops(ops, options.context_lines as usize) {
+            if group.iter().all(|op| op.tag() == DiffTag::Equal) {
+                continue;
+            }
+            let mut text = format!("{}\n", UnifiedHunkHeader::new(&group));
+            for op in &group {
+                let (tag, old_range, new_range) = op.as_tag_tuple();
+                if tag == DiffTag::Equal {
+                    for line in &old_lines[old_range] {
+                        push_hunk_line(&mut text, ' ', line);
+                    }
+                    continue;
+                }
+                for line in &old_lines[old_range] {
+                    patch.deletions += 1;
+                    push_hunk_line(&mut text, '-', line);
+                }
+                for line in &new_lines[new_range] {
+                    patch.insertions += 1;
+                    push_hunk_line(&mut text, '+', line);
+                }
+            }
+            patch.body_bytes += text.len();
+            if patch.body_bytes <= body_limit {
+                patch.body.extend_from_slice(text.as_bytes());
+            }
+        }
+        Ok(patch)
+    }
+
+    fn load_manifest(&self, id: &str) -> Result<Manifest, ReviewDiffError> {
+        let path = self.manifest_path(id);
+        let bytes = std::fs::read(&path).map_err(|source| ReviewDiffError::MissingSnapshot {
+            snapshot: id.to_string(),
+            source,
+        })?;
+        serde_json::from_slice(&bytes).map_err(|err| ReviewDiffError::MissingSnapshot {
+            snapshot: id.to_string(),
+            source: err.into(),
+        })
+    }
+
+    fn write_object(&self, bytes: &[u8]) -> Result<String> {
+        let hash = hash_bytes(bytes);
+        let path = self.object_path(&hash);
+        if !path.exists() {
+            write_atomically(&path, bytes)?;
+        }
+        Ok(hash)
+    }
+
+    fn read_object(&self, hash: &str) -> std::io::Result<Vec<u8>> {
+        std::fs::read(self.object_path(hash))
+    }
+
+    fn object_path(&self, hash: &str) -> PathBuf {
+        let (prefix, rest) = hash.split_at(2);
+        self.root.join("objects").join(prefix).join(rest)
+    }
+
+    fn manifest_path(&self, id: &str) -> PathBuf {
+        self.root.join("manifests").join(format!("{}.json", id))
+    }
+}
+
+/// How an added file relates to a file of the base snapshot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ChangeRelation {
+    Rename,
+    Copy,
+}
+
+/// One entry of a snapshot diff before it is rendered.
+#[derive(Debug, Clone)]
+struct FileChange<'a> {
+    old_path: Option<&'a str>,
+    new_path: Option<&'a str>,
+    relation: Option<ChangeRelation>,
+    similarity: u32,
+}
+
+impl<'a> FileChange<'a> {
+    fn plain(old_path: Option<&'a str>, new_path: Option<&'a str>) -> Self {
+        Self {
+            old_path,
+            new_path,
+            relation: None,
+            similarity: 0,
+        }
+    }
+}
+
+impl ManifestEntry {
+    fn mode(&self) -> &'static str {
+        if self.executable { "100755" } else { "100644" }
+    }
+}
+
+/// Comparison key for a line under the given whitespace mode.
+fn normalize_line(line: &str, mode: WhitespaceMode) -> Cow<'_, str> {
+    match mode {
+        WhitespaceMode::Show => Cow::Borrowed(line),
+        WhitespaceMode::IgnoreAtEol => Cow::Borrowed(line.trim_end()),
+        WhitespaceMode::IgnoreChange => {
+            Cow::Owned(line.split_whitespace().collect::<Vec<_>>().join(" "))
+        }
+        WhitespaceMode::IgnoreAll => {
+            Cow::Owned(line.chars().filter(|c| !c.is_whitespace()).collect())
+        }
+    }
+}
+
+fn push_hunk_line(text: &mut String, prefix: char, line: &str) {
+    text.push(prefix);
+    text.push_str(line);
+    if !line.ends_with('\n') {
+        text.push_str("\n\\ No newline at end of file\n");
+    }
+}
+
+fn hash_bytes(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+/// Forward-slash path relative to `root`, or `None` for paths outside it.
+fn relative_path(root: &Path, path: &Path) -> Option<String> {
+    let relative = path.strip_prefix(root).ok()?;
+    let parts: Vec<_> = relative
+        .components()
+        .map(|component| component.as_os_str().to_str())
+        .collect::<Option<_>>()?;
+    Some(parts.join("/"))
+}
+
+fn as_text(bytes: &[u8]) -> Option<&str> {
+    let sniff = &bytes[..bytes.len().min(BINARY_SNIFF_BYTES)];
+    if sniff.contains(&0) {
+        return None;
+    }
+    std::str::from_utf8(bytes).ok()
+}
+
+/// Entries of `dir`, or none when it does not exist.
+fn dir_entries(dir: &Path) -> Result<Vec<PathBuf>> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err).with_context(|| format!("Failed to list {}", dir.display())),
+    };
+    entries
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<std::io::Result<_>>()
+        .with_context(|| format!("Failed to list {}", dir.display()))
+}
+
+fn remove_file(path: &Path) -> Result<()> {
+    match std::fs::remove_file(path) {
+        Ok(()) => Ok(()),
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(()),
+        Err(err) => Err(err).with_context(|| format!("Failed to remove {}", path.display())),
+    }
+}
+
+#[cfg(unix)]
+pub(crate) fn is_executable(metadata: &std::fs::Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.permissions().mode() & 0o111 != 0
+}
+
+#[cfg(not(unix))]
+pub(crate) fn is_executable(_metadata: &std::fs::Metadata) -> bool {
+    false
+}
+
+#[cfg(unix)]
+fn set_executable(path: &Path, executable: bool) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    let mut permissions = std::fs::metadata(path)
+        .with_context(|| format!("Failed to stat {}", path.display()))?
+        .permissions();
+    let mode = permissions.mode();
+    let updated = if executable {
+        mode | ((mode & 0o444) >> 2)
+    } else {
+        mode & !0o111
+    };
+    if updated != mode {
+        permissions.set_mode(updated);
+        std::fs::set_permissions(path, permissions)
+            .with_context(|| format!("Failed to update permissions on {}", path.display()))?;
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn set_executable(_path: &Path, _executable: bool) -> Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::review_diff::DiffChangeKind;
+    use crate::review_diff::SnapshotSource;
+    use crate::review_diff::diff_range;
+    use crate::review_diff::list_changed_files;
+
+    #[test]
+    fn captures_and_diffs_plain_directories() {
+        let workspace = tempfile::tempdir().unwrap();
+        let store_dir = tempfile::tempdir().unwrap();
+        let store = FileSnapshotStore::new(store_dir.path().to_path_buf());
+        let settings = SnapshotCaptureSettings::default();
+        let write = |path: &str, content: &str| {
+            let path = workspace.path().join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        };
+
+        write(".gitignore", "target/\n");
+        write("src/main.rs", "fn main() {\n    println!(\"one\");\n}\n");
+        write("gone.txt", "bye\n");
+        write("target/debug.log", "ignored\n");
+        let base = store.capture(workspace.path(), &settings).unwrap().0;
+        assert_eq!(store.capture(workspace.path(), &settings).unwrap().0, base);
+
+        write("src/main.rs", "fn main() {\n    println!(\"two\");\n}\n");
+        std::fs::remove_file(workspace.path().join("gone.txt")).unwrap();
+        write("new.txt", "hi");
+        write("target/debug.log", "still ignored\n");
+        let target = store.capture(workspace.path(), &settings).unwrap().0;
+
+        let source = SnapshotSource::Files(store.clone());
+        let diff = diff_range(&source, &base, &target, &ReviewDiffOptions::default()).unwrap();
+        let expected = "\
+diff --git a/gone.txt b/gone.txt
+deleted file mode 100644
+--- a/gone.txt
++++ /dev/null
+@@ -1 +0,0 @@
+-bye
+diff --git a/new.txt b/new.txt
+new file mode 100644
+--- /dev/null
++++ b/new.txt
+@@ -0,0 +1 @@
++hi
+\\ No newline at end of file
+diff --git a/src/main.rs b/src/main.rs
+--- a/src/main.rs
++++ b/src/main.rs
+@@ -1,3 +1,3 @@
+ fn main() {
+-    println!(\"one\");
++    println!(\"two\");
+ }
+";
+        assert_eq!(diff.unified_diff, expected);
+        let kinds: Vec<_> = diff.files.iter().map(|file| file.change_kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                DiffChangeKind::Deleted,
+                DiffChangeKind::Added,
+                DiffChangeKind::Modified
+            ]
+        );
+
+        store.restore(workspace.path(), &base).unwrap();
+        for path in store.added_paths(workspace.path(), &base, &target).unwrap() {
+            std::fs::remove_file(path).unwrap();
+        }
+        assert_eq!(store.capture(workspace.path(), &settings).unwrap().0, base);
+    }
+
+    #[test]
+    fn applies_diff_options_to_file_snapshots() {
+        let workspace = tempfile::tempdir().unwrap();
+        let store_dir = tempfile::tempdir().unwrap();
+        let store = FileSnapshotStore::new(store_dir.path().to_path_buf());
+        let settings = SnapshotCaptureSettings::default();
+        let write = |path: &str, content: &str| {
+            let path = workspace.path().join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        };
+        let moved: String = (1..=10).map(|n| format!("line {}\n", n)).collect();
+
+        write("old/moved.txt", &moved);
+        write("spaces.txt", "a b\nc\n");
+        let base = store.capture(workspace.path(), &settings).unwrap().0;
+
+        std::fs::remove_dir_all(workspace.path().join("old")).unwrap();
+        write("new/moved.txt", &moved.replace("line 10", "line ten"));
+        write("spaces.txt", "a   b  \nc\n");
+        let target = store.capture(workspace.path(), &settings).unwrap().0;
+
+        let source = SnapshotSource::Files(store.clone());
+        let mut options = ReviewDiffOptions {
+            context_lines: 1,
+            ..Default::default()
+        };
+        let diff = diff_range(&source, &base, &target, &options).unwrap();
+        let expected = "\
+diff --git a/old/moved.txt b/new/moved.txt
+similarity index 90%
+rename from old/moved.txt
+rename to new/moved.txt
+--- a/old/moved.txt
++++ b/new/moved.txt
+@@ -9,2 +9,2 @@
+ line 9
+-line 10
++line ten
+diff --git a/spaces.txt b/spaces.txt
+--- a/spaces.txt
++++ b/spaces.txt
+@@ -1,2 +1,2 @@
+-a b
++a   b  
+ c
+";
+        assert_eq!(diff.unified_diff, expected);
+        assert_eq!(diff.files[0].change_kind, DiffChangeKind::Renamed);
+
+        options.whitespace = WhitespaceMode::IgnoreChange;
+        options.pathspecs = vec!["*.txt".to_string()];
+        let paths: Vec<_> = list_changed_files(&source, &base, &target, &options)
+            .unwrap()
+            .into_iter()
+            .map(|file| file.display_path)
+            .collect();
+        assert_eq!(paths, vec!["new/moved.txt"]);
+
+        options.detect_renames = false;
+        options.pathspecs = vec!["old".to_string()];
+        let files = list_changed_files(&source, &base, &target, &options).unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].change_kind, DiffChangeKind::Deleted);
+    }
+
+    #[test]
+    fn garbage_collection_keeps_what_live_snapshots_reference() {
+        let workspace = tempfile::tempdir().unwrap();
+        let store_dir = tempfile::tempdir().unwrap();
+        let store = FileSnapshotStore::new(store_dir.path().to_path_buf());
+        let settings = SnapshotCaptureSettings::default();
+        let write = |path: &str, content: &str| {
+            std::fs::write(workspace.path().join(path), content).unwrap();
+        };
+
+        write("shared.txt", "shared\n");
+        write("old.txt", "old\n");
+        let old = store.capture(workspace.path(), &settings).unwrap().0;
+        std::fs::remove_file(workspace.path().join("old.txt")).unwrap();
+        write("new.txt", "new\n");
+        let new = store.capture(workspace.path(), &settings).unwrap().0;
+        let live = HashSet::from([new.clone()]);
+        let recent = Duration::from_secs(60 * 60);
+
+        assert_eq!(
+            store
+                .collect_garbage(&live, &HashSet::new(), recent)
+                .unwrap(),
+            0
+        );
+        assert!(store.read_file(&old, "old.txt").unwrap().is_some());
+
+        let discarded = HashSet::from([old.clone()]);
+        assert_eq!(store.collect_garbage(&live, &discarded, recent).unwrap(), 1);
+        assert!(store.read_file(&old, "shared.txt").is_err());
+        assert!(!store.object_path(&hash_bytes(b"old\n")).exists());
+        assert_eq!(
+            store.read_file(&new, "shared.txt").unwrap().unwrap(),
+            b"shared\n"
+        );
+        assert_eq!(store.read_file(&new, "new.txt").unwrap().unwrap(), b"new\n");
+
+        assert_eq!(
+            store
+                .collect_garbage(&HashSet::new(), &HashSet::new(), Duration::ZERO)
+                .unwrap(),
+            1
+        );
+        assert!(store.read_file(&new, "shared.txt").is_err());
+    }
+}
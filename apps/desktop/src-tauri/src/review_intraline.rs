@@ -0,0 +1,190 @@
+use std::time::Duration;
+use std::time::Instant;
+
+use similar::Algorithm;
+use similar::DiffTag;
+use similar::capture_diff_slices_deadline;
+
+use crate::review_diff::DiffHunk;
+use crate::review_diff::DiffLineKind;
+use crate::review_diff::DiffSpan;
+
+/// Lines longer than this (in bytes) are shown without intra-line spans.
+const MAX_INTRALINE_LINE_BYTES: usize = 4 * 1024;
+/// Pairs whose unchanged text is a smaller share than this are treated as
+/// rewritten lines; highlighting nearly everything adds no information.
+const MIN_INTRALINE_SIMILARITY: f32 = 0.4;
+/// Time budget for the word diff of a single line pair.
+const INTRALINE_DEADLINE: Duration = Duration::from_millis(5);
+
+/// Fill in `spans` for removed and added lines that pair up within a hunk.
+/// Each run of removals directly followed by additions is paired line by
+/// line, as the split view shows them.
+pub(crate) fn highlight_hunk(hunk: &mut DiffHunk) {
+    let mut index = 0;
+    while index < hunk.lines.len() {
+        let removals = run_of(hunk, index, DiffLineKind::Removal);
+        if removals.is_empty() {
+            index += 1;
+            continue;
+        }
+        let next = removals.last().map_or(index, |last| last + 1);
+        let additions = run_of(hunk, next, DiffLineKind::Addition);
+
+        for (&old, &new) in removals.iter().zip(&additions) {
+            if let Some((old_spans, new_spans)) =
+                line_spans(&hunk.lines[old].text, &hunk.lines[new].text)
+            {
+                hunk.lines[old].spans = old_spans;
+                hunk.lines[new].spans = new_spans;
+            }
+        }
+        index = additions
+            .last()
+            .or(removals.last())
+            .map_or(next, |last| last + 1);
+    }
+}
+
+/// Indexes of consecutive `kind` lines starting at `start`, stepping over
+/// `\ No newline at end of file` markers.
+fn run_of(hunk: &DiffHunk, start: usize, kind: DiffLineKind) -> Vec<usize> {
+    let mut run = Vec::new();
+    for (index, line) in hunk.lines.iter().enumerate().skip(start) {
+        if line.kind == kind {
+            run.push(index);
+        } else if line.kind != DiffLineKind::Metadata {
+            break;
+        }
+    }
+    run
+}
+
+/// Changed ranges of an old/new line pair in UTF-16 code units; `None` when
+/// the lines have too little in common to highlight.
+fn line_spans(old: &str, new: &str) -> Option<(Vec<DiffSpan>, Vec<DiffSpan>)> {
+    if old.len() > MAX_INTRALINE_LINE_BYTES || new.len() > MAX_INTRALINE_LINE_BYTES {
+        return None;
+    }
+
+    let old_tokens = tokenize(old);
+    let new_tokens = tokenize(new);
+    let deadline = Instant::now() + INTRALINE_DEADLINE;
+    let ops =
+        capture_diff_slices_deadline(Algorithm::Myers, &old_tokens, &new_tokens, Some(deadline));
+
+    let old_offsets = utf16_offsets(&old_tokens);
+    let new_offsets = utf16_offsets(&new_tokens);
+    let mut old_spans = Vec::new();
+    let mut new_spans = Vec::new();
+    let mut unchanged = 0;
+    for op in ops {
+        let (tag, old_range, new_range) = op.as_tag_tuple();
+        if tag == DiffTag::Equal {
+            unchanged += old_offsets[old_range.end] - old_offsets[old_range.start];
+            continue;
+        }
+        push_span(
+            &mut old_spans,
+            old_offsets[old_range.start],
+            old_offsets[old_range.end],
+        );
+        push_span(
+            &mut new_spans,
+            new_offsets[new_range.start],
+            new_offsets[new_range.end],
+        );
+    }
+
+    let total = old_offsets[old_tokens.len()] + new_offsets[new_tokens.len()];
+    if total == 0 || (2 * unchanged) as f32 / (total as f32) < MIN_INTRALINE_SIMILARITY {
+        return None;
+    }
+    Some((old_spans, new_spans))
+}
+
+/// Split a line into words, runs of whitespace and single other characters.
+fn tokenize(line: &str) -> Vec<&str> {
+    let class = |ch: char| {
+        if ch.is_alphanumeric() || ch == '_' {
+            1
+        } else if ch.is_whitespace() {
+            2
+        } else {
+            0
+        }
+    };
+
+    let mut tokens = Vec::new();
+    let mut start = 0;
+    let mut previous = None;
+    for (index, ch) in line.char_indices() {
+        let current = class(ch);
+        if index > start && (current == 0 || previous != Some(current)) {
+            tokens.push(&line[start..index]);
+            start = index;
+        }
+        previous = Some(current);
+    }
+    if start < line.len() {
+        tokens.push(&line[start..]);
+    }
+    tokens
+}
+
+/// UTF-16 offset of each token boundary, with the line length last.
+fn utf16_offsets(tokens: &[&str]) -> Vec<u32> {
+    let mut offsets = Vec::with_capacity(tokens.len() + 1);
+    let mut offset = 0;
+    offsets.push(offset);
+    for token in tokens {
+        offset += token.encode_utf16().count() as u32;
+        offsets.push(offset);
+    }
+    offsets
+}
+
+fn push_span(spans: &mut Vec<DiffSpan>, start: u32, end: u32) {
+    if start == end {
+        return;
+    }
+    match spans.last_mut() {
+        Some(last) if last.end == start => last.end = end,
+        _ => spans.push(DiffSpan { start, end }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::review_diff::parse_unified_diff;
+
+    #[test]
+    fn highlights_changed_words_in_paired_lines() {
+        let diff = "\
+diff --git a/a.rs b/a.rs
+--- a/a.rs
++++ b/a.rs
+@@ -1,3 +1,3 @@
+-let café = compute(1, \"x\");
+-fn unrelated() {}
++let café = compute(2, \"x\");
++struct Rewritten;
+ end
+";
+        let files = parse_unified_diff(diff);
+        let lines = &files[0].hunks[0].lines;
+        let spans = |index: usize| {
+            lines[index]
+                .spans
+                .iter()
+                .map(|span| (span.start, span.end))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(spans(0), vec![(19, 20)]);
+        assert_eq!(spans(2), vec![(19, 20)]);
+        assert!(lines[1].spans.is_empty());
+        assert!(lines[3].spans.is_empty());
+        assert!(lines[4].spans.is_empty());
+    }
+}
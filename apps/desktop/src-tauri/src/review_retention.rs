@@ -0,0 +1,95 @@
+use std::path::Path;
+use std::time::Duration;
+
+use git2::Oid;
+use serde::Deserialize;
+use serde::Serialize;
+use ts_rs::TS;
+
+use crate::review_diff::ReviewDiffError;
+use crate::review_diff::open_repository;
+
+/// Namespace of the refs that keep snapshot commits reachable, so `git gc`
+/// leaves them alone until the conversation is pruned.
+const SNAPSHOT_REF_PREFIX: &str = "refs/pasture/snapshots";
+
+/// Outcome of pruning the snapshots of inactive conversations.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotPruneSummary {
+    pub pruned_conversation_ids: Vec<String>,
+    /// Snapshot refs deleted from workspace repositories.
+    pub removed_refs: u32,
+}
+
+/// Age after which a conversation's snapshots are pruned.
+pub fn retention_duration(days: u32) -> Duration {
+    Duration::from_secs(u64::from(days) * 24 * 60 * 60)
+}
+
+fn conversation_ref_prefix(conversation_id: &str) -> String {
+    format!("{}/{}/", SNAPSHOT_REF_PREFIX, conversation_id)
+}
+
+/// Point a ref at a snapshot commit so it survives garbage collection.
+/// Pinning the same commit again is a no-op.
+pub fn pin_snapshot(
+    repo_path: &Path,
+    conversation_id: &str,
+    commit_id: &str,
+) -> Result<(), ReviewDiffError> {
+    let repo = open_repository(repo_path)?;
+    let oid = Oid::from_str(commit_id)?;
+    let name = format!("{}{}", conversation_ref_prefix(conversation_id), commit_id);
+    repo.reference(&name, oid, true, "pasture: pin review snapshot")?;
+    Ok(())
+}
+
+/// Delete every snapshot ref of a conversation, leaving its commits to the
+/// next `git gc`. Returns the number of refs removed.
+pub fn unpin_conversation(repo_path: &Path, conversation_id: &str) -> Result<u32, ReviewDiffError> {
+    let repo = open_repository(repo_path)?;
+    let glob = format!("{}*", conversation_ref_prefix(conversation_id));
+    let mut removed = 0;
+    for reference in repo.references_glob(&glob)? {
+        reference?.delete()?;
+        removed += 1;
+    }
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git2::Repository;
+    use git2::Signature;
+
+    #[test]
+    fn pins_and_unpins_conversation_snapshots() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let tree = repo
+            .find_tree(repo.treebuilder(None).unwrap().write().unwrap())
+            .unwrap();
+        let signature = Signature::now("Pasture", "pasture@example.com").unwrap();
+        let commit = repo
+            .commit(None, &signature, &signature, "snapshot", &tree, &[])
+            .unwrap()
+            .to_string();
+
+        pin_snapshot(dir.path(), "conv", &commit).unwrap();
+        pin_snapshot(dir.path(), "conv", &commit).unwrap();
+        pin_snapshot(dir.path(), "other", &commit).unwrap();
+        let pinned = repo
+            .find_reference(&format!("refs/pasture/snapshots/conv/{}", commit))
+            .unwrap();
+        assert_eq!(pinned.target().unwrap().to_string(), commit);
+
+        assert_eq!(unpin_conversation(dir.path(), "conv").unwrap(), 1);
+        assert_eq!(unpin_conversation(dir.path(), "conv").unwrap(), 0);
+        assert!(
+            repo.find_reference(&format!("refs/pasture/snapshots/other/{}", commit))
+                .is_ok()
+        );
+    }
+}
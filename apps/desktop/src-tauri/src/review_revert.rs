@@ -0,0 +1,503 @@
+use std::io::ErrorKind;
+use std::path::Path;
+
+use serde::Deserialize;
+use serde::Serialize;
+use ts_rs::TS;
+
+use crate::review_diff::DiffFile;
+use crate::review_diff::DiffHunk;
+use crate::review_diff::DiffLineKind;
+use crate::review_diff::ReviewDiffError;
+use crate::review_diff::ReviewDiffOptions;
+use crate::review_diff::SnapshotSource;
+use crate::review_diff::diff_selected_files;
+use crate::review_diff::read_snapshot_file;
+use crate::review_diff::snapshot_root;
+
+/// A change from a review range to undo in the working tree.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct RevertSelection {
+    /// Either side of a rename.
+    pub path: String,
+    /// Indexes into the file's `hunks` as returned by `get_turn_diff_range`;
+    /// the whole file is reverted when omitted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hunks: Option<Vec<usize>>,
+}
+
+/// A selection that could not be reverted cleanly.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct RevertConflict {
+    pub path: String,
+    pub reason: String,
+}
+
+/// Result of a revert. Nothing is written when there are conflicts.
+#[derive(Debug, Clone, Default)]
+pub struct RevertOutcome {
+    pub written_paths: Vec<String>,
+    pub conflicts: Vec<RevertConflict>,
+}
+
+/// Working tree change planned for one path.
+struct PlannedWrite {
+    path: String,
+    /// New contents, or `None` to remove the file.
+    contents: Option<Vec<u8>>,
+}
+
+enum PlanError {
+    Conflict(String),
+    Failed(ReviewDiffError),
+}
+
+impl From<ReviewDiffError> for PlanError {
+    fn from(err: ReviewDiffError) -> Self {
+        Self::Failed(err)
+    }
+}
+
+impl From<std::io::Error> for PlanError {
+    fn from(err: std::io::Error) -> Self {
+        Self::Failed(err.into())
+    }
+}
+
+/// One side of a hunk line, as it appears in the file.
+#[derive(Debug, Clone)]
+struct SideLine {
+    text: String,
+    newline: bool,
+    /// The line ends in `\r\n`. Diff text loses the terminator, so hunk
+    /// lines take it from the lines they replace or follow.
+    crlf: bool,
+}
+
+impl SideLine {
+    fn same_content(&self, other: &SideLine) -> bool {
+        self.text == other.text && self.newline == other.newline
+    }
+}
+
+/// Reverse-apply the selected changes of `base..target` to the working tree
+/// at `cwd`. Hunk indexes refer to the diff computed with `options`. Every
+/// selection is checked against the files on disk first; if any of them
+/// changed since `target`, nothing is written.
+pub fn revert_changes(
+    source: &SnapshotSource,
+    base: &str,
+    target: &str,
+    cwd: &Path,
+    selections: &[RevertSelection],
+    options: &ReviewDiffOptions,
+) -> Result<RevertOutcome, ReviewDiffError> {
+    let root = snapshot_root(source, cwd)?;
+    let paths: Vec<String> = selections
+        .iter()
+        .map(|selection| selection.path.clone())
+        .collect();
+    let files = diff_selected_files(source, base, target, &paths, true, options)?;
+
+    let mut outcome = RevertOutcome::default();
+    let mut writes: Vec<PlannedWrite> = Vec::new();
+    for selection in selections {
+        let file = files.iter().find(|file| {
+            file.old_path.as_deref() == Some(selection.path.as_str())
+                || file.new_path.as_deref() == Some(selection.path.as_str())
+        });
+        let planned = match file {
+            None => Err(PlanError::Conflict(
+                "not changed in the selected range".to_string(),
+            )),
+            Some(file) => match &selection.hunks {
+                None => plan_file_revert(source, base, target, &root, file),
+                Some(hunks) => plan_hunk_revert(&root, file, hunks),
+            },
+        };
+
+        match planned {
+            Ok(planned) => {
+                for write in planned {
+                    if writes.iter().any(|existing| existing.path == write.path) {
+                        outcome.conflicts.push(RevertConflict {
+                            path: write.path,
+                            reason: "selected more than once".to_string(),
+                        });
+                    } else {
+                        writes.push(write);
+                    }
+                }
+            }
+            Err(PlanError::Conflict(reason)) => outcome.conflicts.push(RevertConflict {
+                path: selection.path.clone(),
+                reason,
+            }),
+            Err(PlanError::Failed(err)) => return Err(err),
+        }
+    }
+
+    if !outcome.conflicts.is_empty() {
+        return Ok(outcome);
+    }
+
+    for write in writes {
+        let path = root.join(&write.path);
+        match write.contents {
+            Some(contents) => {
+                if let Some(parent) = path.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                std::fs::write(&path, contents)?;
+            }
+            None => match std::fs::remove_file(&path) {
+                Ok(()) => {}
+                Err(err) if err.kind() == ErrorKind::NotFound => {}
+                Err(err) => return Err(err.into()),
+            },
+        }
+        outcome.written_paths.push(write.path);
+    }
+    Ok(outcome)
+}
+
+/// Put the file back the way it was in `base`, undoing adds, deletes and
+/// renames. The file on disk must still match `target`.
+fn plan_file_revert(
+    source: &SnapshotSource,
+    base: &str,
+    target: &str,
+    root: &Path,
+    file: &DiffFile,
+) -> Result<Vec<PlannedWrite>, PlanError> {
+    if let Some(new_path) = &file.new_path {
+        let expected = read_snapshot_file(source, target, new_path)?;
+        if read_current(&root.join(new_path))? != expected {
+            return Err(PlanError::Conflict(format!(
+                "{} changed since the selected turn",
+                new_path
+            )));
+        }
+    }
+
+    let mut writes = Vec::new();
+    match (&file.old_path, &file.new_path) {
+        (Some(old_path), new_path) => {
+            if new_path.as_ref() != Some(old_path) {
+                if read_current(&root.join(old_path))?.is_some() {
+                    return Err(PlanError::Conflict(format!(
+                        "{} already exists in the working tree",
+                        old_path
+                    )));
+                }
+                if let Some(new_path) = new_path {
+                    writes.push(PlannedWrite {
+                        path: new_path.clone(),
+                        contents: None,
+                    });
+                }
+            }
+            writes.push(PlannedWrite {
+                path: old_path.clone(),
+                contents: read_snapshot_file(source, base, old_path)?,
+            });
+        }
+        (None, Some(new_path)) => writes.push(PlannedWrite {
+            path: new_path.clone(),
+            contents: None,
+        }),
+        (None, None) => {}
+    }
+    Ok(writes)
+}
+
+/// Reverse-apply individual hunks to the file as it is on disk. Hunks that
+/// moved because of unrelated edits are found again by their content.
+fn plan_hunk_revert(
+    root: &Path,
+    file: &DiffFile,
+    hunk_indexes: &[usize],
+) -> Result<Vec<PlannedWrite>, PlanError> {
+    if file.binary {
+        return Err(PlanError::Conflict(
+            "binary files can only be reverted as a whole".to_string(),
+        ));
+    }
+    let Some(path) = &file.new_path else {
+        return Err(PlanError::Conflict(
+            "deleted files can only be reverted as a whole".to_string(),
+        ));
+    };
+
+    let mut hunks: Vec<&DiffHunk> = Vec::with_capacity(hunk_indexes.len());
+    for index in hunk_indexes {
+        let hunk = file
+            .hunks
+            .get(*index)
+            .ok_or_else(|| PlanError::Conflict(format!("hunk {} does not exist", index)))?;
+        hunks.push(hunk);
+    }
+    // Bottom-up, so earlier hunks keep their line numbers.
+    hunks.sort_by_key(|hunk| std::cmp::Reverse(hunk.new_start));
+    hunks.dedup_by_key(|hunk| hunk.new_start);
+
+    let current = read_current(&root.join(path))?
+        .ok_or_else(|| PlanError::Conflict(format!("{} no longer exists", path)))?;
+    let current = String::from_utf8(current)
+        .map_err(|_| PlanError::Conflict(format!("{} is no longer a text file", path)))?;
+    let mut lines: Vec<SideLine> = current
+        .split_inclusive('\n')
+        .map(|line| match line.strip_suffix('\n') {
+            Some(text) => SideLine {
+                text: text.strip_suffix('\r').unwrap_or(text).to_string(),
+                newline: true,
+                crlf: text.ends_with('\r'),
+            },
+            None => SideLine {
+                text: line.to_string(),
+                newline: false,
+                crlf: false,
+            },
+        })
+        .collect();
+
+    for hunk in hunks {
+        let (mut old_side, new_side, context_of) = hunk_sides(hunk);
+        // Hunks that only remove lines report the line before the gap.
+        let expected = if hunk.new_lines == 0 {
+            hunk.new_start as usize
+        } else {
+            hunk.new_start.saturating_sub(1) as usize
+        };
+        let at = locate(&lines, &new_side, expected).ok_or_else(|| {
+            PlanError::Conflict(format!(
+                "{} changed since the selected turn near line {}",
+                path, hunk.new_start
+            ))
+        })?;
+        // Context lines keep their own terminator; restored lines follow
+        // the line before them.
+        let mut previous_crlf = lines
+            .get(at.saturating_sub(1))
+            .is_some_and(|line| line.crlf);
+        for (line, context) in old_side.iter_mut().zip(context_of) {
+            line.crlf = match context {
+                Some(index) => lines[at + index].crlf,
+                None => previous_crlf,
+            };
+            previous_crlf = line.crlf;
+        }
+        lines.splice(at..at + new_side.len(), old_side);
+    }
+
+    let mut contents = String::with_capacity(current.len());
+    for line in &lines {
+        contents.push_str(&line.text);
+        if line.newline {
+            contents.push_str(if line.crlf { "\r\n" } else { "\n" });
+        }
+    }
+    Ok(vec![PlannedWrite {
+        path: path.clone(),
+        contents: Some(contents.into_bytes()),
+    }])
+}
+
+/// Split a hunk into the lines it expects before and after the change,
+/// plus, for each old-side line, its position on the new side if it is
+/// a context line.
+fn hunk_sides(hunk: &DiffHunk) -> (Vec<SideLine>, Vec<SideLine>, Vec<Option<usize>>) {
+    let mut old_side = Vec::new();
+    let mut new_side = Vec::new();
+    let mut context_of = Vec::new();
+    let mut previous = None;
+    for line in &hunk.lines {
+        let side_line = SideLine {
+            text: line.text.clone(),
+            newline: true,
+            crlf: false,
+        };
+        match line.kind {
+            DiffLineKind::Context => {
+                context_of.push(Some(new_side.len()));
+                old_side.push(side_line.clone());
+                new_side.push(side_line);
+            }
+            DiffLineKind::Removal => {
+                context_of.push(None);
+                old_side.push(side_line);
+            }
+            DiffLineKind::Addition => new_side.push(side_line),
+            // `\ No newline at end of file` applies to the line before it.
+            DiffLineKind::Metadata => {
+                let sides = match previous {
+                    Some(DiffLineKind::Context) => [Some(&mut old_side), Some(&mut new_side)],
+                    Some(DiffLineKind::Removal) => [Some(&mut old_side), None],
+                    Some(DiffLineKind::Addition) => [None, Some(&mut new_side)],
+                    _ => [None, None],
+                };
+                for side in sides.into_iter().flatten() {
+                    if let Some(last) = side.last_mut() {
+                        last.newline = false;
+                    }
+                }
+            }
+        }
+        previous = Some(line.kind);
+    }
+    (old_side, new_side, context_of)
+}
+
+/// Position of `needle` in `lines`, preferring the match closest to
+/// `expected`.
+fn locate(lines: &[SideLine], needle: &[SideLine], expected: usize) -> Option<usize> {
+    let last_start = lines.len().checked_sub(needle.len())?;
+    let matches_at = |start: usize| {
+        lines[start..start + needle.len()]
+            .iter()
+            .zip(needle)
+            .all(|(line, expected)| line.same_content(expected))
+    };
+    let expected = expected.min(last_start);
+    (0..=last_start)
+        .flat_map(|distance| {
+            let before = expected.checked_sub(distance);
+            let after = Some(expected + distance).filter(|start| *start <= last_start);
+            [before, after]
+        })
+        .flatten()
+        .find(|start| matches_at(*start))
+}
+
+fn read_current(path: &Path) -> std::io::Result<Option<Vec<u8>>> {
+    match std::fs::read(path) {
+        Ok(bytes) => Ok(Some(bytes)),
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::review_capture::SnapshotCaptureSettings;
+    use crate::review_file_snapshots::FileSnapshotStore;
+
+    #[test]
+    fn reverts_selected_hunks_and_reports_conflicts() {
+        let workspace = tempfile::tempdir().unwrap();
+        let store_dir = tempfile::tempdir().unwrap();
+        let store = FileSnapshotStore::new(store_dir.path().to_path_buf());
+        let source = SnapshotSource::Files(store.clone());
+        let file = workspace.path().join("notes.txt");
+        let lines: Vec<String> = (1..=20).map(|n| format!("line {}", n)).collect();
+
+        std::fs::write(&file, format!("{}\n", lines.join("\n"))).unwrap();
+        std::fs::write(workspace.path().join("extra.txt"), "keep\n").unwrap();
+        let base = store
+            .capture(workspace.path(), &SnapshotCaptureSettings::default())
+            .unwrap()
+            .0;
+
+        let mut edited = lines.clone();
+        edited[1] = "line two".to_string();
+        edited[17] = "line eighteen".to_string();
+        std::fs::write(&file, format!("{}\n", edited.join("\n"))).unwrap();
+        std::fs::remove_file(workspace.path().join("extra.txt")).unwrap();
+        let target = store
+            .capture(workspace.path(), &SnapshotCaptureSettings::default())
+            .unwrap()
+            .0;
+
+        // An unrelated edit above the second hunk shifts it down.
+        edited.insert(8, "inserted".to_string());
+        std::fs::write(&file, format!("{}\n", edited.join("\n"))).unwrap();
+
+        let selections = vec![
+            RevertSelection {
+                path: "notes.txt".to_string(),
+                hunks: Some(vec![1]),
+            },
+            RevertSelection {
+                path: "extra.txt".to_string(),
+                hunks: None,
+            },
+        ];
+        let outcome = revert_changes(
+            &source,
+            &base,
+            &target,
+            workspace.path(),
+            &selections,
+            &ReviewDiffOptions::default(),
+        )
+        .unwrap();
+        assert!(outcome.conflicts.is_empty());
+        assert_eq!(outcome.written_paths, vec!["notes.txt", "extra.txt"]);
+
+        let reverted = std::fs::read_to_string(&file).unwrap();
+        assert!(reverted.contains("line two\n"));
+        assert!(reverted.contains("inserted\n"));
+        assert!(reverted.contains("line 18\n"));
+        assert_eq!(
+            std::fs::read_to_string(workspace.path().join("extra.txt")).unwrap(),
+            "keep\n"
+        );
+
+        let whole_file = vec![RevertSelection {
+            path: "notes.txt".to_string(),
+            hunks: None,
+        }];
+        let outcome = revert_changes(
+            &source,
+            &base,
+            &target,
+            workspace.path(),
+            &whole_file,
+            &ReviewDiffOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(outcome.conflicts.len(), 1);
+        assert!(outcome.written_paths.is_empty());
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), reverted);
+    }
+
+    #[test]
+    fn hunk_revert_keeps_each_line_ending() {
+        let workspace = tempfile::tempdir().unwrap();
+        let store_dir = tempfile::tempdir().unwrap();
+        let store = FileSnapshotStore::new(store_dir.path().to_path_buf());
+        let source = SnapshotSource::Files(store.clone());
+        let file = workspace.path().join("mixed.txt");
+        let original = "one\r\ntwo\nthree\r\nfour\r\nfive\nsix\r\nseven\n";
+
+        std::fs::write(&file, original).unwrap();
+        let base = store
+            .capture(workspace.path(), &SnapshotCaptureSettings::default())
+            .unwrap()
+            .0;
+        std::fs::write(&file, original.replace("four", "FOUR")).unwrap();
+        let target = store
+            .capture(workspace.path(), &SnapshotCaptureSettings::default())
+            .unwrap()
+            .0;
+
+        let selections = vec![RevertSelection {
+            path: "mixed.txt".to_string(),
+            hunks: Some(vec![0]),
+        }];
+        let outcome = revert_changes(
+            &source,
+            &base,
+            &target,
+            workspace.path(),
+            &selections,
+            &ReviewDiffOptions::default(),
+        )
+        .unwrap();
+        assert!(outcome.conflicts.is_empty());
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), original);
+    }
+}
@@ -3,54 +3,451 @@ use std::collections::HashSet;
 use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 
 use anyhow::Context;
 use anyhow::Result;
 use anyhow::anyhow;
+use chrono::DateTime;
+use chrono::Utc;
 use codex_git::CreateGhostCommitOptions;
+use codex_git::GitToolingError;
 use codex_git::create_ghost_commit;
+use codex_git::restore_to_commit;
+use serde::Deserialize;
+use serde::Serialize;
+use tokio::fs;
 use tokio::sync::Mutex;
+use tokio::sync::MutexGuard;
+use tokio::sync::RwLock;
+use ts_rs::TS;
+use uuid::Uuid;
+
+use crate::atomic_file::write_atomically;
+use crate::review_capture::SnapshotCaptureReport;
+use crate::review_capture::SnapshotCaptureSettings;
+use crate::review_capture::SnapshotLimitWarning;
+use crate::review_capture::capture_git_snapshot;
+use crate::review_diff::DiffFileSummary;
+use crate::review_diff::ReviewDiffError;
+use crate::review_diff::ReviewDiffOptions;
+use crate::review_diff::SnapshotSource;
+use crate::review_diff::added_paths;
+use crate::review_diff::list_changed_files;
+use crate::review_diff::resolve_revision;
+use crate::review_diff::snapshot_root;
+use crate::review_file_snapshots::FileSnapshotStore;
+use crate::review_retention::pin_snapshot;
+use crate::review_retention::unpin_conversation;
+
+/// Attempts made for a snapshot before a transient failure is reported.
+const SNAPSHOT_ATTEMPTS: u32 = 3;
+/// Delay before the first retry; doubled for each further attempt.
+const SNAPSHOT_RETRY_DELAY: Duration = Duration::from_millis(250);
+
+/// Prompts stored with turn snapshots are cut to this many characters.
+const PROMPT_PREVIEW_MAX_LEN: usize = 200;
+
+/// Error text that points at contention rather than a broken workspace.
+const TRANSIENT_ERROR_MARKERS: &[&str] = &[
+    "index.lock",
+    "another git process",
+    "resource busy",
+    "text file busy",
+    "being used by another process",
+    "resource temporarily unavailable",
+];
+
+type WarningCallback = dyn Fn(&str, SnapshotLimitWarning) + Send + Sync;
+
+/// Receives capture limit warnings along with the conversation they came from.
+#[derive(Clone)]
+pub struct SnapshotWarningSink(Arc<WarningCallback>);
+
+impl SnapshotWarningSink {
+    pub fn new(emit: impl Fn(&str, SnapshotLimitWarning) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(emit))
+    }
+
+    fn emit(&self, conversation_id: &str, warning: SnapshotLimitWarning) {
+        (self.0)(conversation_id, warning)
+    }
+}
+
+impl std::fmt::Debug for SnapshotWarningSink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SnapshotWarningSink")
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct ReviewSnapshots {
+    conversation_id: String,
     inner: Arc<Mutex<ConversationSnapshotState>>,
+    store_path: PathBuf,
+    file_store: FileSnapshotStore,
+    /// Serializes writes so the file on disk always reflects the latest state.
+    persist_lock: Arc<Mutex<()>>,
+    /// Shared by every conversation; pruning holds it for writing so no
+    /// store is read while it is being deleted.
+    store_lock: Arc<RwLock<()>>,
+}
+
+/// Storage used for a conversation's snapshots.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SnapshotBackend {
+    /// Ghost commits in the workspace repository.
+    #[default]
+    Git,
+    /// Content-addressed copies, used when the workspace is not a git repository.
+    Files,
 }
 
 #[derive(Debug)]
 struct ConversationSnapshotState {
     cwd: PathBuf,
-    base_commit: Option<String>,
+    backend: SnapshotBackend,
+    base_snapshot: Option<SnapshotRecord>,
     disabled: bool,
+    /// Most recent capture failure, cleared by the next successful capture.
+    last_error: Option<String>,
     capturing_base: bool,
     inflight_turns: HashSet<String>,
-    turn_commits: HashMap<String, String>,
+    turn_snapshots: HashMap<String, SnapshotRecord>,
+    safety_snapshots: Vec<SafetySnapshotRecord>,
+    /// User messages seen so far; the current value numbers the next snapshot.
+    user_turns: u32,
+    last_prompt: Option<String>,
+    capture_settings: SnapshotCaptureSettings,
+    warning_sink: Option<SnapshotWarningSink>,
+    /// Whether the persisted history has been read.
+    loaded: bool,
 }
 
 impl ConversationSnapshotState {
     fn new(cwd: PathBuf) -> Self {
         Self {
             cwd,
-            base_commit: None,
+            backend: SnapshotBackend::Git,
+            base_snapshot: None,
             disabled: false,
+            last_error: None,
             capturing_base: false,
             inflight_turns: HashSet::new(),
-            turn_commits: HashMap::new(),
+            turn_snapshots: HashMap::new(),
+            safety_snapshots: Vec::new(),
+            user_turns: 0,
+            last_prompt: None,
+            capture_settings: SnapshotCaptureSettings::default(),
+            warning_sink: None,
+            loaded: false,
+        }
+    }
+
+    fn restore(&mut self, persisted: PersistedSnapshotState) {
+        self.backend = persisted.backend;
+        self.base_snapshot = persisted.base;
+        self.turn_snapshots = persisted.turns;
+        self.safety_snapshots = persisted.safety;
+    }
+
+    fn forget_history(&mut self) {
+        self.backend = SnapshotBackend::Git;
+        self.base_snapshot = None;
+        self.turn_snapshots.clear();
+        self.safety_snapshots.clear();
+        self.loaded = true;
+    }
+
+    fn to_persisted(&self) -> PersistedSnapshotState {
+        PersistedSnapshotState {
+            backend: self.backend,
+            base: self.base_snapshot.clone(),
+            turns: self.turn_snapshots.clone(),
+            safety: self.safety_snapshots.clone(),
+            cwd: Some(self.cwd.clone()),
         }
     }
+
+    /// Remember a capture failure; only permanent ones turn snapshotting off.
+    fn record_failure(&mut self, err: &anyhow::Error) {
+        if classify_snapshot_error(err) == SnapshotFailure::Permanent {
+            self.disabled = true;
+        }
+        self.last_error = Some(format!("{:#}", err));
+    }
+
+    /// Turn snapshots ordered by capture time.
+    fn ordered_turns(&self) -> Vec<TurnSnapshotSummary> {
+        let mut records: Vec<_> = self.turn_snapshots.iter().collect();
+        records.sort_by_key(|(_, record)| record.captured_at);
+        records
+            .into_iter()
+            .enumerate()
+            .map(|(position, (event_id, record))| TurnSnapshotSummary {
+                event_id: event_id.clone(),
+                commit_id: record.commit_id.clone(),
+                // Snapshots from before turn tracking fall back to their position.
+                turn_index: record.turn_index.unwrap_or(position as u32 + 1),
+                captured_at: record.captured_at,
+                prompt: record.prompt.clone(),
+                stats: record.stats,
+                origin: record.origin,
+            })
+            .collect()
+    }
+
+    /// Most recent turn snapshot, or the base when no turn was captured yet.
+    fn latest_commit(&self) -> Option<String> {
+        self.turn_snapshots
+            .values()
+            .max_by_key(|record| record.captured_at)
+            .or(self.base_snapshot.as_ref())
+            .map(|record| record.commit_id.clone())
+    }
+
+    fn commit_for(&self, event_id: Option<&str>) -> Option<String> {
+        self.record_for(event_id)
+            .map(|record| record.commit_id.clone())
+    }
+
+    fn record_for(&self, event_id: Option<&str>) -> Option<&SnapshotRecord> {
+        match event_id {
+            Some(event_id) => self.turn_snapshots.get(event_id),
+            None => self.base_snapshot.as_ref(),
+        }
+    }
+}
+
+/// One side of a review comparison.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapshotRef<'a> {
+    /// The snapshot captured when the conversation started.
+    Base,
+    /// The snapshot recorded for the turn with this event id.
+    Turn(&'a str),
+    /// Any git revision: `HEAD`, a branch, a tag or a commit SHA.
+    Git(&'a str),
+}
+
+impl<'a> SnapshotRef<'a> {
+    /// Pick the side named by a request; neither means the conversation
+    /// base. Callers reject requests that set both.
+    pub fn from_parts(event_id: Option<&'a str>, git_ref: Option<&'a str>) -> Self {
+        match (git_ref, event_id) {
+            (Some(git_ref), _) => SnapshotRef::Git(git_ref),
+            (None, Some(event_id)) => SnapshotRef::Turn(event_id),
+            (None, None) => SnapshotRef::Base,
+        }
+    }
+}
+
+/// A review side that is either already known or still a git revision.
+enum PendingCommit {
+    Known(Option<String>),
+    Revision(String),
+}
+
+/// Ghost commit captured for the conversation base or a single turn.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SnapshotRecord {
+    commit_id: String,
+    captured_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    turn_index: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    prompt: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    stats: Option<TurnChangeStats>,
+    #[serde(default)]
+    origin: SnapshotOrigin,
+    /// Files the capture left out or kept at their committed version; a
+    /// restore leaves them as they are on disk.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    skipped_paths: Vec<String>,
+}
+
+impl SnapshotRecord {
+    fn new(commit_id: String, report: &SnapshotCaptureReport) -> Self {
+        Self {
+            commit_id,
+            captured_at: Utc::now(),
+            turn_index: None,
+            prompt: None,
+            stats: None,
+            origin: SnapshotOrigin::Turn,
+            skipped_paths: report.skipped_paths(),
+        }
+    }
+}
+
+/// What produced a snapshot in the turn timeline.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "kebab-case")]
+pub enum SnapshotOrigin {
+    /// Captured when an agent turn finished.
+    #[default]
+    Turn,
+    /// Captured after changes were reverted from the review.
+    Revert,
+    /// Captured when a turn starts, holding edits made outside the agent
+    /// since the previous snapshot.
+    External,
+}
+
+/// Size of the change a turn made relative to the snapshot before it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct TurnChangeStats {
+    pub files_changed: u32,
+    pub insertions: u32,
+    pub deletions: u32,
+}
+
+impl TurnChangeStats {
+    fn from_files(files: &[DiffFileSummary]) -> Self {
+        Self {
+            files_changed: files.len() as u32,
+            insertions: files.iter().map(|file| file.insertions).sum(),
+            deletions: files.iter().map(|file| file.deletions).sum(),
+        }
+    }
+}
+
+/// Workspace state captured right before a restore overwrote it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SafetySnapshotRecord {
+    commit_id: String,
+    captured_at: DateTime<Utc>,
+    restored_commit_id: String,
+}
+
+/// On-disk representation of a conversation's snapshot history.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PersistedSnapshotState {
+    #[serde(default)]
+    backend: SnapshotBackend,
+    #[serde(default)]
+    base: Option<SnapshotRecord>,
+    #[serde(default)]
+    turns: HashMap<String, SnapshotRecord>,
+    #[serde(default)]
+    safety: Vec<SafetySnapshotRecord>,
+    /// Workspace the snapshots were taken in; missing in older stores.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    cwd: Option<PathBuf>,
 }
 
 #[derive(Debug, Clone)]
 pub struct ConversationSnapshotSummary {
     pub disabled: bool,
+    pub last_error: Option<String>,
     pub base_commit: Option<String>,
-    pub turn_commits: Vec<(String, String)>,
+    /// Turn snapshots ordered by capture time.
+    pub turns: Vec<TurnSnapshotSummary>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TurnSnapshotSummary {
+    pub event_id: String,
+    pub commit_id: String,
+    /// 1-based user turn that produced the snapshot.
+    pub turn_index: u32,
+    pub captured_at: DateTime<Utc>,
+    pub prompt: Option<String>,
+    pub stats: Option<TurnChangeStats>,
+    pub origin: SnapshotOrigin,
+}
+
+/// Snapshots to replay as commits when exporting a range.
+#[derive(Debug, Clone)]
+pub struct SnapshotExportRange {
+    pub repo_path: PathBuf,
+    pub base_commit: String,
+    /// Non-empty, in capture order.
+    pub turns: Vec<TurnSnapshotSummary>,
+}
+
+/// The base snapshot and the turn snapshots that followed it, for walking a
+/// file's history.
+#[derive(Debug, Clone)]
+pub struct SnapshotHistory {
+    pub source: SnapshotSource,
+    pub base_commit: String,
+    /// In capture order.
+    pub turns: Vec<TurnSnapshotSummary>,
+}
+
+/// Outcome of rolling the workspace back to a recorded snapshot.
+#[derive(Debug, Clone)]
+pub struct RestoredSnapshot {
+    pub restored_commit: String,
+    pub safety_commit: String,
 }
 
 impl ReviewSnapshots {
-    pub fn new(cwd: PathBuf) -> Self {
+    /// Create a snapshot store backed by `store_path`. History captured by a
+    /// previous session is read on first access, under `store_lock`.
+    /// `file_store` holds snapshots for workspaces outside git.
+    pub fn new(
+        conversation_id: String,
+        cwd: PathBuf,
+        store_path: PathBuf,
+        file_store: FileSnapshotStore,
+        store_lock: Arc<RwLock<()>>,
+    ) -> Self {
         Self {
+            conversation_id,
             inner: Arc::new(Mutex::new(ConversationSnapshotState::new(cwd))),
+            store_path,
+            file_store,
+            persist_lock: Arc::new(Mutex::new(())),
+            store_lock,
+        }
+    }
+
+    /// Lock the snapshot state, reading the persisted history first if this
+    /// is the first access.
+    async fn state(&self) -> MutexGuard<'_, ConversationSnapshotState> {
+        let guard = self.inner.lock().await;
+        if guard.loaded {
+            return guard;
+        }
+        drop(guard);
+
+        let _store_guard = self.store_lock.read().await;
+        let mut guard = self.inner.lock().await;
+        if !guard.loaded {
+            match read_persisted_store(&self.store_path).await {
+                Ok(persisted) => guard.restore(persisted),
+                Err(err)
+                    if err
+                        .downcast_ref::<std::io::Error>()
+                        .is_some_and(|err| err.kind() == std::io::ErrorKind::NotFound) => {}
+                Err(err) => log::warn!(
+                    "Ignoring review snapshot store {}: {:#}",
+                    self.store_path.display(),
+                    err
+                ),
+            }
+            guard.loaded = true;
         }
+        guard
+    }
+
+    pub async fn set_capture_settings(&self, settings: SnapshotCaptureSettings) {
+        let mut guard = self.inner.lock().await;
+        guard.capture_settings = settings;
+    }
+
+    /// Report limit warnings to `sink` as soon as a capture raises them.
+    pub async fn set_warning_sink(&self, sink: SnapshotWarningSink) {
+        let mut guard = self.inner.lock().await;
+        guard.warning_sink = Some(sink);
     }
 
     pub async fn update_cwd(&self, cwd: &Path) {
@@ -58,15 +455,21 @@ impl ReviewSnapshots {
         guard.cwd = cwd.to_path_buf();
     }
 
+    /// Drop the history held in memory after its store was discarded.
+    pub async fn forget_history(&self) {
+        let mut guard = self.inner.lock().await;
+        guard.forget_history();
+    }
+
     pub async fn ensure_base(&self) -> Result<()> {
         let snapshot_cwd = {
-            let mut guard = self.inner.lock().await;
+            let mut guard = self.state().await;
 
             if guard.disabled {
                 return Err(anyhow!("Snapshotting disabled for conversation"));
             }
 
-            if guard.base_commit.is_some() || guard.capturing_base {
+            if guard.base_snapshot.is_some() || guard.capturing_base {
                 return Ok(());
             }
 
@@ -74,32 +477,80 @@ impl ReviewSnapshots {
             guard.cwd.clone()
         };
 
-        let snapshot_result = Self::create_snapshot(snapshot_cwd).await;
+        let snapshot_result = self.create_snapshot(snapshot_cwd).await;
 
-        let mut guard = self.inner.lock().await;
+        let mut guard = self.state().await;
         guard.capturing_base = false;
 
         match snapshot_result {
-            Ok(commit_id) => {
-                guard.base_commit.get_or_insert(commit_id);
+            Ok((commit_id, report)) => {
+                guard.last_error = None;
+                guard
+                    .base_snapshot
+                    .get_or_insert_with(|| SnapshotRecord::new(commit_id, &report));
+                drop(guard);
+                self.persist_logged().await;
                 Ok(())
             }
             Err(err) => {
-                guard.disabled = true;
+                guard.record_failure(&err);
                 Err(err)
             }
         }
     }
 
     pub async fn record_turn_snapshot(&self, event_id: &str) -> Result<Option<String>> {
+        self.record_snapshot(event_id, SnapshotOrigin::Turn, None)
+            .await
+    }
+
+    /// Capture the workspace after changes were reverted from the review, so
+    /// later ranges diff against what is on disk. Returns the new snapshot's
+    /// event id and commit.
+    pub async fn record_revert_snapshot(
+        &self,
+        description: &str,
+    ) -> Result<Option<(String, String)>> {
+        let event_id = format!("revert-{}", Uuid::new_v4());
+        let commit_id = self
+            .record_snapshot(
+                &event_id,
+                SnapshotOrigin::Revert,
+                Some(prompt_preview(description)),
+            )
+            .await?;
+        Ok(commit_id.map(|commit_id| (event_id, commit_id)))
+    }
+
+    /// Capture edits made outside the agent right before a new turn starts,
+    /// so they are not attributed to that turn. Nothing is recorded when the
+    /// workspace did not change since the last snapshot.
+    pub async fn record_external_changes(&self) -> Result<Option<String>> {
+        let event_id = format!("external-{}", Uuid::new_v4());
+        self.record_snapshot(
+            &event_id,
+            SnapshotOrigin::External,
+            Some("Changes made outside the agent".to_string()),
+        )
+        .await
+    }
+
+    /// Capture and record a snapshot under `event_id`. Turn snapshots are
+    /// labelled with the latest user prompt unless `label` is given.
+    async fn record_snapshot(
+        &self,
+        event_id: &str,
+        origin: SnapshotOrigin,
+        label: Option<String>,
+    ) -> Result<Option<String>> {
         let snapshot_cwd = {
-            let mut guard = self.inner.lock().await;
+            let mut guard = self.state().await;
 
             if guard.disabled {
                 return Ok(None);
             }
 
-            if guard.base_commit.is_none() {
+            if guard.base_snapshot.is_none() {
                 return Err(anyhow!("Base snapshot unavailable for conversation"));
             }
 
@@ -110,75 +561,1173 @@ impl ReviewSnapshots {
             guard.cwd.clone()
         };
 
-        let snapshot_result = Self::create_snapshot(snapshot_cwd).await;
+        let snapshot_result = self.create_snapshot(snapshot_cwd).await;
 
-        let mut guard = self.inner.lock().await;
-        guard.inflight_turns.remove(event_id);
+        let (mut record, previous, source) = {
+            let mut guard = self.state().await;
+            let (commit_id, report) = match snapshot_result {
+                Ok(captured) => captured,
+                Err(err) => {
+                    guard.inflight_turns.remove(event_id);
+                    guard.record_failure(&err);
+                    return Err(err);
+                }
+            };
+            guard.last_error = None;
 
-        match snapshot_result {
-            Ok(commit_id) => {
-                guard
-                    .turn_commits
-                    .insert(event_id.to_string(), commit_id.clone());
-                Ok(Some(commit_id))
-            }
-            Err(err) => {
-                guard.disabled = true;
-                Err(err)
+            let mut record = SnapshotRecord::new(commit_id, &report);
+            record.origin = origin;
+            record.turn_index = (guard.user_turns > 0).then_some(guard.user_turns);
+            record.prompt = label.or_else(|| guard.last_prompt.clone());
+            (record, guard.latest_commit(), self.snapshot_source(&guard))
+        };
+
+        if let Some(previous) = previous {
+            record.stats = change_stats(source, previous, record.commit_id.clone()).await;
+        }
+
+        let unchanged = record.stats.is_some_and(|stats| stats.files_changed == 0);
+        if origin == SnapshotOrigin::External && unchanged {
+            self.state().await.inflight_turns.remove(event_id);
+            return Ok(None);
+        }
+
+        let commit_id = record.commit_id.clone();
+        {
+            let mut guard = self.state().await;
+            guard.inflight_turns.remove(event_id);
+            guard.turn_snapshots.insert(event_id.to_string(), record);
+        }
+        self.persist_logged().await;
+        Ok(Some(commit_id))
+    }
+
+    /// Note a user message so the next turn snapshot can be labelled with it.
+    pub async fn record_user_prompt(&self, message: &str) {
+        let mut guard = self.state().await;
+        guard.user_turns += 1;
+        guard.last_prompt = Some(prompt_preview(message));
+    }
+
+    /// Align the user turn counter with history replayed from a rollout.
+    pub async fn sync_user_turns(&self, user_turns: u32, last_prompt: Option<&str>) {
+        let mut guard = self.state().await;
+        guard.user_turns = user_turns;
+        guard.last_prompt = last_prompt.map(prompt_preview);
+    }
+
+    /// Start the history of a fork from `source`: its base and the snapshots
+    /// of its first `keep_turns` user turns. Git snapshots are pinned again
+    /// under this conversation so pruning the source leaves them reachable.
+    pub async fn inherit_history(&self, source: &ReviewSnapshots, keep_turns: u32) {
+        let (backend, cwd, base, turns) = {
+            let guard = source.state().await;
+            let Some(base) = guard.base_snapshot.clone() else {
+                return;
+            };
+            let kept: HashSet<String> = guard
+                .ordered_turns()
+                .into_iter()
+                .filter(|turn| turn.turn_index <= keep_turns)
+                .map(|turn| turn.event_id)
+                .collect();
+            let turns: HashMap<String, SnapshotRecord> = guard
+                .turn_snapshots
+                .iter()
+                .filter(|(event_id, _)| kept.contains(*event_id))
+                .map(|(event_id, record)| (event_id.clone(), record.clone()))
+                .collect();
+            (guard.backend, guard.cwd.clone(), base, turns)
+        };
+
+        if backend == SnapshotBackend::Git {
+            let commits: Vec<String> = std::iter::once(&base)
+                .chain(turns.values())
+                .map(|record| record.commit_id.clone())
+                .collect();
+            let conversation_id = self.conversation_id.clone();
+            let pinned = tokio::task::spawn_blocking(move || {
+                for commit_id in commits {
+                    // An unpinned snapshot still works until `git gc` collects it.
+                    if let Err(err) = pin_snapshot(&cwd, &conversation_id, &commit_id) {
+                        log::warn!("Failed to pin snapshot {}: {}", commit_id, err);
+                    }
+                }
+            })
+            .await;
+            if let Err(err) = pinned {
+                log::warn!("Failed to join snapshot pinning: {}", err);
             }
         }
+
+        {
+            let mut guard = self.state().await;
+            guard.backend = backend;
+            guard.base_snapshot = Some(base);
+            guard.turn_snapshots.extend(turns);
+        }
+        self.persist_logged().await;
+    }
+
+    /// Storage and commit id for one side of a review.
+    pub async fn snapshot_for(
+        &self,
+        snapshot: SnapshotRef<'_>,
+    ) -> Result<Option<(SnapshotSource, String)>> {
+        let Some((source, pending)) = self.pending_commits(&[snapshot]).await else {
+            return Ok(None);
+        };
+        let mut commits = resolve_pending(&source, pending).await?;
+        Ok(commits.pop().flatten().map(|commit| (source, commit)))
     }
 
+    /// Storage and commit ids for both sides of a review range.
     pub async fn commits_for_range(
+        &self,
+        base: SnapshotRef<'_>,
+        target: SnapshotRef<'_>,
+    ) -> Result<Option<(SnapshotSource, String, String)>> {
+        let Some((source, pending)) = self.pending_commits(&[base, target]).await else {
+            return Ok(None);
+        };
+        let mut commits = resolve_pending(&source, pending).await?.into_iter();
+
+        let (Some(Some(base_commit)), Some(Some(target_commit))) = (commits.next(), commits.next())
+        else {
+            return Ok(None);
+        };
+        Ok(Some((source, base_commit, target_commit)))
+    }
+
+    /// Look up snapshot commits under the lock; git revisions are left for
+    /// `resolve_pending` so the repository is not opened while holding it.
+    async fn pending_commits(
+        &self,
+        snapshots: &[SnapshotRef<'_>],
+    ) -> Option<(SnapshotSource, Vec<PendingCommit>)> {
+        let guard = self.state().await;
+
+        if guard.disabled {
+            return None;
+        }
+
+        let pending = snapshots
+            .iter()
+            .map(|snapshot| match snapshot {
+                SnapshotRef::Base => PendingCommit::Known(guard.commit_for(None)),
+                SnapshotRef::Turn(event_id) => {
+                    PendingCommit::Known(guard.commit_for(Some(event_id)))
+                }
+                SnapshotRef::Git(revision) => PendingCommit::Revision(revision.to_string()),
+            })
+            .collect();
+        Some((self.snapshot_source(&guard), pending))
+    }
+
+    /// Turn snapshots after `base_event_id` (the conversation base when
+    /// omitted) up to and including `target_event_id`, for exporting as git
+    /// history.
+    pub async fn export_range(
         &self,
         base_event_id: Option<&str>,
         target_event_id: &str,
-    ) -> Result<Option<(PathBuf, String, String)>> {
-        let guard = self.inner.lock().await;
+    ) -> Result<SnapshotExportRange> {
+        let guard = self.state().await;
 
         if guard.disabled {
-            return Ok(None);
+            return Err(anyhow!("Snapshotting disabled for conversation"));
+        }
+        if guard.backend != SnapshotBackend::Git {
+            return Err(anyhow!(
+                "Exporting requires a workspace inside a git repository"
+            ));
         }
 
-        let base_commit = match base_event_id {
-            Some(event_id) => guard.turn_commits.get(event_id).cloned(),
-            None => guard.base_commit.clone(),
-        };
-        let target_commit = guard.turn_commits.get(target_event_id).cloned();
+        let base_commit = guard
+            .commit_for(base_event_id)
+            .ok_or_else(|| anyhow!("No base snapshot recorded for export range"))?;
 
-        let Some(base_commit) = base_commit else {
-            return Ok(None);
+        let turns = guard.ordered_turns();
+        let position = |event_id: &str| {
+            turns
+                .iter()
+                .position(|turn| turn.event_id == event_id)
+                .ok_or_else(|| anyhow!("No snapshot recorded for turn {}", event_id))
         };
-        let Some(target_commit) = target_commit else {
-            return Ok(None);
+        let end = position(target_event_id)?;
+        let start = match base_event_id {
+            Some(base_event_id) => position(base_event_id)? + 1,
+            None => 0,
         };
+        if start > end {
+            return Err(anyhow!("Export range base comes after its target"));
+        }
 
-        Ok(Some((guard.cwd.clone(), base_commit, target_commit)))
+        Ok(SnapshotExportRange {
+            repo_path: guard.cwd.clone(),
+            base_commit,
+            turns: turns[start..=end].to_vec(),
+        })
     }
 
-    pub async fn snapshot_summary(&self) -> ConversationSnapshotSummary {
-        let guard = self.inner.lock().await;
-        let turn_commits = guard
-            .turn_commits
+    /// Base snapshot and the turn snapshots up to and including
+    /// `target_event_id`, or all of them when omitted.
+    pub async fn history_until(&self, target_event_id: Option<&str>) -> Option<SnapshotHistory> {
+        let guard = self.state().await;
+
+        if guard.disabled {
+            return None;
+        }
+
+        let base_commit = guard.commit_for(None)?;
+        let mut turns = guard.ordered_turns();
+        if let Some(target_event_id) = target_event_id {
+            let end = turns
+                .iter()
+                .position(|turn| turn.event_id == target_event_id)?;
+            turns.truncate(end + 1);
+        }
+
+        Some(SnapshotHistory {
+            source: self.snapshot_source(&guard),
+            base_commit,
+            turns,
+        })
+    }
+
+    /// Delete the stored snapshot history of a conversation that is not open,
+    /// unpinning its ghost commits so `git gc` can collect them. Returns the
+    /// number of refs removed. File store snapshots are left to
+    /// [`FileSnapshotStore::collect_garbage`].
+    pub async fn discard_store(conversation_id: &str, store_path: &Path) -> Result<u32> {
+        let persisted = read_persisted_store(store_path).await?;
+
+        let mut removed = 0;
+        if persisted.backend == SnapshotBackend::Git
+            && let Some(cwd) = persisted.cwd
+        {
+            let conversation_id = conversation_id.to_string();
+            let result =
+                tokio::task::spawn_blocking(move || unpin_conversation(&cwd, &conversation_id))
+                    .await
+                    .context("failed to join unpin task")?;
+            match result {
+                Ok(count) => removed = count,
+                // The workspace may have been moved or deleted since.
+                Err(err) => log::debug!("Failed to unpin snapshots: {}", err),
+            }
+        }
+
+        fs::remove_file(store_path)
+            .await
+            .context("Failed to remove review snapshot store")?;
+        Ok(removed)
+    }
+
+    /// File store snapshots a stored history refers to; none when it is kept
+    /// in git.
+    pub async fn file_snapshot_ids(store_path: &Path) -> Result<Vec<String>> {
+        let persisted = read_persisted_store(store_path).await?;
+        if persisted.backend != SnapshotBackend::Files {
+            return Ok(Vec::new());
+        }
+        let records = persisted.base.iter().chain(persisted.turns.values());
+        let safety = persisted
+            .safety
             .iter()
-            .map(|(event_id, commit)| (event_id.clone(), commit.clone()))
-            .collect();
+            .flat_map(|record| [&record.commit_id, &record.restored_commit_id]);
+        Ok(records
+            .map(|record| &record.commit_id)
+            .chain(safety)
+            .cloned()
+            .collect())
+    }
+
+    /// Check out the working tree to the base snapshot (`None`) or a turn
+    /// snapshot. The current state is captured first so the restore itself can
+    /// be undone. Files either snapshot left out are not touched.
+    pub async fn restore_snapshot(&self, event_id: Option<&str>) -> Result<RestoredSnapshot> {
+        let (cwd, backend, source, restored_commit, mut kept_paths) = {
+            let guard = self.state().await;
+
+            if guard.disabled {
+                return Err(anyhow!("Snapshotting disabled for conversation"));
+            }
+
+            let record = guard.record_for(event_id).ok_or_else(|| match event_id {
+                Some(event_id) => anyhow!("No snapshot recorded for turn {}", event_id),
+                None => anyhow!("Base snapshot unavailable for conversation"),
+            })?;
+
+            (
+                guard.cwd.clone(),
+                guard.backend,
+                self.snapshot_source(&guard),
+                record.commit_id.clone(),
+                record.skipped_paths.clone(),
+            )
+        };
+
+        let (safety_commit, safety_report) = self
+            .create_snapshot(cwd.clone())
+            .await
+            .context("Failed to capture safety snapshot")?;
+        kept_paths.extend(safety_report.skipped_paths());
+        kept_paths.sort();
+        kept_paths.dedup();
+
+        {
+            let mut guard = self.state().await;
+            guard.safety_snapshots.push(SafetySnapshotRecord {
+                commit_id: safety_commit.clone(),
+                captured_at: Utc::now(),
+                restored_commit_id: restored_commit.clone(),
+            });
+        }
+        self.persist_logged().await;
+
+        let target = restored_commit.clone();
+        let safety = safety_commit.clone();
+        let file_store = self.file_store.clone();
+        tokio::task::spawn_blocking(move || {
+            let root = snapshot_root(&source, &cwd)?;
+            let kept = KeptFiles::set_aside(&root, &kept_paths)?;
+            let restored = match backend {
+                SnapshotBackend::Git => restore_working_tree(&cwd, &target, &safety),
+                SnapshotBackend::Files => {
+                    restore_file_snapshot(&file_store, &cwd, &target, &safety)
+                }
+            };
+            let put_back = kept.put_back();
+            restored.and(put_back)
+        })
+        .await
+        .context("failed to join restore task")??;
+
+        Ok(RestoredSnapshot {
+            restored_commit,
+            safety_commit,
+        })
+    }
+
+    /// Turn snapshotting back on after it was disabled by a failure.
+    pub async fn reenable(&self) {
+        let mut guard = self.state().await;
+        guard.disabled = false;
+        guard.last_error = None;
+    }
+
+    pub async fn snapshot_summary(&self) -> ConversationSnapshotSummary {
+        let guard = self.state().await;
+        let turns = guard.ordered_turns();
 
         ConversationSnapshotSummary {
             disabled: guard.disabled,
-            base_commit: guard.base_commit.clone(),
-            turn_commits,
+            last_error: guard.last_error.clone(),
+            base_commit: guard
+                .base_snapshot
+                .as_ref()
+                .map(|record| record.commit_id.clone()),
+            turns,
         }
     }
 
-    async fn create_snapshot(cwd: PathBuf) -> Result<String> {
-        tokio::task::spawn_blocking(move || {
-            let options = CreateGhostCommitOptions::new(&cwd);
-            create_ghost_commit(&options)
-                .map(|commit| commit.id().to_string())
-                .map_err(|err| anyhow!(err))
+    fn snapshot_source(&self, state: &ConversationSnapshotState) -> SnapshotSource {
+        match state.backend {
+            SnapshotBackend::Git => SnapshotSource::Git(state.cwd.clone()),
+            SnapshotBackend::Files => SnapshotSource::Files(self.file_store.clone()),
+        }
+    }
+
+    async fn persist(&self) -> Result<()> {
+        let _write_guard = self.persist_lock.lock().await;
+        let json = {
+            let guard = self.state().await;
+            serde_json::to_string_pretty(&guard.to_persisted())
+                .context("Failed to serialize review snapshots")?
+        };
+
+        let store_path = self.store_path.clone();
+        tokio::task::spawn_blocking(move || write_atomically(&store_path, json.as_bytes()))
+            .await
+            .context("failed to join review snapshot write")?
+            .context("Failed to write review snapshot store")
+    }
+
+    async fn persist_logged(&self) {
+        if let Err(err) = self.persist().await {
+            log::warn!(
+                "Failed to persist review snapshots to {}: {}",
+                self.store_path.display(),
+                err
+            );
+        }
+    }
+
+    /// Capture the workspace, retrying with backoff while failures look
+    /// transient.
+    async fn create_snapshot(&self, cwd: PathBuf) -> Result<(String, SnapshotCaptureReport)> {
+        let mut delay = SNAPSHOT_RETRY_DELAY;
+        let mut attempt = 1;
+        loop {
+            match self.capture_once(cwd.clone()).await {
+                Ok(captured) => return Ok(captured),
+                Err(err)
+                    if attempt < SNAPSHOT_ATTEMPTS
+                        && classify_snapshot_error(&err) == SnapshotFailure::Transient =>
+                {
+                    log::debug!(
+                        "Snapshot attempt {} for {} failed, retrying: {:#}",
+                        attempt,
+                        cwd.display(),
+                        err
+                    );
+                    tokio::time::sleep(delay).await;
+                    delay *= 2;
+                    attempt += 1;
+                }
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    /// Capture the workspace with the conversation's backend. A conversation
+    /// without snapshots falls back to the file store when the workspace turns
+    /// out not to be a git repository.
+    async fn capture_once(&self, cwd: PathBuf) -> Result<(String, SnapshotCaptureReport)> {
+        let (backend, settings) = {
+            let guard = self.state().await;
+            (guard.backend, guard.capture_settings.clone())
+        };
+        if backend == SnapshotBackend::Files {
+            return self.capture_files(cwd, settings).await;
+        }
+
+        let git_cwd = cwd.clone();
+        let git_settings = settings.clone();
+        let conversation_id = self.conversation_id.clone();
+        let result = tokio::task::spawn_blocking(move || {
+            let Some((commit_id, report)) = capture_git(&git_cwd, &git_settings)? else {
+                return Ok(None);
+            };
+            // An unpinned snapshot still works until `git gc` collects it.
+            if let Err(err) = pin_snapshot(&git_cwd, &conversation_id, &commit_id) {
+                log::warn!("Failed to pin snapshot {}: {}", commit_id, err);
+            }
+            Ok::<_, anyhow::Error>(Some((commit_id, report)))
         })
         .await
-        .context("failed to capture snapshot task")?
+        .context("failed to capture snapshot task")??;
+
+        match result {
+            Some((commit_id, report)) => {
+                self.note_capture_report(&report, &settings).await;
+                Ok((commit_id, report))
+            }
+            None => {
+                {
+                    let mut guard = self.state().await;
+                    if guard.base_snapshot.is_some() || !guard.turn_snapshots.is_empty() {
+                        return Err(anyhow!(
+                            "Workspace is no longer a git repository: {}",
+                            cwd.display()
+                        ));
+                    }
+                    guard.backend = SnapshotBackend::Files;
+                }
+                log::info!(
+                    "{} is not a git repository; using file snapshots",
+                    cwd.display()
+                );
+                self.capture_files(cwd, settings).await
+            }
+        }
+    }
+
+    async fn capture_files(
+        &self,
+        cwd: PathBuf,
+        settings: SnapshotCaptureSettings,
+    ) -> Result<(String, SnapshotCaptureReport)> {
+        let file_store = self.file_store.clone();
+        let capture_settings = settings.clone();
+        let (snapshot_id, report) =
+            tokio::task::spawn_blocking(move || file_store.capture(&cwd, &capture_settings))
+                .await
+                .context("failed to capture snapshot task")??;
+        self.note_capture_report(&report, &settings).await;
+        Ok((snapshot_id, report))
+    }
+
+    async fn note_capture_report(
+        &self,
+        report: &SnapshotCaptureReport,
+        settings: &SnapshotCaptureSettings,
+    ) {
+        if !report.skipped_large_files.is_empty() {
+            log::debug!(
+                "Left {} large files out of a snapshot",
+                report.skipped_large_files.len()
+            );
+        }
+        let Some(warning) = report.limit_warning(settings) else {
+            return;
+        };
+        let sink = self.state().await.warning_sink.clone();
+        match sink {
+            Some(sink) => sink.emit(&self.conversation_id, warning),
+            None => log::warn!(
+                "Snapshot of conversation {} hit capture limits: {:?}",
+                self.conversation_id,
+                warning
+            ),
+        }
+    }
+}
+
+async fn read_persisted_store(store_path: &Path) -> Result<PersistedSnapshotState> {
+    let content = fs::read_to_string(store_path)
+        .await
+        .context("Failed to read review snapshot store")?;
+    serde_json::from_str(&content).context("Failed to parse review snapshot store")
+}
+
+/// Capture the repository at `cwd` as a ghost commit, honouring capture
+/// settings when there are any. `None` when `cwd` is not in a repository.
+fn capture_git(
+    cwd: &Path,
+    settings: &SnapshotCaptureSettings,
+) -> Result<Option<(String, SnapshotCaptureReport)>> {
+    if settings.is_empty() {
+        let options = CreateGhostCommitOptions::new(cwd);
+        return match create_ghost_commit(&options) {
+            Ok(commit) => Ok(Some((
+                commit.id().to_string(),
+                SnapshotCaptureReport::default(),
+            ))),
+            Err(GitToolingError::NotAGitRepository { .. }) => Ok(None),
+            Err(err) => Err(anyhow!(err)),
+        };
+    }
+
+    match capture_git_snapshot(cwd, settings) {
+        Ok(captured) => Ok(Some(captured)),
+        Err(ReviewDiffError::OpenRepository { source, .. })
+            if source.code() == git2::ErrorCode::NotFound =>
+        {
+            Ok(None)
+        }
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Diff stats between two snapshots; `None` when they cannot be computed.
+async fn change_stats(
+    source: SnapshotSource,
+    base: String,
+    target: String,
+) -> Option<TurnChangeStats> {
+    let result = tokio::task::spawn_blocking(move || {
+        list_changed_files(&source, &base, &target, &ReviewDiffOptions::default())
+    })
+    .await;
+    match result {
+        Ok(Ok(files)) => Some(TurnChangeStats::from_files(&files)),
+        Ok(Err(err)) => {
+            log::debug!("Failed to compute turn change stats: {}", err);
+            None
+        }
+        Err(err) => {
+            log::debug!("Failed to join turn stats task: {}", err);
+            None
+        }
+    }
+}
+
+/// Resolve the git revisions among `pending` off the async runtime.
+async fn resolve_pending(
+    source: &SnapshotSource,
+    pending: Vec<PendingCommit>,
+) -> Result<Vec<Option<String>>> {
+    let source = source.clone();
+    tokio::task::spawn_blocking(move || {
+        pending
+            .into_iter()
+            .map(|commit| match commit {
+                PendingCommit::Known(commit) => Ok(commit),
+                PendingCommit::Revision(revision) => resolve_revision(&source, &revision).map(Some),
+            })
+            .collect::<Result<Vec<_>, _>>()
+    })
+    .await
+    .context("failed to join revision lookup")?
+    .map_err(Into::into)
+}
+
+fn prompt_preview(message: &str) -> String {
+    let message = message.trim();
+    match message.char_indices().nth(PROMPT_PREVIEW_MAX_LEN) {
+        Some((truncate_at, _)) => format!("{}…", &message[..truncate_at]),
+        None => message.to_string(),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SnapshotFailure {
+    /// Lock contention or a busy file; worth retrying.
+    Transient,
+    /// The workspace cannot be snapshotted until something changes.
+    Permanent,
+}
+
+fn classify_snapshot_error(err: &anyhow::Error) -> SnapshotFailure {
+    let io_transient = err.chain().any(|cause| {
+        cause.downcast_ref::<std::io::Error>().is_some_and(|io| {
+            matches!(
+                io.kind(),
+                std::io::ErrorKind::Interrupted
+                    | std::io::ErrorKind::WouldBlock
+                    | std::io::ErrorKind::TimedOut
+                    | std::io::ErrorKind::ResourceBusy
+            )
+        })
+    });
+    let message = format!("{:#}", err).to_lowercase();
+    if io_transient
+        || TRANSIENT_ERROR_MARKERS
+            .iter()
+            .any(|marker| message.contains(marker))
+    {
+        SnapshotFailure::Transient
+    } else {
+        SnapshotFailure::Permanent
+    }
+}
+
+/// File-store counterpart of [`restore_working_tree`].
+fn restore_file_snapshot(
+    file_store: &FileSnapshotStore,
+    cwd: &Path,
+    target: &str,
+    safety: &str,
+) -> Result<()> {
+    file_store.restore(cwd, target)?;
+    for path in file_store.added_paths(cwd, target, safety)? {
+        match std::fs::remove_file(&path) {
+            Ok(()) => {}
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+            Err(err) => {
+                return Err(err).with_context(|| format!("Failed to remove {}", path.display()));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Files moved out of the way while a restore runs, so their current content
+/// survives it.
+struct KeptFiles {
+    /// Each path with where it was moved, or `None` when it did not exist.
+    entries: Vec<(PathBuf, Option<PathBuf>)>,
+}
+
+impl KeptFiles {
+    fn set_aside(root: &Path, paths: &[String]) -> Result<Self> {
+        let mut kept = Self {
+            entries: Vec::new(),
+        };
+        for relative in paths {
+            if let Err(err) = kept.set_aside_one(root.join(relative)) {
+                if let Err(put_back_err) = kept.put_back() {
+                    log::warn!("Failed to put back kept files: {:#}", put_back_err);
+                }
+                return Err(err);
+            }
+        }
+        Ok(kept)
+    }
+
+    fn set_aside_one(&mut self, path: PathBuf) -> Result<()> {
+        match std::fs::symlink_metadata(&path) {
+            Ok(metadata) if metadata.is_dir() => Ok(()),
+            Ok(_) => {
+                let mut aside = path.clone().into_os_string();
+                aside.push(format!(".pasture-kept-{}", Uuid::new_v4()));
+                let aside = PathBuf::from(aside);
+                std::fs::rename(&path, &aside)
+                    .with_context(|| format!("Failed to move {} aside", path.display()))?;
+                self.entries.push((path, Some(aside)));
+                Ok(())
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                self.entries.push((path, None));
+                Ok(())
+            }
+            Err(err) => Err(err).with_context(|| format!("Failed to stat {}", path.display())),
+        }
+    }
+
+    /// Undo whatever the restore wrote at the kept paths.
+    fn put_back(self) -> Result<()> {
+        for (path, aside) in self.entries {
+            match std::fs::remove_file(&path) {
+                Ok(()) => {}
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+                Err(err) => {
+                    return Err(err)
+                        .with_context(|| format!("Failed to remove {}", path.display()));
+                }
+            }
+            if let Some(aside) = aside {
+                std::fs::rename(&aside, &path)
+                    .with_context(|| format!("Failed to put back {}", path.display()))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Restore tracked content from `target` and remove files that only exist
+/// because they were created after it. Those files are preserved in the
+/// `safety` snapshot, so nothing is lost.
+fn restore_working_tree(cwd: &Path, target: &str, safety: &str) -> Result<()> {
+    restore_to_commit(cwd, target).map_err(|err| anyhow!(err))?;
+
+    // The repository root may sit above the workspace; only prune inside it.
+    let root = cwd.canonicalize().unwrap_or_else(|_| cwd.to_path_buf());
+    for path in added_paths(cwd, target, safety)? {
+        if !path.starts_with(&root) {
+            continue;
+        }
+        match std::fs::remove_file(&path) {
+            Ok(()) => {}
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+            Err(err) => {
+                return Err(err).with_context(|| format!("Failed to remove {}", path.display()));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lock_contention_is_transient() {
+        let lock = anyhow!(
+            "fatal: Unable to create '/repo/.git/index.lock': File exists. Another git process seems to be running"
+        );
+        assert_eq!(classify_snapshot_error(&lock), SnapshotFailure::Transient);
+
+        let busy = anyhow::Error::new(std::io::Error::from(std::io::ErrorKind::ResourceBusy))
+            .context("Failed to read src/main.rs");
+        assert_eq!(classify_snapshot_error(&busy), SnapshotFailure::Transient);
+
+        let too_large = anyhow!("Workspace exceeds file snapshot limits");
+        assert_eq!(
+            classify_snapshot_error(&too_large),
+            SnapshotFailure::Permanent
+        );
+    }
+
+    #[tokio::test]
+    async fn restore_keeps_files_left_out_of_snapshots() {
+        let dir = tempfile::tempdir().unwrap();
+        let workspace = dir.path().join("workspace");
+        let repo = git2::Repository::init(&workspace).unwrap();
+        std::fs::write(workspace.join("small.txt"), "before\n").unwrap();
+        std::fs::write(workspace.join("large.txt"), "committed\n").unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("small.txt")).unwrap();
+        index.add_path(Path::new("large.txt")).unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = git2::Signature::now("Test", "test@example.com").unwrap();
+        repo.commit(Some("HEAD"), &signature, &signature, "init", &tree, &[])
+            .unwrap();
+
+        let snapshots = ReviewSnapshots::new(
+            "conversation".to_string(),
+            workspace.clone(),
+            dir.path().join("store.json"),
+            FileSnapshotStore::new(dir.path().join("files")),
+            Arc::default(),
+        );
+        snapshots
+            .set_capture_settings(SnapshotCaptureSettings {
+                max_file_bytes: Some(32),
+                ..Default::default()
+            })
+            .await;
+        let large_edit = "edited past the size limit\n".repeat(4);
+        std::fs::write(workspace.join("large.txt"), &large_edit).unwrap();
+        snapshots.ensure_base().await.unwrap();
+
+        std::fs::write(workspace.join("small.txt"), "after\n").unwrap();
+        snapshots.restore_snapshot(None).await.unwrap();
+
+        let read = |path: &str| std::fs::read_to_string(workspace.join(path)).unwrap();
+        assert_eq!(read("small.txt"), "before\n");
+        assert_eq!(read("large.txt"), large_edit);
+        assert_eq!(std::fs::read_dir(&workspace).unwrap().count(), 3);
+    }
+
+    #[tokio::test]
+    async fn restore_matches_the_snapshot_and_records_a_safety_snapshot() {
+        for use_git in [true, false] {
+            let dir = tempfile::tempdir().unwrap();
+            let workspace = dir.path().join("workspace");
+            std::fs::create_dir_all(&workspace).unwrap();
+            std::fs::write(workspace.join("a.txt"), "one\n").unwrap();
+            if use_git {
+                let repo = git2::Repository::init(&workspace).unwrap();
+                let mut index = repo.index().unwrap();
+                index.add_path(Path::new("a.txt")).unwrap();
+                let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+                let signature = git2::Signature::now("Test", "test@example.com").unwrap();
+                repo.commit(Some("HEAD"), &signature, &signature, "init", &tree, &[])
+                    .unwrap();
+            }
+            let store_path = dir.path().join("store.json");
+            let snapshots = ReviewSnapshots::new(
+                "conversation".to_string(),
+                workspace.clone(),
+                store_path.clone(),
+                FileSnapshotStore::new(dir.path().join("files")),
+                Arc::default(),
+            );
+            snapshots
+                .set_capture_settings(SnapshotCaptureSettings {
+                    max_file_bytes: Some(1024),
+                    ..Default::default()
+                })
+                .await;
+            snapshots.ensure_base().await.unwrap();
+            std::fs::write(workspace.join("a.txt"), "two\n").unwrap();
+            std::fs::create_dir_all(workspace.join("sub")).unwrap();
+            std::fs::write(workspace.join("sub/new.txt"), "new\n").unwrap();
+            let turn = snapshots
+                .record_turn_snapshot("turn-1")
+                .await
+                .unwrap()
+                .unwrap();
+            std::fs::write(workspace.join("a.txt"), "three\n").unwrap();
+            std::fs::write(workspace.join("extra.txt"), "extra\n").unwrap();
+
+            let read = |path: &str| std::fs::read_to_string(workspace.join(path)).ok();
+            let to_turn = snapshots.restore_snapshot(Some("turn-1")).await.unwrap();
+            assert_eq!(to_turn.restored_commit, turn);
+            assert_eq!(read("a.txt").as_deref(), Some("two\n"));
+            assert_eq!(read("sub/new.txt").as_deref(), Some("new\n"));
+            assert_eq!(read("extra.txt"), None);
+
+            let to_base = snapshots.restore_snapshot(None).await.unwrap();
+            assert_eq!(read("a.txt").as_deref(), Some("one\n"));
+            assert_eq!(read("sub/new.txt"), None);
+
+            let base = snapshots.snapshot_summary().await.base_commit.unwrap();
+            let persisted = read_persisted_store(&store_path).await.unwrap();
+            let recorded: Vec<_> = persisted
+                .safety
+                .iter()
+                .map(|record| (record.commit_id.clone(), record.restored_commit_id.clone()))
+                .collect();
+            assert_eq!(
+                recorded,
+                vec![
+                    (to_turn.safety_commit.clone(), turn),
+                    (to_base.safety_commit, base),
+                ]
+            );
+
+            // The safety snapshot can bring back what the restore removed.
+            let source = snapshots.snapshot_source(&*snapshots.state().await);
+            assert_eq!(matches!(source, SnapshotSource::Git(_)), use_git);
+            let extra =
+                crate::review_diff::snapshot_file(&source, &to_turn.safety_commit, "extra.txt")
+                    .unwrap()
+                    .unwrap();
+            assert_eq!(extra.content.as_deref(), Some("extra\n"));
+        }
+    }
+
+    #[tokio::test]
+    async fn capture_warnings_reach_the_sink_right_away() {
+        let dir = tempfile::tempdir().unwrap();
+        let workspace = dir.path().join("workspace");
+        git2::Repository::init(&workspace).unwrap();
+        std::fs::write(workspace.join("a.txt"), "aaaaaaaa\n").unwrap();
+        std::fs::write(workspace.join("b.txt"), "bbbbbbbb\n").unwrap();
+
+        let snapshots = ReviewSnapshots::new(
+            "conversation".to_string(),
+            workspace,
+            dir.path().join("store.json"),
+            FileSnapshotStore::new(dir.path().join("files")),
+            Arc::default(),
+        );
+        snapshots
+            .set_capture_settings(SnapshotCaptureSettings {
+                max_total_bytes: Some(12),
+                ..Default::default()
+            })
+            .await;
+        let received = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink_received = received.clone();
+        snapshots
+            .set_warning_sink(SnapshotWarningSink::new(move |conversation_id, warning| {
+                sink_received
+                    .lock()
+                    .unwrap()
+                    .push((conversation_id.to_string(), warning));
+            }))
+            .await;
+
+        snapshots.ensure_base().await.unwrap();
+
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 1);
+        assert_eq!(received[0].0, "conversation");
+        assert_eq!(received[0].1.skipped_count, 1);
+    }
+
+    #[tokio::test]
+    async fn history_survives_reopening_the_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let workspace = dir.path().join("workspace");
+        git2::Repository::init(&workspace).unwrap();
+        std::fs::write(workspace.join("a.txt"), "one\n").unwrap();
+        let store_dir = dir.path().join("store");
+        let open = || {
+            ReviewSnapshots::new(
+                "conversation".to_string(),
+                workspace.clone(),
+                store_dir.join("conversation.json"),
+                FileSnapshotStore::new(dir.path().join("files")),
+                Arc::default(),
+            )
+        };
+
+        let snapshots = open();
+        snapshots.ensure_base().await.unwrap();
+        snapshots.record_user_prompt("Change a").await;
+        std::fs::write(workspace.join("a.txt"), "two\n").unwrap();
+        let commit = snapshots.record_turn_snapshot("turn-1").await.unwrap();
+        let saved = snapshots.snapshot_summary().await;
+
+        let reopened = open().snapshot_summary().await;
+        assert_eq!(reopened.base_commit, saved.base_commit);
+        assert_eq!(reopened.turns.len(), 1);
+        let turn = &reopened.turns[0];
+        assert_eq!(Some(&turn.commit_id), commit.as_ref());
+        assert_eq!(turn.event_id, "turn-1");
+        assert_eq!(turn.turn_index, 1);
+        assert_eq!(turn.prompt.as_deref(), Some("Change a"));
+        assert_eq!(turn.stats, saved.turns[0].stats);
+        assert_eq!(turn.captured_at, saved.turns[0].captured_at);
+
+        // Only the store itself is left behind, no temporary files.
+        let names: Vec<_> = std::fs::read_dir(&store_dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(names, vec!["conversation.json"]);
+    }
+
+    #[tokio::test]
+    async fn summary_lists_turns_in_capture_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let workspace = dir.path().join("workspace");
+        std::fs::create_dir_all(&workspace).unwrap();
+        std::fs::write(workspace.join("a.txt"), "one\n").unwrap();
+        let open = || {
+            ReviewSnapshots::new(
+                "conversation".to_string(),
+                workspace.clone(),
+                dir.path().join("store.json"),
+                FileSnapshotStore::new(dir.path().join("files")),
+                Arc::default(),
+            )
+        };
+
+        let snapshots = open();
+        snapshots
+            .set_capture_settings(SnapshotCaptureSettings {
+                max_file_bytes: Some(1024),
+                ..Default::default()
+            })
+            .await;
+        snapshots.ensure_base().await.unwrap();
+        // Event ids sort differently from the order the turns ran in.
+        let turns = [
+            ("turn-c", "Append a line", "a.txt", "one\ntwo\n"),
+            ("turn-a", "Add b", "b.txt", "b\n"),
+            ("turn-b", "Rewrite a", "a.txt", "three\n"),
+        ];
+        for (event_id, prompt, path, content) in turns {
+            snapshots.record_user_prompt(prompt).await;
+            std::fs::write(workspace.join(path), content).unwrap();
+            snapshots.record_turn_snapshot(event_id).await.unwrap();
+        }
+
+        let expected = vec![
+            ("turn-c", 1, "Append a line", (1, 1, 0)),
+            ("turn-a", 2, "Add b", (1, 1, 0)),
+            ("turn-b", 3, "Rewrite a", (1, 1, 2)),
+        ];
+        for summary in [
+            snapshots.snapshot_summary().await,
+            open().snapshot_summary().await,
+        ] {
+            let turns: Vec<_> = summary
+                .turns
+                .iter()
+                .map(|turn| {
+                    let stats = turn.stats.unwrap();
+                    (
+                        turn.event_id.as_str(),
+                        turn.turn_index,
+                        turn.prompt.as_deref().unwrap(),
+                        (stats.files_changed, stats.insertions, stats.deletions),
+                    )
+                })
+                .collect();
+            assert_eq!(turns, expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn edits_between_turns_get_their_own_entry() {
+        let dir = tempfile::tempdir().unwrap();
+        let workspace = dir.path().join("workspace");
+        std::fs::create_dir_all(&workspace).unwrap();
+        std::fs::write(workspace.join("a.txt"), "one\n").unwrap();
+        let snapshots = ReviewSnapshots::new(
+            "conversation".to_string(),
+            workspace.clone(),
+            dir.path().join("store.json"),
+            FileSnapshotStore::new(dir.path().join("files")),
+            Arc::default(),
+        );
+        snapshots
+            .set_capture_settings(SnapshotCaptureSettings {
+                max_file_bytes: Some(1024),
+                ..Default::default()
+            })
+            .await;
+        snapshots.ensure_base().await.unwrap();
+
+        snapshots.record_user_prompt("First").await;
+        std::fs::write(workspace.join("a.txt"), "two\n").unwrap();
+        snapshots.record_turn_snapshot("turn-1").await.unwrap();
+
+        // Nothing changed since the turn, so there is nothing to record.
+        assert_eq!(snapshots.record_external_changes().await.unwrap(), None);
+
+        std::fs::write(workspace.join("notes.txt"), "mine\n").unwrap();
+        let external = snapshots.record_external_changes().await.unwrap();
+        assert!(external.is_some());
+        assert_eq!(snapshots.record_external_changes().await.unwrap(), None);
+
+        snapshots.record_user_prompt("Second").await;
+        std::fs::write(workspace.join("a.txt"), "three\n").unwrap();
+        snapshots.record_turn_snapshot("turn-2").await.unwrap();
+
+        let turns = snapshots.snapshot_summary().await.turns;
+        let entries: Vec<_> = turns
+            .iter()
+            .map(|turn| {
+                let stats = turn.stats.unwrap();
+                (
+                    turn.origin,
+                    turn.prompt.as_deref().unwrap(),
+                    (stats.files_changed, stats.insertions, stats.deletions),
+                )
+            })
+            .collect();
+        assert_eq!(
+            entries,
+            vec![
+                (SnapshotOrigin::Turn, "First", (1, 1, 1)),
+                (
+                    SnapshotOrigin::External,
+                    "Changes made outside the agent",
+                    (1, 1, 0)
+                ),
+                (SnapshotOrigin::Turn, "Second", (1, 1, 1)),
+            ]
+        );
+        assert_eq!(Some(&turns[1].commit_id), external.as_ref());
+    }
+
+    #[tokio::test]
+    async fn fork_keeps_snapshots_of_kept_turns() {
+        let dir = tempfile::tempdir().unwrap();
+        let workspace = dir.path().join("workspace");
+        let repo = git2::Repository::init(&workspace).unwrap();
+        std::fs::write(workspace.join("a.txt"), "one\n").unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("a.txt")).unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = git2::Signature::now("Test", "test@example.com").unwrap();
+        repo.commit(Some("HEAD"), &signature, &signature, "init", &tree, &[])
+            .unwrap();
+        let open = |conversation_id: &str| {
+            ReviewSnapshots::new(
+                conversation_id.to_string(),
+                workspace.clone(),
+                dir.path().join(format!("{conversation_id}.json")),
+                FileSnapshotStore::new(dir.path().join("files")),
+                Arc::default(),
+            )
+        };
+
+        let source = open("source");
+        source
+            .set_capture_settings(SnapshotCaptureSettings {
+                max_file_bytes: Some(1024),
+                ..Default::default()
+            })
+            .await;
+        source.ensure_base().await.unwrap();
+        for (turn, content) in [("First", "two\n"), ("Second", "three\n")] {
+            source.record_user_prompt(turn).await;
+            std::fs::write(workspace.join("a.txt"), content).unwrap();
+            source
+                .record_turn_snapshot(&format!("turn-{turn}"))
+                .await
+                .unwrap();
+        }
+        let source_summary = source.snapshot_summary().await;
+
+        // Editing the second message forks after the first turn.
+        let fork = open("fork");
+        fork.inherit_history(&source, 1).await;
+
+        for summary in [
+            fork.snapshot_summary().await,
+            open("fork").snapshot_summary().await,
+        ] {
+            assert_eq!(summary.base_commit, source_summary.base_commit);
+            assert_eq!(summary.turns.len(), 1);
+            assert_eq!(summary.turns[0].event_id, "turn-First");
+            assert_eq!(
+                summary.turns[0].commit_id,
+                source_summary.turns[0].commit_id
+            );
+            assert_eq!(summary.turns[0].turn_index, 1);
+            assert_eq!(summary.turns[0].prompt.as_deref(), Some("First"));
+        }
+        let pinned: Vec<String> = repo
+            .references_glob("refs/pasture/snapshots/fork/*")
+            .unwrap()
+            .map(|reference| reference.unwrap().target().unwrap().to_string())
+            .collect();
+        assert_eq!(pinned.len(), 2);
+        assert!(pinned.contains(source_summary.base_commit.as_ref().unwrap()));
+        assert!(pinned.contains(&source_summary.turns[0].commit_id));
     }
 }
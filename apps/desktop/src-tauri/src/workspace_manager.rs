@@ -3,9 +3,12 @@ use anyhow::Result;
 use serde::Deserialize;
 use serde::Serialize;
 use std::collections::HashMap;
+use std::collections::HashSet;
 use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
+use std::time::SystemTime;
 use tokio::fs;
 use tokio::sync::Mutex;
 use tokio::sync::RwLock;
@@ -17,7 +20,16 @@ use codex_protocol::config_types::SandboxMode;
 use codex_protocol::protocol::AskForApproval;
 
 use crate::env;
+use crate::event_listener::EventSubscriptionManager;
+use crate::review_capture::SnapshotCaptureSettings;
+use crate::review_file_snapshots::FileSnapshotStore;
+use crate::review_retention::SnapshotPruneSummary;
 use crate::review_snapshots::ReviewSnapshots;
+use crate::review_snapshots::SnapshotWarningSink;
+
+/// File store manifests younger than this survive garbage collection, so
+/// captures a conversation has not recorded yet keep their data.
+const FILE_SNAPSHOT_GC_GRACE: Duration = Duration::from_secs(60 * 60);
 
 #[derive(Debug, Clone)]
 pub struct ActiveConversation {
@@ -28,12 +40,12 @@ pub struct ActiveConversation {
 }
 
 impl ActiveConversation {
-    pub fn new(rollout_path: PathBuf, cwd: PathBuf) -> Self {
+    pub fn new(rollout_path: PathBuf, cwd: PathBuf, review_snapshots: ReviewSnapshots) -> Self {
         Self {
             rollout_path,
-            cwd: cwd.clone(),
+            cwd,
             environment: Arc::new(Mutex::new(None)),
-            review_snapshots: ReviewSnapshots::new(cwd),
+            review_snapshots,
         }
     }
 
@@ -79,6 +91,12 @@ pub struct WorkspacePersistenceState {
     pub recent: Vec<String>,
     #[serde(default)]
     pub workspace_defaults: HashMap<String, WorkspaceComposerDefaults>,
+    /// Review snapshots of conversations inactive for longer than this are
+    /// pruned on startup; kept forever when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snapshot_retention_days: Option<u32>,
+    #[serde(default)]
+    pub snapshot_settings: HashMap<String, SnapshotCaptureSettings>,
 }
 
 /// Remembered per-workspace defaults applied to new conversations.
@@ -111,18 +129,34 @@ impl WorkspaceComposerDefaults {
 pub struct WorkspaceManager {
     state: Arc<RwLock<WorkspacePersistenceState>>,
     state_path: PathBuf,
+    review_snapshots_dir: PathBuf,
+    file_snapshot_store: FileSnapshotStore,
+    /// Held for writing while pruning, for reading while a conversation
+    /// loads its snapshot history.
+    review_store_lock: Arc<RwLock<()>>,
+    snapshot_warning_sink: Option<SnapshotWarningSink>,
     active_conversations: Arc<Mutex<HashMap<String, ActiveConversation>>>,
 }
 
 impl WorkspaceManager {
-    pub fn new(state_path: PathBuf) -> Self {
+    pub fn new(state_path: PathBuf, review_snapshots_dir: PathBuf) -> Self {
         Self {
             state: Arc::new(RwLock::new(WorkspacePersistenceState::default())),
             state_path,
+            file_snapshot_store: FileSnapshotStore::new(review_snapshots_dir.join("files")),
+            review_snapshots_dir,
+            review_store_lock: Arc::new(RwLock::new(())),
+            snapshot_warning_sink: None,
             active_conversations: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
+    /// Send capture limit warnings of every stored conversation to `sink`.
+    pub fn with_snapshot_warning_sink(mut self, sink: SnapshotWarningSink) -> Self {
+        self.snapshot_warning_sink = Some(sink);
+        self
+    }
+
     pub async fn load_state(&self) -> Result<()> {
         let content = match fs::read_to_string(&self.state_path).await {
             Ok(content) => content,
@@ -244,19 +278,245 @@ impl WorkspaceManager {
             conversations.get(&conversation_id).cloned()
         };
 
+        let capture_settings = self.snapshot_settings_for_cwd(&cwd).await;
         if let Some(mut conversation) = existing {
             conversation.refresh_paths(rollout_path, cwd).await;
+            conversation
+                .review_snapshots()
+                .set_capture_settings(capture_settings)
+                .await;
             let mut conversations = self.active_conversations.lock().await;
             conversations.insert(conversation_id, conversation.clone());
             conversation
         } else {
-            let conversation = ActiveConversation::new(rollout_path, cwd);
+            let store_path = self.review_snapshot_store_path(&conversation_id);
+            let review_snapshots = ReviewSnapshots::new(
+                conversation_id.clone(),
+                cwd.clone(),
+                store_path,
+                self.file_snapshot_store.clone(),
+                self.review_store_lock.clone(),
+            );
+            review_snapshots
+                .set_capture_settings(capture_settings)
+                .await;
+            if let Some(sink) = &self.snapshot_warning_sink {
+                review_snapshots.set_warning_sink(sink.clone()).await;
+            }
+            let conversation = ActiveConversation::new(rollout_path, cwd, review_snapshots);
             let mut conversations = self.active_conversations.lock().await;
             conversations.insert(conversation_id, conversation.clone());
             conversation
         }
     }
 
+    pub async fn get_snapshot_settings_for_normalized(
+        &self,
+        workspace_path: &str,
+    ) -> SnapshotCaptureSettings {
+        let state = self.state.read().await;
+        state
+            .snapshot_settings
+            .get(workspace_path)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Save capture settings for a workspace and apply them to its open
+    /// conversations.
+    pub async fn set_snapshot_settings_for_normalized(
+        &self,
+        workspace_path: &str,
+        settings: SnapshotCaptureSettings,
+    ) -> Result<()> {
+        let mut state = self.state.write().await;
+
+        if settings.is_empty() {
+            state.snapshot_settings.remove(workspace_path);
+        } else {
+            state
+                .snapshot_settings
+                .insert(workspace_path.to_string(), settings.clone());
+        }
+
+        drop(state);
+        self.save_state().await?;
+
+        let conversations: Vec<ActiveConversation> = {
+            let conversations = self.active_conversations.lock().await;
+            conversations.values().cloned().collect()
+        };
+        for conversation in conversations {
+            let in_workspace = self
+                .normalize_workspace_path(&conversation.cwd.to_string_lossy())
+                .is_ok_and(|path| path == workspace_path);
+            if in_workspace {
+                conversation
+                    .review_snapshots()
+                    .set_capture_settings(settings.clone())
+                    .await;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn snapshot_settings_for_cwd(&self, cwd: &Path) -> SnapshotCaptureSettings {
+        match self.normalize_workspace_path(&cwd.to_string_lossy()) {
+            Ok(normalized) => self.get_snapshot_settings_for_normalized(&normalized).await,
+            Err(_) => SnapshotCaptureSettings::default(),
+        }
+    }
+
+    pub async fn get_snapshot_retention_days(&self) -> Option<u32> {
+        let state = self.state.read().await;
+        state.snapshot_retention_days
+    }
+
+    pub async fn set_snapshot_retention_days(&self, retention_days: Option<u32>) -> Result<()> {
+        let mut state = self.state.write().await;
+        state.snapshot_retention_days = retention_days;
+
+        drop(state);
+        self.save_state().await?;
+
+        Ok(())
+    }
+
+    /// Drop the review snapshots of conversations that are not subscribed to
+    /// and were last snapshotted more than `older_than` ago, then reclaim
+    /// file store data no remaining conversation refers to.
+    pub async fn prune_review_snapshots(
+        &self,
+        older_than: Duration,
+        event_manager: &EventSubscriptionManager,
+    ) -> Result<SnapshotPruneSummary> {
+        let cutoff = SystemTime::now()
+            .checked_sub(older_than)
+            .unwrap_or(SystemTime::UNIX_EPOCH);
+        let mut summary = SnapshotPruneSummary::default();
+
+        let store_guard = self.review_store_lock.write().await;
+        let subscribed = event_manager.subscribed_conversation_ids().await;
+        for (conversation_id, path) in self.review_snapshot_stores().await? {
+            let modified = fs::metadata(&path)
+                .await
+                .and_then(|metadata| metadata.modified());
+            if !matches!(modified, Ok(modified) if modified < cutoff) {
+                continue;
+            }
+            if subscribed.contains(&conversation_id) {
+                continue;
+            }
+
+            match ReviewSnapshots::discard_store(&conversation_id, &path).await {
+                Ok(removed) => {
+                    if let Some(conversation) = self.get_active_conversation(&conversation_id).await
+                    {
+                        conversation.review_snapshots().forget_history().await;
+                    }
+                    summary.removed_refs += removed;
+                    summary.pruned_conversation_ids.push(conversation_id);
+                }
+                Err(e) => log::warn!(
+                    "Failed to prune review snapshots for {}: {:#}",
+                    conversation_id,
+                    e
+                ),
+            }
+        }
+        drop(store_guard);
+
+        if !summary.pruned_conversation_ids.is_empty()
+            && let Err(e) = self.collect_file_snapshot_garbage(&HashSet::new()).await
+        {
+            log::warn!("Failed to collect unused file snapshots: {:#}", e);
+        }
+        Ok(summary)
+    }
+
+    /// Delete file store snapshots that no stored snapshot history refers
+    /// to. Recent ones are kept unless `discarded`. Returns the number of
+    /// snapshots removed.
+    pub async fn collect_file_snapshot_garbage(&self, discarded: &HashSet<String>) -> Result<u32> {
+        let mut live = HashSet::new();
+        for (_, path) in self.review_snapshot_stores().await? {
+            match ReviewSnapshots::file_snapshot_ids(&path).await {
+                Ok(ids) => live.extend(ids),
+                // Deleted since it was listed.
+                Err(_) if !fs::try_exists(&path).await.unwrap_or(true) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        let store = self.file_snapshot_store.clone();
+        let discarded = discarded.clone();
+        tokio::task::spawn_blocking(move || {
+            store.collect_garbage(&live, &discarded, FILE_SNAPSHOT_GC_GRACE)
+        })
+        .await
+        .context("failed to join file snapshot collection")?
+    }
+
+    /// Conversation ids and paths of every stored snapshot history.
+    async fn review_snapshot_stores(&self) -> Result<Vec<(String, PathBuf)>> {
+        let mut entries = match fs::read_dir(&self.review_snapshots_dir).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e).context("Failed to list review snapshot stores"),
+        };
+        let mut stores = Vec::new();
+        while let Some(entry) = entries
+            .next_entry()
+            .await
+            .context("Failed to list review snapshot stores")?
+        {
+            let path = entry.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                continue;
+            }
+            let Some(conversation_id) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                continue;
+            };
+            stores.push((conversation_id.to_string(), path));
+        }
+        Ok(stores)
+    }
+
+    /// Forget a conversation, e.g. after its rollout moved or was deleted.
+    pub async fn remove_active_conversation(
+        &self,
+        conversation_id: &str,
+    ) -> Option<ActiveConversation> {
+        let mut conversations = self.active_conversations.lock().await;
+        conversations.remove(conversation_id)
+    }
+
+    /// Forget a conversation and delete its review snapshot history along
+    /// with the file store snapshots only it used. Returns the number of
+    /// snapshot refs removed.
+    pub async fn discard_review_snapshots(&self, conversation_id: &str) -> Result<u32> {
+        self.remove_active_conversation(conversation_id).await;
+        let store_path = self.review_snapshot_store_path(conversation_id);
+        if !fs::try_exists(&store_path).await.unwrap_or(false) {
+            return Ok(0);
+        }
+        let discarded: HashSet<String> = ReviewSnapshots::file_snapshot_ids(&store_path)
+            .await?
+            .into_iter()
+            .collect();
+        let removed = ReviewSnapshots::discard_store(conversation_id, &store_path).await?;
+        if !discarded.is_empty() {
+            self.collect_file_snapshot_garbage(&discarded).await?;
+        }
+        Ok(removed)
+    }
+
+    /// Location of the persisted review snapshot history for a conversation.
+    fn review_snapshot_store_path(&self, conversation_id: &str) -> PathBuf {
+        self.review_snapshots_dir
+            .join(format!("{}.json", conversation_id))
+    }
+
     pub async fn get_active_conversation(
         &self,
         conversation_id: &str,
@@ -265,3 +525,43 @@ impl WorkspaceManager {
         conversations.get(conversation_id).cloned()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn prune_ignores_conversations_that_are_only_listed() {
+        let dir = tempfile::tempdir().unwrap();
+        let workspace = dir.path().join("workspace");
+        git2::Repository::init(&workspace).unwrap();
+        std::fs::write(workspace.join("a.txt"), "one\n").unwrap();
+        let manager = WorkspaceManager::new(
+            dir.path().join("state.json"),
+            dir.path().join("review-snapshots"),
+        );
+
+        // Listing a conversation registers it without making it in use.
+        let conversation = manager
+            .store_active_conversation(
+                "conversation".to_string(),
+                dir.path().join("rollout.jsonl"),
+                workspace.clone(),
+            )
+            .await;
+        let snapshots = conversation.review_snapshots();
+        snapshots.ensure_base().await.unwrap();
+        std::fs::write(workspace.join("a.txt"), "two\n").unwrap();
+        snapshots.record_turn_snapshot("turn-1").await.unwrap();
+
+        let summary = manager
+            .prune_review_snapshots(Duration::ZERO, &EventSubscriptionManager::new())
+            .await
+            .unwrap();
+        assert_eq!(summary.pruned_conversation_ids, vec!["conversation"]);
+        assert!(!manager.review_snapshot_store_path("conversation").exists());
+        let remaining = snapshots.snapshot_summary().await;
+        assert_eq!(remaining.base_commit, None);
+        assert!(remaining.turns.is_empty());
+    }
+}
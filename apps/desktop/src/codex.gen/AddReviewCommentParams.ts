@@ -0,0 +1,17 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ReviewCommentSide } from './ReviewCommentSide';
+
+/**
+ * Parameters accepted when drafting a review comment.
+ */
+export type AddReviewCommentParams = {
+  conversationId: string;
+  baseEventId: string | null;
+  targetEventId: string;
+  filePath: string;
+  side: ReviewCommentSide;
+  line: number;
+  body: string;
+  contextLine: string | null;
+};
@@ -2,10 +2,12 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 import type { AuthState } from './AuthState';
 import type { ConversationEventPayload } from './ConversationEventPayload';
+import type { SnapshotWarningPayload } from './SnapshotWarningPayload';
 
 /**
  * Union of events emitted to the renderer.
  */
 export type CodexEvent =
   | { kind: 'conversation-event'; payload: ConversationEventPayload }
-  | { kind: 'auth-updated'; payload: AuthState };
+  | { kind: 'auth-updated'; payload: AuthState }
+  | { kind: 'snapshot-limit-exceeded'; payload: SnapshotWarningPayload };
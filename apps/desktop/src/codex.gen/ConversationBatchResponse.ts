@@ -0,0 +1,12 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ConversationBatchFailure } from './ConversationBatchFailure';
+
+/**
+ * Outcome of a bulk operation; each conversation succeeds or fails on its
+ * own.
+ */
+export type ConversationBatchResponse = {
+  succeeded: Array<string>;
+  failed: Array<ConversationBatchFailure>;
+};
@@ -0,0 +1,13 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ConversationSummary } from './ConversationSummary';
+import type { SearchSnippet } from './SearchSnippet';
+
+/**
+ * Conversation matching a search, with the passage that matched best.
+ */
+export type ConversationSearchResult = {
+  conversation: ConversationSummary;
+  score: number;
+  snippet: SearchSnippet | null;
+};
@@ -11,4 +11,9 @@ export type ConversationSummary = {
   cwd: string;
   preview: string;
   timestamp: string | null;
+  /**
+   * Time of the latest recorded event.
+   */
+  lastActivity: string | null;
+  archived: boolean;
 };
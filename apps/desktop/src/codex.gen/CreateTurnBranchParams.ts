@@ -0,0 +1,19 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Parameters accepted when materialising a snapshot range as a branch.
+ */
+export type CreateTurnBranchParams = {
+  conversationId: string;
+  baseEventId: string | null;
+  targetEventId: string;
+  /**
+   * Defaults to `pasture/<conversation>/turn-<N>`.
+   */
+  branchName: string | null;
+  /**
+   * Move the branch if it already exists.
+   */
+  force: boolean;
+};
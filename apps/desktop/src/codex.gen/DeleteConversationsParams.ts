@@ -0,0 +1,13 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Conversations to delete for good.
+ */
+export type DeleteConversationsParams = {
+  conversationIds: Array<string>;
+  /**
+   * Must be `true`; deleted conversations cannot be restored.
+   */
+  confirm: boolean;
+};
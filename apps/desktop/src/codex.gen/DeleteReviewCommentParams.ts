@@ -0,0 +1,10 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Parameters accepted when deleting a drafted review comment.
+ */
+export type DeleteReviewCommentParams = {
+  conversationId: string;
+  commentId: string;
+};
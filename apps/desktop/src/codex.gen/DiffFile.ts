@@ -0,0 +1,28 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DiffChangeKind } from './DiffChangeKind';
+import type { DiffHunk } from './DiffHunk';
+
+/**
+ * One file section of a unified diff.
+ */
+export type DiffFile = {
+  oldPath: string | null;
+  newPath: string | null;
+  displayPath: string;
+  changeKind: DiffChangeKind;
+  oldMode: string | null;
+  newMode: string | null;
+  /**
+   * Similarity percentage reported for renames and copies.
+   */
+  similarity: number | null;
+  binary: boolean;
+  insertions: number;
+  deletions: number;
+  /**
+   * Hunks were omitted because the patch exceeds the size caps.
+   */
+  collapsed: boolean;
+  hunks: Array<DiffHunk>;
+};
@@ -0,0 +1,21 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DiffChangeKind } from './DiffChangeKind';
+
+/**
+ * Lightweight description of a changed file, returned before any hunks are
+ * fetched.
+ */
+export type DiffFileSummary = {
+  oldPath: string | null;
+  newPath: string | null;
+  displayPath: string;
+  changeKind: DiffChangeKind;
+  binary: boolean;
+  insertions: number;
+  deletions: number;
+  /**
+   * The file's patch exceeds [`MAX_FILE_PATCH_BYTES`].
+   */
+  collapsed: boolean;
+};
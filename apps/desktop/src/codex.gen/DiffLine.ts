@@ -0,0 +1,16 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DiffLineKind } from './DiffLineKind';
+import type { DiffSpan } from './DiffSpan';
+
+export type DiffLine = {
+  kind: DiffLineKind;
+  text: string;
+  oldNumber: number | null;
+  newNumber: number | null;
+  /**
+   * Changed parts of a removed or added line that pairs with a line on
+   * the other side; empty when the whole line should be highlighted.
+   */
+  spans?: Array<DiffSpan>;
+};
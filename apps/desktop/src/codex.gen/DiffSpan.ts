@@ -0,0 +1,8 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A changed range within a line, in UTF-16 code units so it indexes
+ * JavaScript strings directly.
+ */
+export type DiffSpan = { start: number; end: number };
@@ -0,0 +1,20 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { InputItem } from './InputItem';
+
+/**
+ * Parameters for editing and resending a user message.
+ */
+export type EditUserMessageParams = {
+  conversationId: string;
+  /**
+   * 1-based user turn whose message is replaced.
+   */
+  turnIndex: number;
+  items: Array<InputItem>;
+  /**
+   * Also restore the workspace to the review snapshot taken before the
+   * edited turn.
+   */
+  restoreWorkspace: boolean;
+};
@@ -0,0 +1,15 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Parameters accepted when exporting a snapshot range as a patch file.
+ */
+export type ExportTurnPatchParams = {
+  conversationId: string;
+  baseEventId: string | null;
+  targetEventId: string;
+  /**
+   * File the `git format-patch` style series is written to.
+   */
+  outputPath: string;
+};
@@ -0,0 +1,13 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A turn snapshot replayed as a regular commit.
+ */
+export type ExportedTurnCommit = {
+  turnIndex: number;
+  eventId: string;
+  snapshotCommitId: string;
+  commitId: string;
+  summary: string;
+};
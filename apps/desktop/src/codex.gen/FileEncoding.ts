@@ -0,0 +1,12 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Text encodings recognised when reading snapshot files.
+ */
+export type FileEncoding =
+  | 'utf8'
+  | 'utf8-bom'
+  | 'utf16-le'
+  | 'utf16-be'
+  | 'latin1';
@@ -0,0 +1,21 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { TurnBlameHunk } from './TurnBlameHunk';
+
+/**
+ * Per-line turn attribution for a file at a snapshot.
+ */
+export type FileTurnBlame = {
+  path: string;
+  /**
+   * `false` when the file does not exist at the snapshot.
+   */
+  exists: boolean;
+  binary: boolean;
+  tooLarge: boolean;
+  lineCount: number;
+  /**
+   * Empty for missing, binary and oversized files.
+   */
+  hunks: Array<TurnBlameHunk>;
+};
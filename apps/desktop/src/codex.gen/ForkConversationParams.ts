@@ -0,0 +1,14 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Parameters for forking a conversation.
+ */
+export type ForkConversationParams = {
+  conversationId: string;
+  /**
+   * User turns carried over to the fork; the fork starts just before the
+   * user message of turn `keep_turns + 1`.
+   */
+  keepTurns: number;
+};
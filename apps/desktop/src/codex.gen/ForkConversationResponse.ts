@@ -0,0 +1,27 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ConversationId } from './ConversationId';
+import type { ReasoningSummary } from './ReasoningSummary';
+import type { SessionConfiguredEvent } from './SessionConfiguredEvent';
+
+/**
+ * Response returned after forking a conversation.
+ */
+export type ForkConversationResponse = {
+  conversationId: ConversationId;
+  sessionConfigured: SessionConfiguredEvent;
+  reasoningSummary: ReasoningSummary;
+  /**
+   * The first user message left out of the fork.
+   */
+  droppedMessage: string;
+  /**
+   * Set when the workspace was restored to the snapshot matching the fork
+   * point.
+   */
+  restoredCommitId: string | null;
+  /**
+   * Snapshot of the workspace taken before it was restored.
+   */
+  safetyCommitId: string | null;
+};
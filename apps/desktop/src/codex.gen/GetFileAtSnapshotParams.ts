@@ -0,0 +1,22 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Parameters accepted when reading a file from a snapshot.
+ */
+export type GetFileAtSnapshotParams = {
+  conversationId: string;
+  /**
+   * Turn snapshot to read from; the conversation base is used when omitted.
+   */
+  eventId: string | null;
+  /**
+   * Git revision to read from instead of a snapshot. At most one of
+   * `event_id` and `git_ref` may be set.
+   */
+  gitRef: string | null;
+  /**
+   * Path relative to the repository root, as in diff responses.
+   */
+  path: string;
+};
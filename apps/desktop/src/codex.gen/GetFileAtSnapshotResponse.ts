@@ -0,0 +1,11 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SnapshotFile } from './SnapshotFile';
+
+export type GetFileAtSnapshotResponse = {
+  commitId: string;
+  /**
+   * `None` when the file does not exist in the snapshot.
+   */
+  file: SnapshotFile | null;
+};
@@ -0,0 +1,17 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Parameters accepted when attributing a file's lines to turns.
+ */
+export type GetTurnBlameParams = {
+  conversationId: string;
+  /**
+   * Turn snapshot to blame; the latest snapshot is used when omitted.
+   */
+  eventId: string | null;
+  /**
+   * Path relative to the repository root, as in diff responses.
+   */
+  path: string;
+};
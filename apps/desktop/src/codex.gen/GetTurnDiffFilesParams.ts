@@ -0,0 +1,35 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ReviewDiffOptions } from './ReviewDiffOptions';
+
+/**
+ * Parameters accepted when fetching hunks for specific files in a range.
+ */
+export type GetTurnDiffFilesParams = {
+  /**
+   * Paths to fetch; either side of a rename matches.
+   */
+  paths: Array<string>;
+  /**
+   * Return hunks for files that would otherwise be collapsed.
+   */
+  expandCollapsed: boolean;
+  /**
+   * Should match the options the file list was fetched with.
+   */
+  options?: ReviewDiffOptions;
+  conversationId: string;
+  baseEventId: string | null;
+  /**
+   * Git revision to compare from instead of a snapshot, e.g. `main` or a
+   * commit SHA. At most one of `base_event_id` and `base_ref` may be set;
+   * the conversation base is used when neither is.
+   */
+  baseRef: string | null;
+  targetEventId: string | null;
+  /**
+   * Git revision to compare against instead of a turn snapshot. Exactly
+   * one of `target_event_id` and `target_ref` must be set.
+   */
+  targetRef: string | null;
+};
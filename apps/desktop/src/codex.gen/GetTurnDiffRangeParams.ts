@@ -1,8 +1,25 @@
 // GENERATED CODE! DO NOT MODIFY BY HAND!
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ReviewDiffOptions } from './ReviewDiffOptions';
 
 export type GetTurnDiffRangeParams = {
+  options?: ReviewDiffOptions;
+  /**
+   * Also return the raw `git diff` text, e.g. to copy the range as a patch.
+   */
+  includeUnifiedDiff?: boolean;
   conversationId: string;
   baseEventId: string | null;
-  targetEventId: string;
+  /**
+   * Git revision to compare from instead of a snapshot, e.g. `main` or a
+   * commit SHA. At most one of `base_event_id` and `base_ref` may be set;
+   * the conversation base is used when neither is.
+   */
+  baseRef: string | null;
+  targetEventId: string | null;
+  /**
+   * Git revision to compare against instead of a turn snapshot. Exactly
+   * one of `target_event_id` and `target_ref` must be set.
+   */
+  targetRef: string | null;
 };
@@ -1,4 +1,15 @@
 // GENERATED CODE! DO NOT MODIFY BY HAND!
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DiffFile } from './DiffFile';
 
-export type GetTurnDiffRangeResponse = { unifiedDiff: string };
+export type GetTurnDiffRangeResponse = {
+  /**
+   * The diff parsed into files, hunks and numbered lines. Files over the
+   * size caps are marked `collapsed` and carry no hunks.
+   */
+  files: Array<DiffFile>;
+  /**
+   * Raw diff text, present only when requested.
+   */
+  unifiedDiff?: string;
+};
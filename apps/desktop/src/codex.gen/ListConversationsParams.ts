@@ -6,4 +6,8 @@ export type ListConversationsParams = {
   cursor: string | null;
   limit: number | null;
   modelProviders: Array<string> | null;
+  /**
+   * List archived conversations too; they are hidden by default.
+   */
+  includeArchived: boolean | null;
 };
@@ -0,0 +1,14 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Parameters accepted when listing drafted review comments.
+ */
+export type ListReviewCommentsParams = {
+  conversationId: string;
+  baseEventId: string | null;
+  /**
+   * Range target; every comment in the conversation is returned when omitted.
+   */
+  targetEventId: string | null;
+};
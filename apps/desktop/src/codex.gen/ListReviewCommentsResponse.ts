@@ -0,0 +1,12 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ReviewComment } from './ReviewComment';
+import type { ReviewSubmission } from './ReviewSubmission';
+
+export type ListReviewCommentsResponse = {
+  comments: Array<ReviewComment>;
+  /**
+   * Every review submitted for the conversation, oldest first.
+   */
+  submissions: Array<ReviewSubmission>;
+};
@@ -4,6 +4,10 @@ import type { TurnSnapshotDescriptor } from './TurnSnapshotDescriptor';
 
 export type ListTurnSnapshotsResponse = {
   disabled: boolean;
+  /**
+   * Why the most recent snapshot capture failed, if it did.
+   */
+  lastError: string | null;
   baseCommitId: string | null;
   snapshots: Array<TurnSnapshotDescriptor>;
 };
@@ -0,0 +1,12 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Parameters accepted when pruning review snapshots.
+ */
+export type PruneReviewSnapshotsParams = {
+  /**
+   * Defaults to the configured retention.
+   */
+  olderThanDays: number | null;
+};
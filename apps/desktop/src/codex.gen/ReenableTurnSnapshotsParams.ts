@@ -0,0 +1,7 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Parameters accepted when turning snapshotting back on for a conversation.
+ */
+export type ReenableTurnSnapshotsParams = { conversationId: string };
@@ -0,0 +1,13 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Parameters accepted when rolling the workspace back to a snapshot.
+ */
+export type RestoreTurnSnapshotParams = {
+  conversationId: string;
+  /**
+   * Turn snapshot to restore; the conversation base is used when omitted.
+   */
+  eventId: string | null;
+};
@@ -0,0 +1,13 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Response returned after restoring the workspace.
+ */
+export type RestoreTurnSnapshotResponse = {
+  restoredCommitId: string;
+  /**
+   * Snapshot of the workspace as it was right before the restore.
+   */
+  safetyCommitId: string;
+};
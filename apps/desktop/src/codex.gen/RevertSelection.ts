@@ -0,0 +1,17 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A change from a review range to undo in the working tree.
+ */
+export type RevertSelection = {
+  /**
+   * Either side of a rename.
+   */
+  path: string;
+  /**
+   * Indexes into the file's `hunks` as returned by `get_turn_diff_range`;
+   * the whole file is reverted when omitted.
+   */
+  hunks?: Array<number> | null;
+};
@@ -0,0 +1,18 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { RevertSelection } from './RevertSelection';
+import type { ReviewDiffOptions } from './ReviewDiffOptions';
+
+/**
+ * Parameters accepted when reverting selected changes from a range.
+ */
+export type RevertTurnChangesParams = {
+  conversationId: string;
+  baseEventId: string | null;
+  targetEventId: string;
+  selections: Array<RevertSelection>;
+  /**
+   * Options of the diff the hunk indexes were taken from.
+   */
+  options?: ReviewDiffOptions;
+};
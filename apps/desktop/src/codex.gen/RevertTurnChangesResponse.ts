@@ -0,0 +1,20 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { RevertConflict } from './RevertConflict';
+
+export type RevertTurnChangesResponse = {
+  /**
+   * Paths written or removed; empty when there were conflicts.
+   */
+  revertedPaths: Array<string>;
+  /**
+   * Selections whose files changed since the range target. Nothing is
+   * reverted when this is non-empty.
+   */
+  conflicts: Array<RevertConflict>;
+  /**
+   * Snapshot taken after the revert, listed as a `revert` entry in the
+   * turn timeline.
+   */
+  snapshotEventId: string | null;
+};
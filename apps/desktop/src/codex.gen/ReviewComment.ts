@@ -0,0 +1,26 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ReviewCommentSide } from './ReviewCommentSide';
+
+/**
+ * Draft review comment attached to a line in a snapshot range.
+ */
+export type ReviewComment = {
+  id: string;
+  baseEventId: string | null;
+  targetEventId: string;
+  filePath: string;
+  side: ReviewCommentSide;
+  line: number;
+  body: string;
+  /**
+   * Text of the commented line when the comment was written.
+   */
+  contextLine: string | null;
+  createdAt: string;
+  updatedAt: string;
+  /**
+   * Submission that sent this comment to the agent, if any.
+   */
+  submissionId?: string | null;
+};
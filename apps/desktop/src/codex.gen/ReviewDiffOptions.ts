@@ -0,0 +1,34 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { WhitespaceMode } from './WhitespaceMode';
+
+/**
+ * Options applied to every file of a review diff. Defaults match plain
+ * `git diff`.
+ */
+export type ReviewDiffOptions = {
+  whitespace: WhitespaceMode;
+  /**
+   * Pair deleted and added files into renames.
+   */
+  detectRenames: boolean;
+  /**
+   * Report added files that were copied from a modified file. Implies
+   * `detect_renames`, as in git.
+   */
+  detectCopies: boolean;
+  /**
+   * Minimum similarity percentage for renames and copies.
+   */
+  similarityThreshold: number;
+  /**
+   * Unchanged lines shown around each change.
+   */
+  contextLines: number;
+  /**
+   * Only include files matching one of these. Plain entries match a file
+   * or everything below a directory; entries containing `*`, `?` or `[`
+   * are globs over the whole path.
+   */
+  pathspecs: Array<string>;
+};
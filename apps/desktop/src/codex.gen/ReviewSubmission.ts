@@ -0,0 +1,13 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Comments from one range that were sent to the agent together.
+ */
+export type ReviewSubmission = {
+  id: string;
+  baseEventId: string | null;
+  targetEventId: string;
+  commentIds: Array<string>;
+  submittedAt: string;
+};
@@ -0,0 +1,15 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type SearchConversationsParams = {
+  query: string;
+  /**
+   * Limit results to conversations started in this workspace.
+   */
+  workspacePath: string | null;
+  limit: number | null;
+  /**
+   * Search archived conversations too; they are hidden by default.
+   */
+  includeArchived: boolean | null;
+};
@@ -0,0 +1,7 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ConversationSearchResult } from './ConversationSearchResult';
+
+export type SearchConversationsResponse = {
+  items: Array<ConversationSearchResult>;
+};
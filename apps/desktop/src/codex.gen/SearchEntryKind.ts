@@ -0,0 +1,7 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Where a snippet was taken from.
+ */
+export type SearchEntryKind = 'user-message' | 'agent-message' | 'reasoning';
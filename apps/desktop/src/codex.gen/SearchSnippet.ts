@@ -0,0 +1,13 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SearchEntryKind } from './SearchEntryKind';
+import type { SearchHighlight } from './SearchHighlight';
+
+/**
+ * Excerpt of the message that best matches a query.
+ */
+export type SearchSnippet = {
+  kind: SearchEntryKind;
+  text: string;
+  highlights: Array<SearchHighlight>;
+};
@@ -0,0 +1,11 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SnapshotCaptureSettings } from './SnapshotCaptureSettings';
+
+/**
+ * Parameters accepted when saving snapshot capture settings.
+ */
+export type SetWorkspaceSnapshotSettingsParams = {
+  workspacePath: string;
+  settings: SnapshotCaptureSettings;
+};
@@ -0,0 +1,23 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Per-workspace rules applied when capturing review snapshots.
+ */
+export type SnapshotCaptureSettings = {
+  /**
+   * Paths or globs left out of snapshots on top of the ignore files, in
+   * the same syntax as diff pathspecs. Tracked files keep their committed
+   * version.
+   */
+  ignoreGlobs?: Array<string>;
+  /**
+   * Files larger than this are left out of snapshots.
+   */
+  maxFileBytes?: bigint | null;
+  /**
+   * Changed files stop being captured once their combined size passes
+   * this, and a warning is raised.
+   */
+  maxTotalBytes?: bigint | null;
+};
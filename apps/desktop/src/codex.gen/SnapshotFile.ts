@@ -0,0 +1,25 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { FileEncoding } from './FileEncoding';
+
+/**
+ * A file read from a snapshot for display.
+ */
+export type SnapshotFile = {
+  path: string;
+  /**
+   * Size in bytes as stored in the snapshot.
+   */
+  size: bigint;
+  binary: boolean;
+  /**
+   * Detected encoding; `None` for binary files.
+   */
+  encoding: FileEncoding | null;
+  /**
+   * Decoded text, left out for binary files and files over
+   * [`MAX_SNAPSHOT_FILE_CONTENT_BYTES`].
+   */
+  content: string | null;
+  tooLarge: boolean;
+};
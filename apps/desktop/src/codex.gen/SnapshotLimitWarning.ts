@@ -0,0 +1,15 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A snapshot that reached the workspace's total size limit and left
+ * changed files out.
+ */
+export type SnapshotLimitWarning = {
+  maxTotalBytes: bigint;
+  skippedCount: number;
+  /**
+   * The first skipped files, at most [`MAX_REPORTED_PATHS`].
+   */
+  skippedPaths: Array<string>;
+};
@@ -0,0 +1,7 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * What produced a snapshot in the turn timeline.
+ */
+export type SnapshotOrigin = 'turn' | 'revert' | 'external';
@@ -0,0 +1,13 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Outcome of pruning the snapshots of inactive conversations.
+ */
+export type SnapshotPruneSummary = {
+  prunedConversationIds: Array<string>;
+  /**
+   * Snapshot refs deleted from workspace repositories.
+   */
+  removedRefs: number;
+};
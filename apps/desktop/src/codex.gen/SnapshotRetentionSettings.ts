@@ -0,0 +1,12 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * How long review snapshots of inactive conversations are kept.
+ */
+export type SnapshotRetentionSettings = {
+  /**
+   * Snapshots are kept forever when unset.
+   */
+  retentionDays: number | null;
+};
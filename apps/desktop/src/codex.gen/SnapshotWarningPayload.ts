@@ -0,0 +1,11 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SnapshotLimitWarning } from './SnapshotLimitWarning';
+
+/**
+ * Warning raised while capturing a conversation's review snapshots.
+ */
+export type SnapshotWarningPayload = {
+  conversationId: string;
+  warning: SnapshotLimitWarning;
+};
@@ -0,0 +1,15 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Parameters accepted when sending the drafted comments for a range to the agent.
+ */
+export type SubmitTurnReviewParams = {
+  conversationId: string;
+  baseEventId: string | null;
+  targetEventId: string;
+  /**
+   * Turn number used in the message heading.
+   */
+  turnNumber: number | null;
+};
@@ -0,0 +1,11 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ReviewSubmission } from './ReviewSubmission';
+
+export type SubmitTurnReviewResponse = {
+  submission: ReviewSubmission;
+  /**
+   * Message that was sent to the agent.
+   */
+  message: string;
+};
@@ -0,0 +1,19 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Consecutive lines last changed by the same snapshot.
+ */
+export type TurnBlameHunk = {
+  /**
+   * First line of the hunk, 1-based.
+   */
+  startLine: number;
+  lineCount: number;
+  /**
+   * Snapshot that last changed these lines; `None` when they are
+   * unchanged since the conversation base.
+   */
+  eventId: string | null;
+  turnIndex: number | null;
+};
@@ -0,0 +1,11 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Size of the change a turn made relative to the snapshot before it.
+ */
+export type TurnChangeStats = {
+  filesChanged: number;
+  insertions: number;
+  deletions: number;
+};
@@ -1,4 +1,26 @@
 // GENERATED CODE! DO NOT MODIFY BY HAND!
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SnapshotOrigin } from './SnapshotOrigin';
+import type { TurnChangeStats } from './TurnChangeStats';
 
-export type TurnSnapshotDescriptor = { eventId: string; commitId: string };
+/**
+ * Turn snapshot entry, listed in capture order.
+ */
+export type TurnSnapshotDescriptor = {
+  eventId: string;
+  commitId: string;
+  /**
+   * 1-based user turn that produced the snapshot.
+   */
+  turnIndex: number;
+  capturedAt: string;
+  /**
+   * User prompt that started the turn, shortened for display.
+   */
+  prompt: string | null;
+  /**
+   * Changes relative to the previous snapshot.
+   */
+  stats: TurnChangeStats | null;
+  origin: SnapshotOrigin;
+};
@@ -0,0 +1,11 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Parameters accepted when editing a drafted review comment.
+ */
+export type UpdateReviewCommentParams = {
+  conversationId: string;
+  commentId: string;
+  body: string;
+};
@@ -0,0 +1,11 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * How whitespace differences are treated, mirroring the `git diff` flags.
+ */
+export type WhitespaceMode =
+  | 'show'
+  | 'ignore-at-eol'
+  | 'ignore-change'
+  | 'ignore-all';
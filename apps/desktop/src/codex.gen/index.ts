@@ -1,6 +1,7 @@
 // GENERATED CODE! DO NOT MODIFY BY HAND!
 export type { AddConversationListenerParams } from './AddConversationListenerParams';
 export type { AddConversationSubscriptionResponse } from './AddConversationSubscriptionResponse';
+export type { AddReviewCommentParams } from './AddReviewCommentParams';
 export type { AgentMessageContent } from './AgentMessageContent';
 export type { AgentMessageContentDeltaEvent } from './AgentMessageContentDeltaEvent';
 export type { AgentMessageDeltaEvent } from './AgentMessageDeltaEvent';
@@ -26,12 +27,28 @@ export type { CompactConversationParams } from './CompactConversationParams';
 export type { ComposerTurnConfigPayload } from './ComposerTurnConfigPayload';
 export type { ContentBlock } from './ContentBlock';
 export type { ContentItem } from './ContentItem';
+export type { ConversationBatchFailure } from './ConversationBatchFailure';
+export type { ConversationBatchParams } from './ConversationBatchParams';
+export type { ConversationBatchResponse } from './ConversationBatchResponse';
 export type { ConversationEventPayload } from './ConversationEventPayload';
 export type { ConversationId } from './ConversationId';
+export type { ConversationSearchResult } from './ConversationSearchResult';
 export type { ConversationSummary } from './ConversationSummary';
+export type { CreateTurnBranchParams } from './CreateTurnBranchParams';
+export type { CreateTurnBranchResponse } from './CreateTurnBranchResponse';
 export type { CreditsSnapshot } from './CreditsSnapshot';
 export type { CustomPrompt } from './CustomPrompt';
+export type { DeleteConversationsParams } from './DeleteConversationsParams';
+export type { DeleteReviewCommentParams } from './DeleteReviewCommentParams';
 export type { DeprecationNoticeEvent } from './DeprecationNoticeEvent';
+export type { DiffChangeKind } from './DiffChangeKind';
+export type { DiffFile } from './DiffFile';
+export type { DiffFileSummary } from './DiffFileSummary';
+export type { DiffHunk } from './DiffHunk';
+export type { DiffLine } from './DiffLine';
+export type { DiffLineKind } from './DiffLineKind';
+export type { DiffSpan } from './DiffSpan';
+export type { EditUserMessageParams } from './EditUserMessageParams';
 export type { EmbeddedResource } from './EmbeddedResource';
 export type { EmbeddedResourceResource } from './EmbeddedResourceResource';
 export type { ErrorEvent } from './ErrorEvent';
@@ -43,11 +60,24 @@ export type { ExecCommandOutputDeltaEvent } from './ExecCommandOutputDeltaEvent'
 export type { ExecCommandSource } from './ExecCommandSource';
 export type { ExecOutputStream } from './ExecOutputStream';
 export type { ExitedReviewModeEvent } from './ExitedReviewModeEvent';
+export type { ExportTurnPatchParams } from './ExportTurnPatchParams';
+export type { ExportTurnPatchResponse } from './ExportTurnPatchResponse';
+export type { ExportedTurnCommit } from './ExportedTurnCommit';
 export type { FileChange } from './FileChange';
+export type { FileEncoding } from './FileEncoding';
+export type { FileTurnBlame } from './FileTurnBlame';
+export type { ForkConversationParams } from './ForkConversationParams';
+export type { ForkConversationResponse } from './ForkConversationResponse';
 export type { FunctionCallOutputContentItem } from './FunctionCallOutputContentItem';
 export type { FunctionCallOutputPayload } from './FunctionCallOutputPayload';
 export type { GetComposerConfigParams } from './GetComposerConfigParams';
+export type { GetFileAtSnapshotParams } from './GetFileAtSnapshotParams';
+export type { GetFileAtSnapshotResponse } from './GetFileAtSnapshotResponse';
 export type { GetHistoryEntryResponseEvent } from './GetHistoryEntryResponseEvent';
+export type { GetTurnBlameParams } from './GetTurnBlameParams';
+export type { GetTurnBlameResponse } from './GetTurnBlameResponse';
+export type { GetTurnDiffFilesParams } from './GetTurnDiffFilesParams';
+export type { GetTurnDiffFilesResponse } from './GetTurnDiffFilesResponse';
 export type { GetTurnDiffRangeParams } from './GetTurnDiffRangeParams';
 export type { GetTurnDiffRangeResponse } from './GetTurnDiffRangeResponse';
 export type { GhostCommit } from './GhostCommit';
@@ -63,6 +93,10 @@ export type { ItemStartedEvent } from './ItemStartedEvent';
 export type { ListConversationsParams } from './ListConversationsParams';
 export type { ListConversationsResponse } from './ListConversationsResponse';
 export type { ListCustomPromptsResponseEvent } from './ListCustomPromptsResponseEvent';
+export type { ListReviewCommentsParams } from './ListReviewCommentsParams';
+export type { ListReviewCommentsResponse } from './ListReviewCommentsResponse';
+export type { ListTurnDiffFilesParams } from './ListTurnDiffFilesParams';
+export type { ListTurnDiffFilesResponse } from './ListTurnDiffFilesResponse';
 export type { ListTurnSnapshotsParams } from './ListTurnSnapshotsParams';
 export type { ListTurnSnapshotsResponse } from './ListTurnSnapshotsResponse';
 export type { LocalShellAction } from './LocalShellAction';
@@ -84,6 +118,7 @@ export type { ParsedCommand } from './ParsedCommand';
 export type { PatchApplyBeginEvent } from './PatchApplyBeginEvent';
 export type { PatchApplyEndEvent } from './PatchApplyEndEvent';
 export type { PlanItemArg } from './PlanItemArg';
+export type { PruneReviewSnapshotsParams } from './PruneReviewSnapshotsParams';
 export type { RateLimitSnapshot } from './RateLimitSnapshot';
 export type { RateLimitWindow } from './RateLimitWindow';
 export type { RawResponseItemEvent } from './RawResponseItemEvent';
@@ -94,27 +129,53 @@ export type { ReasoningItemContent } from './ReasoningItemContent';
 export type { ReasoningItemReasoningSummary } from './ReasoningItemReasoningSummary';
 export type { ReasoningRawContentDeltaEvent } from './ReasoningRawContentDeltaEvent';
 export type { ReasoningSummary } from './ReasoningSummary';
+export type { ReenableTurnSnapshotsParams } from './ReenableTurnSnapshotsParams';
 export type { RemoveConversationListenerParams } from './RemoveConversationListenerParams';
 export type { Resource } from './Resource';
 export type { ResourceLink } from './ResourceLink';
 export type { ResourceTemplate } from './ResourceTemplate';
 export type { RespondApprovalParams } from './RespondApprovalParams';
 export type { ResponseItem } from './ResponseItem';
+export type { RestoreTurnSnapshotParams } from './RestoreTurnSnapshotParams';
+export type { RestoreTurnSnapshotResponse } from './RestoreTurnSnapshotResponse';
+export type { RevertConflict } from './RevertConflict';
+export type { RevertSelection } from './RevertSelection';
+export type { RevertTurnChangesParams } from './RevertTurnChangesParams';
+export type { RevertTurnChangesResponse } from './RevertTurnChangesResponse';
 export type { ReviewCodeLocation } from './ReviewCodeLocation';
+export type { ReviewComment } from './ReviewComment';
+export type { ReviewCommentSide } from './ReviewCommentSide';
+export type { ReviewDiffOptions } from './ReviewDiffOptions';
 export type { ReviewFinding } from './ReviewFinding';
 export type { ReviewLineRange } from './ReviewLineRange';
 export type { ReviewOutputEvent } from './ReviewOutputEvent';
 export type { ReviewRequest } from './ReviewRequest';
+export type { ReviewSubmission } from './ReviewSubmission';
 export type { Role } from './Role';
 export type { SandboxCommandAssessment } from './SandboxCommandAssessment';
 export type { SandboxMode } from './SandboxMode';
 export type { SandboxPolicy } from './SandboxPolicy';
 export type { SandboxRiskLevel } from './SandboxRiskLevel';
+export type { SearchConversationsParams } from './SearchConversationsParams';
+export type { SearchConversationsResponse } from './SearchConversationsResponse';
+export type { SearchEntryKind } from './SearchEntryKind';
+export type { SearchHighlight } from './SearchHighlight';
+export type { SearchSnippet } from './SearchSnippet';
 export type { SendUserMessageParams } from './SendUserMessageParams';
 export type { SessionConfiguredEvent } from './SessionConfiguredEvent';
 export type { SetWindowTitleParams } from './SetWindowTitleParams';
+export type { SetWorkspaceSnapshotSettingsParams } from './SetWorkspaceSnapshotSettingsParams';
+export type { SnapshotCaptureSettings } from './SnapshotCaptureSettings';
+export type { SnapshotFile } from './SnapshotFile';
+export type { SnapshotLimitWarning } from './SnapshotLimitWarning';
+export type { SnapshotOrigin } from './SnapshotOrigin';
+export type { SnapshotPruneSummary } from './SnapshotPruneSummary';
+export type { SnapshotRetentionSettings } from './SnapshotRetentionSettings';
+export type { SnapshotWarningPayload } from './SnapshotWarningPayload';
 export type { StepStatus } from './StepStatus';
 export type { StreamErrorEvent } from './StreamErrorEvent';
+export type { SubmitTurnReviewParams } from './SubmitTurnReviewParams';
+export type { SubmitTurnReviewResponse } from './SubmitTurnReviewResponse';
 export type { TaskCompleteEvent } from './TaskCompleteEvent';
 export type { TaskStartedEvent } from './TaskStartedEvent';
 export type { TextContent } from './TextContent';
@@ -128,6 +189,8 @@ export type { ToolInputSchema } from './ToolInputSchema';
 export type { ToolOutputSchema } from './ToolOutputSchema';
 export type { TurnAbortReason } from './TurnAbortReason';
 export type { TurnAbortedEvent } from './TurnAbortedEvent';
+export type { TurnBlameHunk } from './TurnBlameHunk';
+export type { TurnChangeStats } from './TurnChangeStats';
 export type { TurnDiffEvent } from './TurnDiffEvent';
 export type { TurnItem } from './TurnItem';
 export type { TurnSnapshotDescriptor } from './TurnSnapshotDescriptor';
@@ -135,6 +198,7 @@ export type { UndoCompletedEvent } from './UndoCompletedEvent';
 export type { UndoStartedEvent } from './UndoStartedEvent';
 export type { UpdateComposerConfigParams } from './UpdateComposerConfigParams';
 export type { UpdatePlanArgs } from './UpdatePlanArgs';
+export type { UpdateReviewCommentParams } from './UpdateReviewCommentParams';
 export type { UserInput } from './UserInput';
 export type { UserMessageEvent } from './UserMessageEvent';
 export type { UserMessageItem } from './UserMessageItem';
@@ -144,5 +208,6 @@ export type { WebSearchAction } from './WebSearchAction';
 export type { WebSearchBeginEvent } from './WebSearchBeginEvent';
 export type { WebSearchEndEvent } from './WebSearchEndEvent';
 export type { WebSearchItem } from './WebSearchItem';
+export type { WhitespaceMode } from './WhitespaceMode';
 export type { WorkspaceComposerDefaults } from './WorkspaceComposerDefaults';
 export type { WorkspacePathParams } from './WorkspacePathParams';
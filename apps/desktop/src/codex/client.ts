@@ -3,10 +3,28 @@ import { invoke } from '@tauri-apps/api/core';
 import type {
   AddConversationListenerParams,
   AddConversationSubscriptionResponse,
+  AddReviewCommentParams,
   AuthState,
   CompactConversationParams,
   ComposerTurnConfigPayload,
+  ConversationBatchParams,
+  ConversationBatchResponse,
+  CreateTurnBranchParams,
+  CreateTurnBranchResponse,
+  DeleteConversationsParams,
+  DeleteReviewCommentParams,
+  EditUserMessageParams,
+  ExportTurnPatchParams,
+  ExportTurnPatchResponse,
+  ForkConversationParams,
+  ForkConversationResponse,
   GetComposerConfigParams,
+  GetFileAtSnapshotParams,
+  GetFileAtSnapshotResponse,
+  GetTurnBlameParams,
+  GetTurnBlameResponse,
+  GetTurnDiffFilesParams,
+  GetTurnDiffFilesResponse,
   GetTurnDiffRangeParams,
   GetTurnDiffRangeResponse,
   InitializeConversationParams,
@@ -15,15 +33,35 @@ import type {
   InterruptConversationResponse,
   ListConversationsParams,
   ListConversationsResponse,
+  ListReviewCommentsParams,
+  ListReviewCommentsResponse,
+  ListTurnDiffFilesParams,
+  ListTurnDiffFilesResponse,
   ListTurnSnapshotsParams,
   ListTurnSnapshotsResponse,
   NewConversationCommandParams,
   NewConversationResponse,
+  PruneReviewSnapshotsParams,
+  ReenableTurnSnapshotsParams,
   RemoveConversationListenerParams,
   RespondApprovalParams,
+  RestoreTurnSnapshotParams,
+  RestoreTurnSnapshotResponse,
+  RevertTurnChangesParams,
+  RevertTurnChangesResponse,
+  ReviewComment,
+  SearchConversationsParams,
+  SearchConversationsResponse,
   SendUserMessageParams,
   SetWindowTitleParams,
+  SetWorkspaceSnapshotSettingsParams,
+  SnapshotCaptureSettings,
+  SnapshotPruneSummary,
+  SnapshotRetentionSettings,
+  SubmitTurnReviewParams,
+  SubmitTurnReviewResponse,
   UpdateComposerConfigParams,
+  UpdateReviewCommentParams,
   WorkspaceComposerDefaults,
   WorkspacePathParams,
 } from '~/codex.gen';
@@ -37,6 +75,38 @@ export namespace Codex {
     });
   }
 
+  export async function searchConversations(
+    params: SearchConversationsParams
+  ): Promise<SearchConversationsResponse> {
+    return await invoke<SearchConversationsResponse>('search_conversations', {
+      params,
+    });
+  }
+
+  export async function archiveConversations(
+    params: ConversationBatchParams
+  ): Promise<ConversationBatchResponse> {
+    return await invoke<ConversationBatchResponse>('archive_conversations', {
+      params,
+    });
+  }
+
+  export async function unarchiveConversations(
+    params: ConversationBatchParams
+  ): Promise<ConversationBatchResponse> {
+    return await invoke<ConversationBatchResponse>('unarchive_conversations', {
+      params,
+    });
+  }
+
+  export async function deleteConversations(
+    params: DeleteConversationsParams
+  ): Promise<ConversationBatchResponse> {
+    return await invoke<ConversationBatchResponse>('delete_conversations', {
+      params,
+    });
+  }
+
   export async function initializeConversation(
     params: InitializeConversationParams
   ): Promise<InitializeConversationResponse> {
@@ -54,6 +124,22 @@ export namespace Codex {
     });
   }
 
+  export async function forkConversation(
+    params: ForkConversationParams
+  ): Promise<ForkConversationResponse> {
+    return await invoke<ForkConversationResponse>('fork_conversation', {
+      params,
+    });
+  }
+
+  export async function editUserMessage(
+    params: EditUserMessageParams
+  ): Promise<ForkConversationResponse> {
+    return await invoke<ForkConversationResponse>('edit_user_message', {
+      params,
+    });
+  }
+
   export async function sendUserMessage(
     params: SendUserMessageParams
   ): Promise<void> {
@@ -68,6 +154,22 @@ export namespace Codex {
     });
   }
 
+  export async function listTurnDiffFiles(
+    params: ListTurnDiffFilesParams
+  ): Promise<ListTurnDiffFilesResponse> {
+    return await invoke<ListTurnDiffFilesResponse>('list_turn_diff_files', {
+      params,
+    });
+  }
+
+  export async function getTurnDiffFiles(
+    params: GetTurnDiffFilesParams
+  ): Promise<GetTurnDiffFilesResponse> {
+    return await invoke<GetTurnDiffFilesResponse>('get_turn_diff_files', {
+      params,
+    });
+  }
+
   export async function listTurnSnapshots(
     params: ListTurnSnapshotsParams
   ): Promise<ListTurnSnapshotsResponse> {
@@ -76,6 +178,112 @@ export namespace Codex {
     });
   }
 
+  export async function reenableTurnSnapshots(
+    params: ReenableTurnSnapshotsParams
+  ): Promise<ListTurnSnapshotsResponse> {
+    return await invoke<ListTurnSnapshotsResponse>('reenable_turn_snapshots', {
+      params,
+    });
+  }
+
+  export async function restoreTurnSnapshot(
+    params: RestoreTurnSnapshotParams
+  ): Promise<RestoreTurnSnapshotResponse> {
+    return await invoke<RestoreTurnSnapshotResponse>('restore_turn_snapshot', {
+      params,
+    });
+  }
+
+  export async function getFileAtSnapshot(
+    params: GetFileAtSnapshotParams
+  ): Promise<GetFileAtSnapshotResponse> {
+    return await invoke<GetFileAtSnapshotResponse>('get_file_at_snapshot', {
+      params,
+    });
+  }
+
+  export async function getTurnBlame(
+    params: GetTurnBlameParams
+  ): Promise<GetTurnBlameResponse> {
+    return await invoke<GetTurnBlameResponse>('get_turn_blame', { params });
+  }
+
+  export async function getSnapshotRetention(): Promise<SnapshotRetentionSettings> {
+    return await invoke<SnapshotRetentionSettings>('get_snapshot_retention');
+  }
+
+  export async function setSnapshotRetention(
+    params: SnapshotRetentionSettings
+  ): Promise<void> {
+    return await invoke<void>('set_snapshot_retention', { params });
+  }
+
+  export async function pruneReviewSnapshots(
+    params: PruneReviewSnapshotsParams
+  ): Promise<SnapshotPruneSummary> {
+    return await invoke<SnapshotPruneSummary>('prune_review_snapshots', {
+      params,
+    });
+  }
+
+  export async function revertTurnChanges(
+    params: RevertTurnChangesParams
+  ): Promise<RevertTurnChangesResponse> {
+    return await invoke<RevertTurnChangesResponse>('revert_turn_changes', {
+      params,
+    });
+  }
+
+  export async function exportTurnPatch(
+    params: ExportTurnPatchParams
+  ): Promise<ExportTurnPatchResponse> {
+    return await invoke<ExportTurnPatchResponse>('export_turn_patch', {
+      params,
+    });
+  }
+
+  export async function createTurnBranch(
+    params: CreateTurnBranchParams
+  ): Promise<CreateTurnBranchResponse> {
+    return await invoke<CreateTurnBranchResponse>('create_turn_branch', {
+      params,
+    });
+  }
+
+  export async function addReviewComment(
+    params: AddReviewCommentParams
+  ): Promise<ReviewComment> {
+    return await invoke<ReviewComment>('add_review_comment', { params });
+  }
+
+  export async function updateReviewComment(
+    params: UpdateReviewCommentParams
+  ): Promise<ReviewComment> {
+    return await invoke<ReviewComment>('update_review_comment', { params });
+  }
+
+  export async function deleteReviewComment(
+    params: DeleteReviewCommentParams
+  ): Promise<void> {
+    return await invoke<void>('delete_review_comment', { params });
+  }
+
+  export async function listReviewComments(
+    params: ListReviewCommentsParams
+  ): Promise<ListReviewCommentsResponse> {
+    return await invoke<ListReviewCommentsResponse>('list_review_comments', {
+      params,
+    });
+  }
+
+  export async function submitTurnReview(
+    params: SubmitTurnReviewParams
+  ): Promise<SubmitTurnReviewResponse> {
+    return await invoke<SubmitTurnReviewResponse>('submit_turn_review', {
+      params,
+    });
+  }
+
   export async function compactConversation(
     params: CompactConversationParams
   ): Promise<void> {
@@ -135,6 +343,21 @@ export namespace Codex {
     );
   }
 
+  export async function getWorkspaceSnapshotSettings(
+    params: WorkspacePathParams
+  ): Promise<SnapshotCaptureSettings> {
+    return await invoke<SnapshotCaptureSettings>(
+      'get_workspace_snapshot_settings',
+      { params }
+    );
+  }
+
+  export async function setWorkspaceSnapshotSettings(
+    params: SetWorkspaceSnapshotSettingsParams
+  ): Promise<void> {
+    return await invoke<void>('set_workspace_snapshot_settings', { params });
+  }
+
   export async function createWorkspaceWindow(
     params: WorkspacePathParams
   ): Promise<void> {
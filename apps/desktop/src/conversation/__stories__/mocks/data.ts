@@ -345,6 +345,8 @@ export const sampleConversationSummaries: ConversationSummary[] = [
     cwd: MOCK_WORKSPACE_PATH,
     preview: 'Fix turn review cumulative diff bug',
     timestamp: iso('2025-11-02T21:46:00Z'),
+    lastActivity: iso('2025-11-02T21:46:00Z'),
+    archived: false,
   },
   {
     conversationId: 'session-transcript-refactor',
@@ -352,6 +354,8 @@ export const sampleConversationSummaries: ConversationSummary[] = [
     cwd: MOCK_WORKSPACE_PATH,
     preview: 'Refactor transcript cells to use primitives',
     timestamp: iso('2025-11-02T17:30:00Z'),
+    lastActivity: iso('2025-11-02T17:30:00Z'),
+    archived: false,
   },
 ];
 
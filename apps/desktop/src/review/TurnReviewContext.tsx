@@ -10,6 +10,7 @@ import {
   useState,
 } from 'react';
 import type {
+  DiffFile,
   GetTurnDiffRangeParams,
   ListTurnSnapshotsResponse,
 } from '~/codex.gen';
@@ -17,6 +18,7 @@ import { Codex } from '~/codex/client';
 import type { TranscriptTurnDiff } from '~/conversation/transcript/types';
 
 import { parseUnifiedDiff } from './diff-parser';
+import { toParsedTurnDiff } from './diff-utils';
 import type {
   ParsedTurnDiff,
   ParsedTurnDiffFile,
@@ -27,7 +29,7 @@ import type {
 } from './types';
 
 type DiffCacheEntry = {
-  raw: string;
+  signature: string;
   parsed: ParsedTurnDiff | null;
   resolved: boolean;
 };
@@ -35,7 +37,8 @@ type DiffCacheEntry = {
 type RangeDiffFetchResult =
   | {
       kind: 'success';
-      diff: string;
+      files: DiffFile[];
+      signature: string;
     }
   | {
       kind: 'error';
@@ -141,7 +144,7 @@ export function TurnReviewProvider({
 
   const snapshotRequestRef = useRef(0);
   const lastSnapshotRefreshKeyRef = useRef<string | null>(null);
-  const lastSnapshotRefreshRawRef = useRef<string | null>(null);
+  const lastSnapshotRefreshSignatureRef = useRef<string | null>(null);
   const autoFollowingTurnNumber = useRef<number | null>(null);
 
   const refreshSnapshots = useCallback(async () => {
@@ -161,6 +164,7 @@ export function TurnReviewProvider({
       if (snapshotRequestRef.current === requestId) {
         setSnapshotData({
           disabled: true,
+          lastError: null,
           baseCommitId: null,
           snapshots: [],
         });
@@ -179,7 +183,7 @@ export function TurnReviewProvider({
     setTargetTurnId(null);
     setSelectedFileId(null);
     lastSnapshotRefreshKeyRef.current = null;
-    lastSnapshotRefreshRawRef.current = null;
+    lastSnapshotRefreshSignatureRef.current = null;
     autoFollowingTurnNumber.current = null;
   }, [conversationId]);
 
@@ -288,7 +292,9 @@ export function TurnReviewProvider({
     return {
       conversationId,
       baseEventId: baseTurnId,
+      baseRef: null,
       targetEventId: targetTurnId,
+      targetRef: null,
     };
   }, [baseTurnId, conversationId, snapshotDisabled, targetTurnId]);
 
@@ -321,7 +327,8 @@ export function TurnReviewProvider({
         if (!cancelled) {
           setRangeDiffResult({
             kind: 'success',
-            diff: response.unifiedDiff ?? '',
+            files: response.files,
+            signature: hashString(JSON.stringify(response.files)),
           });
         }
       } catch (error) {
@@ -388,18 +395,18 @@ export function TurnReviewProvider({
       if (
         existing &&
         existing.resolved &&
-        existing.raw === rangeDiffResult.diff
+        existing.signature === rangeDiffResult.signature
       ) {
         return prev;
       }
       const parsed =
-        rangeDiffResult.diff.trim().length > 0
-          ? parseUnifiedDiff(rangeDiffResult.diff)
+        rangeDiffResult.files.length > 0
+          ? toParsedTurnDiff(rangeDiffResult.files)
           : null;
       return {
         ...prev,
         [diffRevisionKey]: {
-          raw: rangeDiffResult.diff,
+          signature: rangeDiffResult.signature,
           parsed,
           resolved: true,
         },
@@ -416,12 +423,12 @@ export function TurnReviewProvider({
     }
     if (
       lastSnapshotRefreshKeyRef.current === rangeKey &&
-      lastSnapshotRefreshRawRef.current === rangeDiffResult.diff
+      lastSnapshotRefreshSignatureRef.current === rangeDiffResult.signature
     ) {
       return;
     }
     lastSnapshotRefreshKeyRef.current = rangeKey;
-    lastSnapshotRefreshRawRef.current = rangeDiffResult.diff;
+    lastSnapshotRefreshSignatureRef.current = rangeDiffResult.signature;
     void refreshSnapshots();
   }, [rangeDiffResult, rangeKey, refreshSnapshots, snapshotDisabled]);
 
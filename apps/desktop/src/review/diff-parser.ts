@@ -46,6 +46,11 @@ const normalizePath = (input: string | undefined): string | null => {
   return input.replace(/^[ab]\//, '');
 };
 
+/**
+ * @deprecated Only parses the raw turn diffs carried by transcript events.
+ * Snapshot ranges come parsed from `getTurnDiffRange`; remove this once
+ * transcript diffs do too.
+ */
 export const parseUnifiedDiff = (input: string): ParsedTurnDiff => {
   const files: ParsedTurnDiffFile[] = [];
   if (!input.trim()) {
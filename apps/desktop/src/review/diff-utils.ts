@@ -1,4 +1,7 @@
+import type { DiffFile } from '~/codex.gen';
+
 import type {
+  ParsedTurnDiff,
   ParsedTurnDiffFile,
   ParsedTurnDiffLine,
   TurnReviewComment,
@@ -21,6 +24,57 @@ export const formatTurnTimestamp = (value: string): string => {
   }).format(date);
 };
 
+const linePrefix = (kind: ParsedTurnDiffLine['kind']): string => {
+  switch (kind) {
+    case 'addition':
+      return '+';
+    case 'removal':
+      return '-';
+    case 'context':
+      return ' ';
+    default:
+      return '';
+  }
+};
+
+export const toParsedTurnDiff = (
+  files: readonly DiffFile[]
+): ParsedTurnDiff => {
+  let lineCounter = 0;
+  return {
+    files: files.map((file, fileIndex) => {
+      const fileId = `file-${fileIndex}`;
+      return {
+        id: fileId,
+        oldPath: file.oldPath,
+        newPath: file.newPath,
+        displayPath: file.displayPath,
+        hunks: file.hunks.map((hunk, hunkIndex) => {
+          const hunkId = `${fileId}-h${hunkIndex}`;
+          return {
+            id: hunkId,
+            header: hunk.header,
+            oldRange: { start: hunk.oldStart, length: hunk.oldLines },
+            newRange: { start: hunk.newStart, length: hunk.newLines },
+            lines: hunk.lines.map((line) => {
+              const id = `${hunkId}-l${lineCounter}`;
+              lineCounter += 1;
+              return {
+                id,
+                kind: line.kind,
+                text: line.text,
+                oldNumber: line.oldNumber,
+                newNumber: line.newNumber,
+                prefix: linePrefix(line.kind),
+              };
+            }),
+          };
+        }),
+      };
+    }),
+  };
+};
+
 export const buildFileDiffStats = (
   files: ParsedTurnDiffFile[]
 ): Map<string, { added: number; removed: number }> => {
@@ -51,6 +51,8 @@ const createDefaultConversationSummary = (): ConversationSummary => ({
   cwd: '/tmp',
   preview: '',
   timestamp: new Date().toISOString(),
+  lastActivity: null,
+  archived: false,
 });
 
 const createDefaultConversationListResponse = () => ({
@@ -124,6 +126,7 @@ const mockCodexNamespace = {
   listTurnSnapshots: defineStub(
     async (): Promise<ListTurnSnapshotsResponse> => ({
       disabled: true,
+      lastError: null,
       baseCommitId: null,
       snapshots: [],
     })
@@ -149,7 +152,7 @@ const mockCodexNamespace = {
   respondPatchApproval: defineStub(async () => undefined),
   getTurnDiffRange: defineStub(
     async (): Promise<GetTurnDiffRangeResponse> => ({
-      unifiedDiff: '',
+      files: [],
     })
   ),
   getAuthState: defineStub(async () => createDefaultAuthState()),
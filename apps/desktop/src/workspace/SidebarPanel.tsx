@@ -90,6 +90,8 @@ export function SidebarPanel() {
         cwd: normalizedWorkspacePath || workspacePath,
         preview: 'New session',
         timestamp: new Date().toISOString(),
+        lastActivity: null,
+        archived: false,
       };
 
       queryClient.setQueryData<WorkspaceConversationsState | undefined>(
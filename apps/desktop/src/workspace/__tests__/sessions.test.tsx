@@ -60,6 +60,8 @@ describe('SidebarPanel sessions', () => {
           cwd: WORKSPACE,
           preview: 'Existing session',
           timestamp: now,
+          lastActivity: now,
+          archived: false,
         },
       ],
       nextCursor: null,
@@ -112,6 +114,8 @@ describe('SidebarPanel sessions', () => {
           cwd: WORKSPACE,
           preview: 'Existing session',
           timestamp: now,
+          lastActivity: now,
+          archived: false,
         },
       ],
       nextCursor: null,
@@ -171,6 +175,8 @@ describe('SidebarPanel sessions', () => {
         cwd: WORKSPACE,
         preview: `Session ${index}`,
         timestamp: now,
+        lastActivity: now,
+        archived: false,
       };
     });
 
@@ -182,6 +188,8 @@ describe('SidebarPanel sessions', () => {
         cwd: WORKSPACE,
         preview: `Next ${index}`,
         timestamp: now,
+        lastActivity: now,
+        archived: false,
       };
     });
 
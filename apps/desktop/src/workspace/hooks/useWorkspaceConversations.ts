@@ -34,6 +34,7 @@ export const useWorkspaceConversations = () => {
         cursor: null,
         limit: PAGE_SIZE,
         modelProviders: null,
+        includeArchived: null,
       });
 
       const filtered = filterSummariesForWorkspace(
@@ -66,6 +67,7 @@ export const useWorkspaceConversations = () => {
         cursor,
         limit: PAGE_SIZE,
         modelProviders: null,
+        includeArchived: null,
       });
 
       const filtered = filterSummariesForWorkspace(